@@ -0,0 +1,57 @@
+//! Times the `*_simd` methods against the generic implementations they mirror.
+//!
+//! Run with `cargo run --release --example simd`.
+extern crate rla;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use rla::vector::*;
+use rla::matrix::*;
+use rla::unit::Radians;
+
+const ITERATIONS: u32 = 10_000_000;
+
+fn time<R, F>(mut f: F) -> Duration
+    where F: FnMut() -> R
+{
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    start.elapsed()
+}
+
+fn compare<R, S, G>(name: &str, scalar: S, simd: G)
+    where S: FnMut() -> R,
+          G: FnMut() -> R
+{
+    let (scalar, simd) = (time(scalar), time(simd));
+    let per_call = |d: Duration| d.as_secs_f64() * 1e9 / ITERATIONS as f64;
+    println!("{:<12} scalar {:>6.2} ns  simd {:>6.2} ns  speedup {:.2}x",
+             name,
+             per_call(scalar),
+             per_call(simd),
+             scalar.as_secs_f64() / simd.as_secs_f64());
+}
+
+fn main() {
+    let m = Mat4x4f::translate(Vec3f::new(1., -2., 3.)) * Mat4x4f::rotation_y(Radians::new(0.8));
+    let n = Mat4x4f::scale(Vec3f::new(2., 0.5, 4.)) * Mat4x4f::rotation_x(Radians::new(0.3));
+    let v4 = Vec4f::new(1., 2., 3., 4.);
+    let (a, b) = (Vec3f::new(1., 2., 3.), Vec3f::new(-0.5, 0.25, 8.));
+
+    compare("mat4 * mat4", || black_box(m) * black_box(n), || black_box(m).mul_simd(&black_box(n)));
+    compare("mat4 * vec4",
+            || black_box(m).mul_v(black_box(v4)),
+            || black_box(m).mul_v_simd(black_box(v4)));
+    compare("mat4 inverse",
+            || black_box(m).try_inverse(),
+            || black_box(m).try_inverse_simd());
+    compare("vec4 dot",
+            || black_box(v4).dot(black_box(v4)),
+            || black_box(v4).dot_simd(black_box(v4)));
+    compare("vec3 add", || black_box(a) + black_box(b), || black_box(a).add_simd(black_box(b)));
+    compare("vec3 cross",
+            || black_box(a).cross(black_box(b)),
+            || black_box(a).cross_simd(black_box(b)));
+}
@@ -1,31 +1,39 @@
 #![allow(dead_code)]
-use vector::Vec3;
-use num::Float;
-use num::FromPrimitive;
+use vector::{Vec3, Point3, Normal3};
+use ray::{Ray, RayHit};
+use num::{Float, NumCast};
 
-#[derive(Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Aabb<T>
     where T: Float
 {
-    min: Vec3<T>,
-    max: Vec3<T>,
+    min: Point3<T>,
+    max: Point3<T>,
 }
 
 impl<T> Aabb<T>
     where T: Float
 {
-    pub fn new(min: Vec3<T>, max: Vec3<T>) -> Self {
+    pub fn new(min: Point3<T>, max: Point3<T>) -> Self {
         Aabb {
-            min: min,
-            max: max,
+            min,
+            max,
         }
     }
 
-    pub fn single(single_point: Vec3<T>) -> Self {
+    pub fn min(&self) -> Point3<T> {
+        self.min
+    }
+
+    pub fn max(&self) -> Point3<T> {
+        self.max
+    }
+
+    pub fn single(single_point: Point3<T>) -> Self {
         Aabb::new(single_point, single_point)
     }
 
-    pub fn union_point(&self, p: Vec3<T>) -> Self {
+    pub fn union_point(&self, p: Point3<T>) -> Self {
         let min_v = self.min.min(p);
         let max_v = self.max.max(p);
         Aabb::new(min_v, max_v)
@@ -42,28 +50,34 @@ impl<T> Aabb<T>
     }
 
     pub fn overlap(&self, other: Self) -> bool {
-        let is_max = Iterator::zip(self.max.into_iter(), other.min.into_iter())
-            .fold(true, |acc, (max, min)| acc && max >= min);
+        (0..3).all(|i| self.max[i] >= other.min[i] && self.min[i] <= other.max[i])
+    }
 
-        let is_min = Iterator::zip(self.min.into_iter(), other.max.into_iter())
-            .fold(true, |acc, (min, max)| acc && max >= min);
+    pub fn inside(&self, point: Point3<T>) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
 
-        is_max && is_min
+    /// The squared distance from `point` to the closest point of the box, zero inside.
+    pub fn distance_sq(&self, point: Point3<T>) -> T {
+        (0..3).fold(T::zero(), |acc, i| {
+            let d = (self.min[i] - point[i]).max(point[i] - self.max[i]).max(T::zero());
+            acc + d * d
+        })
     }
 
-    pub fn inside(&self, point: Vec3<T>) -> bool {
-        let is_max = Iterator::zip(self.max.into_iter(), point.into_iter())
-            .fold(true, |acc, (max, p)| acc && max >= p);
-        let is_min = Iterator::zip(self.min.into_iter(), point.into_iter())
-            .fold(true, |acc, (min, p)| acc && min <= p);
+    pub fn overlap_sphere(&self, center: Point3<T>, radius: T) -> bool {
+        self.distance_sq(center) <= radius * radius
+    }
 
-        is_max && is_min
+    pub fn center(&self) -> Point3<T> {
+        let half: T = NumCast::from(0.5).unwrap();
+        self.min + self.diagonale() * half
     }
 
     /// Returns the position of a point relative to the corners of the box, where a point
     /// at the minimum corner has offset (0, 0, 0), a point at the maximum corner has offset
     /// (1, 1, 1)
-    pub fn offset(&self, point: Vec3<T>) -> Vec3<T> {
+    pub fn offset(&self, point: Point3<T>) -> Vec3<T> {
         (point - self.min) / self.diagonale()
     }
 
@@ -75,7 +89,6 @@ impl<T> Aabb<T>
     }
 
     pub fn surface_area(&self) -> T {
-        use num::NumCast;
         let d = self.diagonale();
         (d.x() * d.y() + d.y() * d.z() + d.x() * d.z()) * NumCast::from(2).unwrap()
     }
@@ -95,28 +108,315 @@ impl<T> Aabb<T>
         }
     }
 
-    fn bounding_sphere(&self) -> Sphere<T>
-        where T: FromPrimitive
-    {
-        let v: T = FromPrimitive::from_f32(0.5).unwrap();
-        let center: Vec3<T> = (self.min + self.max) * v;
+    /// Slab test, returns the ray parameters at which the ray enters and exits the box,
+    /// clipped to `[0, max_time]`. A ray that starts inside the box enters at zero.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<(T, T)> {
+        let mut t_enter = T::zero();
+        let mut t_exit = ray.max_time;
+        for axis in 0..3 {
+            let inv_dir = T::one() / ray.direction[axis];
+            let t0 = (self.min[axis] - ray.origin[axis]) * inv_dir;
+            let t1 = (self.max[axis] - ray.origin[axis]) * inv_dir;
+            let (t_near, t_far) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            // `max` and `min` ignore the NaN produced by a ray that lies in a slab boundary.
+            t_enter = t_enter.max(t_near);
+            t_exit = t_exit.min(t_far);
+            if t_enter > t_exit {
+                return None;
+            }
+        }
+        Some((t_enter, t_exit))
+    }
+
+    /// The first point where the ray crosses the surface of the box, this is the exit point
+    /// if the ray starts inside or on the surface. The normal always points out of the box.
+    pub fn ray_hit(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let (t_enter, t_exit) = self.intersect_ray(ray)?;
+        let time = if t_enter > T::zero() {
+            t_enter
+        } else if t_exit < ray.max_time {
+            t_exit
+        } else {
+            return None;
+        };
+        let point = ray.at(time);
+        Some(RayHit::new(time, point, self.face_normal(point)))
+    }
+
+    /// The outward normal of the face closest to `point`.
+    fn face_normal(&self, point: Point3<T>) -> Normal3<T> {
+        let mut normal = Normal3::zero();
+        let mut best = T::infinity();
+        for axis in 0..3 {
+            let to_min = (point[axis] - self.min[axis]).abs();
+            let to_max = (self.max[axis] - point[axis]).abs();
+            if to_min < best {
+                best = to_min;
+                normal = Normal3::zero();
+                normal[axis] = -T::one();
+            }
+            if to_max < best {
+                best = to_max;
+                normal = Normal3::zero();
+                normal[axis] = T::one();
+            }
+        }
+        normal
+    }
+
+    /// The smallest sphere that contains the box.
+    pub fn bounding_sphere(&self) -> Sphere<T> {
+        let center = self.center();
         Sphere::new(center, center.distance(self.max))
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
+/// Anything with an axis aligned bounding box, e.g. the primitives of a `Bvh`.
+pub trait Bounded<T>
+    where T: Float
+{
+    /// The world space bounds.
+    fn bounds(&self) -> Aabb<T>;
+}
+
+impl<T> Bounded<T> for Aabb<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        *self
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Sphere<T>
     where T: Float
 {
-    center: Vec3<T>,
+    center: Point3<T>,
     radius: T,
 }
 impl<T: Float> Sphere<T> {
-    fn new(center: Vec3<T>, radius: T) -> Self {
+    pub fn new(center: Point3<T>, radius: T) -> Self {
         Sphere {
-            center: center,
-            radius: radius,
+            center,
+            radius,
+        }
+    }
+
+    pub fn center(&self) -> Point3<T> {
+        self.center
+    }
+
+    pub fn radius(&self) -> T {
+        self.radius
+    }
+
+    /// The minimum enclosing sphere of `points`, Welzl's algorithm applied incrementally to
+    /// growing prefixes of the shuffled points. Returns `None` if `points` is empty.
+    pub fn from_points(points: &[Point3<T>]) -> Option<Self> {
+        let mut points = points.to_vec();
+        // A fixed shuffle, the expected linear running time needs a random order.
+        let mut seed = 0x9e37_79b9u32;
+        for i in (1..points.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            points.swap(i, seed as usize % (i + 1));
+        }
+        let (&first, _) = points.split_first()?;
+        let mut sphere = Sphere::new(first, T::zero());
+        for i in 1..points.len() {
+            if !sphere.encloses(points[i]) {
+                sphere = Self::welzl(&points[..i], &mut vec![points[i]]);
+            }
+        }
+        Some(sphere)
+    }
+
+    /// The minimum sphere around `points` with all of `boundary` on its surface.
+    fn welzl(points: &[Point3<T>], boundary: &mut Vec<Point3<T>>) -> Self {
+        let mut sphere = Self::circumsphere(boundary);
+        if boundary.len() == 4 {
+            return sphere;
+        }
+        for i in 0..points.len() {
+            if !sphere.encloses(points[i]) {
+                boundary.push(points[i]);
+                sphere = Self::welzl(&points[..i], boundary);
+                boundary.pop();
+            }
+        }
+        sphere
+    }
+
+    /// The smallest sphere through up to four points.
+    fn circumsphere(boundary: &[Point3<T>]) -> Self {
+        let two: T = NumCast::from(2).unwrap();
+        let half: T = NumCast::from(0.5).unwrap();
+        let a = boundary[0];
+        let center = match boundary.len() {
+            1 => Some(a),
+            2 => Some(a + (boundary[1] - a) * half),
+            3 => {
+                let (ab, ac) = (boundary[1] - a, boundary[2] - a);
+                let n = ab.cross(ac);
+                let denom = two * n.length_sq();
+                if denom == T::zero() {
+                    None
+                } else {
+                    let offset = n.cross(ab) * ac.length_sq() + ac.cross(n) * ab.length_sq();
+                    Some(a + offset / denom)
+                }
+            }
+            _ => {
+                let (u, v, w) = (boundary[1] - a, boundary[2] - a, boundary[3] - a);
+                let denom = two * u.dot(v.cross(w));
+                if denom == T::zero() {
+                    None
+                } else {
+                    let offset = v.cross(w) * u.length_sq() + w.cross(u) * v.length_sq() +
+                                 u.cross(v) * w.length_sq();
+                    Some(a + offset / denom)
+                }
+            }
+        };
+        match center {
+            Some(center) => Sphere::new(center, center.distance(a)),
+            // Collinear or coplanar points, the sphere is spanned by fewer of them.
+            None => {
+                let mut best: Option<Self> = None;
+                for skip in 0..boundary.len() {
+                    let rest: Vec<_> = boundary.iter()
+                        .enumerate()
+                        .filter(|&(i, _)| i != skip)
+                        .map(|(_, &p)| p)
+                        .collect();
+                    let candidate = Self::circumsphere(&rest).union_point(boundary[skip]);
+                    if best.is_none_or(|b| candidate.radius < b.radius) {
+                        best = Some(candidate);
+                    }
+                }
+                best.expect("At least two points")
+            }
+        }
+    }
+
+    /// `contains_point` with some slack for the rounding errors of `circumsphere`.
+    fn encloses(&self, point: Point3<T>) -> bool {
+        let slack = T::one() + T::epsilon() * NumCast::from(64).unwrap();
+        self.center.distance_sq(point) <= self.radius * self.radius * slack
+    }
+
+    /// An enclosing sphere of `points` that is at most a few percent larger than the
+    /// minimum one, Ritter's algorithm. Returns `None` if `points` is empty.
+    pub fn from_points_approx(points: &[Point3<T>]) -> Option<Self> {
+        let (&first, _) = points.split_first()?;
+        let farthest = |from: Point3<T>| {
+            points.iter().cloned().fold(from, |best, p| {
+                if from.distance_sq(p) > from.distance_sq(best) { p } else { best }
+            })
+        };
+        let y = farthest(first);
+        let z = farthest(y);
+        let half: T = NumCast::from(0.5).unwrap();
+        let mut sphere = Sphere::new(y + (z - y) * half, y.distance(z) * half);
+        for &p in points {
+            sphere = sphere.union_point(p);
+        }
+        Some(sphere)
+    }
+
+    /// The smallest sphere that contains this sphere and `point`.
+    pub fn union_point(&self, point: Point3<T>) -> Self {
+        self.union(&Sphere::new(point, T::zero()))
+    }
+
+    /// The smallest sphere that contains both spheres.
+    pub fn union(&self, other: &Self) -> Self {
+        if self.contains_sphere(other) {
+            return *self;
+        }
+        if other.contains_sphere(self) {
+            return *other;
+        }
+        let half: T = NumCast::from(0.5).unwrap();
+        let d = other.center - self.center;
+        let distance = d.length();
+        let radius = (distance + self.radius + other.radius) * half;
+        Sphere::new(self.center + d * ((radius - self.radius) / distance), radius)
+    }
+
+    pub fn contains_point(&self, point: Point3<T>) -> bool {
+        self.center.distance_sq(point) <= self.radius * self.radius
+    }
+
+    pub fn contains_sphere(&self, other: &Self) -> bool {
+        self.center.distance(other.center) + other.radius <= self.radius
+    }
+
+    pub fn overlap(&self, other: &Self) -> bool {
+        let radii = self.radius + other.radius;
+        self.center.distance_sq(other.center) <= radii * radii
+    }
+
+    pub fn overlap_aabb(&self, aabb: Aabb<T>) -> bool {
+        aabb.overlap_sphere(self.center, self.radius)
+    }
+
+    pub fn to_aabb(&self) -> Aabb<T> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(self.center - r, self.center + r)
+    }
+
+    /// Returns the ray parameters at which the ray enters and exits the sphere, clipped to
+    /// `[0, max_time]`.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<(T, T)> {
+        let oc = ray.origin - self.center;
+        let a = ray.direction.length_sq();
+        let half_b = ray.direction.dot(oc);
+        let c = oc.length_sq() - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < T::zero() || a == T::zero() {
+            return None;
+        }
+        // Avoids the cancellation of `-b + sqrt(d)` for the root closer to zero.
+        let q = if half_b > T::zero() {
+            -half_b - discriminant.sqrt()
+        } else {
+            -half_b + discriminant.sqrt()
+        };
+        let (t0, t1) = if q == T::zero() {
+            (T::zero(), T::zero())
+        } else {
+            let (r0, r1) = (q / a, c / q);
+            if r0 < r1 { (r0, r1) } else { (r1, r0) }
+        };
+        let t_enter = t0.max(T::zero());
+        let t_exit = t1.min(ray.max_time);
+        if t_enter > t_exit {
+            return None;
+        }
+        Some((t_enter, t_exit))
+    }
+
+    /// The first point where the ray crosses the surface of the sphere, this is the exit
+    /// point if the ray starts inside. The normal always points out of the sphere. A sphere
+    /// without radius has no normal and is never hit.
+    pub fn ray_hit(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        if self.radius == T::zero() {
+            return None;
         }
+        let (t_enter, t_exit) = self.intersect_ray(ray)?;
+        let starts_inside = (ray.origin - self.center).length_sq() < self.radius * self.radius;
+        let time = if !starts_inside {
+            t_enter
+        } else if t_exit < ray.max_time {
+            t_exit
+        } else {
+            return None;
+        };
+        let point = ray.at(time);
+        let normal = ((point - self.center) / self.radius).to_normal();
+        Some(RayHit::new(time, point, normal))
     }
 }
 
@@ -124,55 +424,186 @@ impl<T: Float> Sphere<T> {
 mod test {
     use vector::*;
     use aabb::*;
+    use ray::Ray;
+    use test_util::Lcg;
 
     #[test]
     fn union() {
-        let aabb = Aabb::new(Vec3f::new(-1., -1., -1.), Vec3f::new(3., 3., 3.));
-        let aabb1 = Aabb::new(Vec3f::new(-2., -2., -2.), Vec3f::new(2., 2., 2.));
+        let aabb = Aabb::new(Point3f::new(-1., -1., -1.), Point3f::new(3., 3., 3.));
+        let aabb1 = Aabb::new(Point3f::new(-2., -2., -2.), Point3f::new(2., 2., 2.));
         let aabb2 = Aabb::union_aabb(aabb, aabb1);
-        assert!(aabb2 == Aabb::new(Vec3f::new(-2., -2., -2.), Vec3f::new(3., 3., 3.)));
+        assert!(aabb2 == Aabb::new(Point3f::new(-2., -2., -2.), Point3f::new(3., 3., 3.)));
     }
 
     #[test]
     fn offset() {
-        let aabb = Aabb::new(Vec3f::new(-2., -2., -2.), Vec3f::new(2., 2., 2.));
-        assert!(aabb.offset(Vec3::new(-2., -2., -2.)) == Vec3::new(0., 0., 0.));
-        assert!(aabb.offset(Vec3::new(2., 2., 2.)) == Vec3::new(1., 1., 1.));
+        let aabb = Aabb::new(Point3f::new(-2., -2., -2.), Point3f::new(2., 2., 2.));
+        assert_approx_eq!(aabb.offset(Point3::new(-2., -2., -2.)), Vec3::new(0., 0., 0.));
+        assert_approx_eq!(aabb.offset(Point3::new(2., 2., 2.)), Vec3::new(1., 1., 1.));
     }
 
     #[test]
     fn inside() {
-        let aabb = Aabb::new(Vec3f::new(-2., -2., -2.), Vec3f::new(2., 2., 2.));
-        assert!(aabb.inside(Vec3f::new(0., 0., 0.)));
-        assert!(aabb.inside(Vec3f::new(2., 2., 2.)));
-        assert!(aabb.inside(Vec3f::new(-2., -2., -2.)));
-        assert!(!aabb.inside(Vec3f::new(-4., 2., -4.)));
-        assert!(!aabb.inside(Vec3f::new(-1., 2., -4.)));
+        let aabb = Aabb::new(Point3f::new(-2., -2., -2.), Point3f::new(2., 2., 2.));
+        assert!(aabb.inside(Point3f::new(0., 0., 0.)));
+        assert!(aabb.inside(Point3f::new(2., 2., 2.)));
+        assert!(aabb.inside(Point3f::new(-2., -2., -2.)));
+        assert!(!aabb.inside(Point3f::new(-4., 2., -4.)));
+        assert!(!aabb.inside(Point3f::new(-1., 2., -4.)));
     }
 
     #[test]
     fn overlap() {
-        let aabb = Aabb::new(Vec3f::new(-2., -2., -2.), Vec3f::new(2., 2., 2.));
-        assert!(aabb.overlap(Aabb::new(Vec3f::new(0., 0., 0.), Vec3f::new(2., 1., 1.))));
-        assert!(aabb.overlap(Aabb::new(Vec3f::new(0., 0., 0.), Vec3f::new(5., 5., 5.))));
-        assert!(!aabb.overlap(Aabb::new(Vec3f::new(3., 3., 3.), Vec3f::new(5., 5., 5.))));
-        assert!(!aabb.overlap(Aabb::new(Vec3f::new(-4., -4., -4.), Vec3f::new(-3., -3., -3.))));
+        let aabb = Aabb::new(Point3f::new(-2., -2., -2.), Point3f::new(2., 2., 2.));
+        assert!(aabb.overlap(Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(2., 1., 1.))));
+        assert!(aabb.overlap(Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(5., 5., 5.))));
+        assert!(!aabb.overlap(Aabb::new(Point3f::new(3., 3., 3.), Point3f::new(5., 5., 5.))));
+        assert!(!aabb.overlap(Aabb::new(Point3f::new(-4., -4., -4.), Point3f::new(-3., -3., -3.))));
     }
 
     #[test]
     fn volume() {
-        let aabb = Aabb::new(Vec3f::new(0., 0., 0.), Vec3f::new(1., 1., 1.));
+        let aabb = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(1., 1., 1.));
         assert!(aabb.volume() == 1.);
-        let aabb1 = Aabb::new(Vec3f::new(0., 0., 0.), Vec3f::new(2., 2., 2.));
+        let aabb1 = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(2., 2., 2.));
         assert!(aabb1.volume() == 8.);
     }
 
+    #[test]
+    fn ray_aabb() {
+        let aabb = Aabb::new(Point3f::new(-1., -1., -1.), Point3f::new(1., 1., 1.));
+        let ray = Ray::new(Point3f::new(-5., 0., 0.), Vec3f::new(1., 0., 0.), 10.);
+        assert!(aabb.intersect_ray(&ray) == Some((4., 6.)));
+        let hit = aabb.ray_hit(&ray).unwrap();
+        assert!(hit.time == 4.);
+        assert!(hit.point == Point3f::new(-1., 0., 0.));
+        assert!(hit.normal == Normal3f::new(-1., 0., 0.));
+
+        let inside = Ray::new(Point3f::new(0., 0., 0.), Vec3f::new(0., 0., -1.), 10.);
+        assert!(aabb.intersect_ray(&inside) == Some((0., 1.)));
+        let hit = aabb.ray_hit(&inside).unwrap();
+        assert!(hit.time == 1.);
+        assert!(hit.normal == Normal3f::new(0., 0., -1.));
+
+        let short = Ray::new(ray.origin, ray.direction, 3.);
+        assert!(aabb.intersect_ray(&short).is_none());
+        let miss = Ray::new(Point3f::new(-5., 2., 0.), Vec3f::new(1., 0., 0.), 10.);
+        assert!(aabb.intersect_ray(&miss).is_none());
+        let away = Ray::new(ray.origin, Vec3f::new(-1., 0., 0.), 10.);
+        assert!(aabb.intersect_ray(&away).is_none());
+        let diagonal = Ray::new(Point3f::new(-2., -2., -2.), Vec3f::new(1., 1., 1.), 10.);
+        assert!(aabb.intersect_ray(&diagonal) == Some((1., 3.)));
+    }
+
+    #[test]
+    fn ray_sphere() {
+        let sphere = Sphere::new(Point3f::new(0., 0., 5.), 2.);
+        let ray = Ray::new(Point3f::new(0., 0., 0.), Vec3f::new(0., 0., 1.), 10.);
+        assert!(sphere.intersect_ray(&ray) == Some((3., 7.)));
+        let hit = sphere.ray_hit(&ray).unwrap();
+        assert!(hit.time == 3.);
+        assert!(hit.point == Point3f::new(0., 0., 3.));
+        assert!(hit.normal == Normal3f::new(0., 0., -1.));
+
+        let inside = Ray::new(Point3f::new(0., 0., 5.), Vec3f::new(0., 1., 0.), 10.);
+        let hit = sphere.ray_hit(&inside).unwrap();
+        assert!(hit.time == 2.);
+        assert!(hit.normal == Normal3f::new(0., 1., 0.));
+
+        let short = Ray::new(ray.origin, ray.direction, 2.);
+        assert!(sphere.intersect_ray(&short).is_none());
+        let miss = Ray::new(Point3f::new(3., 0., 0.), Vec3f::new(0., 0., 1.), 10.);
+        assert!(sphere.intersect_ray(&miss).is_none());
+        let point = Sphere::new(Point3f::new(0., 0., 5.), 0.);
+        assert!(point.ray_hit(&ray).is_none());
+    }
+
     #[test]
     fn bounding_sphere() {
-        let aabb = Aabb::new(Vec3f::new(0., 0., 0.), Vec3f::new(1., 1., 1.));
+        let aabb = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(1., 1., 1.));
         let sphere = aabb.bounding_sphere();
         assert!(sphere ==
-                Sphere::new(Vec3f::new(0.5, 0.5, 0.5),
+                Sphere::new(Point3f::new(0.5, 0.5, 0.5),
                             Vec3f::new(0.5, 0.5, 0.5).length()));
     }
+
+    /// The smallest sphere around `points` by trying every sphere spanned by up to four
+    /// of them.
+    fn brute_force_sphere(points: &[Point3f]) -> Sphere<f32> {
+        let n = points.len();
+        let mut candidates = Vec::new();
+        for i in 0..n {
+            for j in i..n {
+                candidates.push(Sphere::circumsphere(&[points[i], points[j]]));
+                for k in j + 1..n {
+                    candidates.push(Sphere::circumsphere(&[points[i], points[j], points[k]]));
+                    for l in k + 1..n {
+                        let four = [points[i], points[j], points[k], points[l]];
+                        candidates.push(Sphere::circumsphere(&four));
+                    }
+                }
+            }
+        }
+        candidates.into_iter()
+            .filter(|s| points.iter().all(|&p| s.center.distance(p) <= s.radius * (1. + 1e-5)))
+            .fold(None, |best: Option<Sphere<f32>>, s| match best {
+                Some(b) if b.radius <= s.radius => Some(b),
+                _ => Some(s),
+            })
+            .unwrap()
+    }
+
+    fn cloud(seed: u32, count: usize) -> Vec<Point3f> {
+        let mut rng = Lcg(seed);
+        (0..count).map(|_| Point3f::new(rng.next(), rng.next() * 2., rng.next() * 3.)).collect()
+    }
+
+    #[test]
+    fn minimum_sphere() {
+        for seed in 0..20 {
+            let points = cloud(seed, 10);
+            let exact = Sphere::from_points(&points).unwrap();
+            let approx = Sphere::from_points_approx(&points).unwrap();
+            let brute = brute_force_sphere(&points);
+            assert!((exact.radius() - brute.radius()).abs() < 1e-4);
+            assert!(exact.radius() <= approx.radius() + 1e-5);
+            for &p in points.iter() {
+                assert!(exact.center().distance(p) <= exact.radius() * (1. + 1e-5));
+                assert!(approx.center().distance(p) <= approx.radius() * (1. + 1e-5));
+            }
+        }
+
+        // The corners of a cube lie on the minimum sphere, duplicates and collinear points
+        // do not break the construction.
+        let aabb = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(2., 2., 2.));
+        let mut corners: Vec<_> = (0..8)
+            .map(|i| (Vec3f::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32) * 2.).to_point())
+            .collect();
+        corners.extend_from_slice(&corners.clone());
+        let sphere = Sphere::from_points(&corners).unwrap();
+        assert!(sphere.center().distance(Point3f::new(1., 1., 1.)) < 1e-5);
+        assert!((sphere.radius() - 3f32.sqrt()).abs() < 1e-5);
+        assert!(aabb.bounding_sphere().center() == sphere.center());
+        let line = [Point3f::zero(), Point3f::new(1., 0., 0.), Point3f::new(4., 0., 0.)];
+        assert!((Sphere::from_points(&line).unwrap().radius() - 2.).abs() < 1e-6);
+        assert!(Sphere::<f32>::from_points(&[]).is_none());
+        assert!(Sphere::<f32>::from_points_approx(&[]).is_none());
+    }
+
+    #[test]
+    fn sphere_algebra() {
+        let a = Sphere::new(Point3f::new(0., 0., 0.), 1.);
+        let b = Sphere::new(Point3f::new(4., 0., 0.), 2.);
+        let union = a.union(&b);
+        assert!(union == Sphere::new(Point3f::new(2.5, 0., 0.), 3.5));
+        assert!(union.contains_sphere(&a) && union.contains_sphere(&b));
+        assert!(a.union(&Sphere::new(Point3f::new(0.5, 0., 0.), 0.2)) == a);
+        assert!(a.contains_point(Point3f::new(0., 1., 0.)));
+        assert!(!a.contains_point(Point3f::new(0., 1.1, 0.)));
+        assert!(!a.overlap(&b));
+        assert!(a.overlap(&Sphere::new(Point3f::new(2.5, 0., 0.), 1.5)));
+        assert!(a.overlap_aabb(Aabb::new(Point3f::new(0.7, 0.7, -1.), Point3f::new(2., 2., 1.))));
+        assert!(!a.overlap_aabb(Aabb::new(Point3f::new(0.8, 0.8, -1.), Point3f::new(2., 2., 1.))));
+        assert!(b.to_aabb() == Aabb::new(Point3f::new(2., -2., -2.), Point3f::new(6., 2., 2.)));
+    }
 }
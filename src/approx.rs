@@ -0,0 +1,332 @@
+use num::Float;
+use vector::CoreVector;
+use matrix::Matrix;
+use quaternion::Quaternion;
+use dual_quaternion::DualQuaternion;
+use aabb::Aabb;
+use transform::Transform;
+
+/// Approximate equality for floats and everything built from them. Compound types compare
+/// element wise and are equal if all of their elements are.
+///
+/// * `abs_diff_eq`: the absolute difference is at most `epsilon`. Useful close to zero.
+/// * `relative_eq`: the difference is at most `max_relative` times the larger magnitude,
+///   or at most `epsilon` close to zero.
+/// * `ulps_eq`: at most `max_ulps` representable floats lie between the values, or the
+///   difference is at most `epsilon` close to zero.
+pub trait ApproxEq {
+    type Epsilon: Copy;
+
+    fn default_epsilon() -> Self::Epsilon;
+    fn default_max_relative() -> Self::Epsilon;
+    fn default_max_ulps() -> u32;
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool;
+    fn relative_eq(&self, other: &Self, epsilon: Self::Epsilon, max_relative: Self::Epsilon)
+                   -> bool;
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool;
+
+    /// `relative_eq` with the default tolerances.
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.relative_eq(other, Self::default_epsilon(), Self::default_max_relative())
+    }
+}
+
+macro_rules! impl_approx_float{
+    ($float: ty) => {
+        impl ApproxEq for $float {
+            type Epsilon = $float;
+
+            fn default_epsilon() -> $float {
+                <$float>::EPSILON
+            }
+
+            fn default_max_relative() -> $float {
+                <$float>::EPSILON
+            }
+
+            fn default_max_ulps() -> u32 {
+                4
+            }
+
+            fn abs_diff_eq(&self, other: &$float, epsilon: $float) -> bool {
+                // Covers infinities of the same sign, their difference is NaN.
+                self == other || (self - other).abs() <= epsilon
+            }
+
+            fn relative_eq(&self, other: &$float, epsilon: $float, max_relative: $float) -> bool {
+                if self == other {
+                    return true;
+                }
+                if self.is_infinite() || other.is_infinite() {
+                    return false;
+                }
+                let diff = (self - other).abs();
+                diff <= epsilon || diff <= self.abs().max(other.abs()) * max_relative
+            }
+
+            fn ulps_eq(&self, other: &$float, epsilon: $float, max_ulps: u32) -> bool {
+                if self.abs_diff_eq(other, epsilon) {
+                    return true;
+                }
+                if self.is_sign_positive() != other.is_sign_positive() || self.is_nan() ||
+                   other.is_nan() {
+                    return false;
+                }
+                // Floats of the same sign are ordered like their bit patterns.
+                let (a, b) = (u64::from(self.to_bits()), u64::from(other.to_bits()));
+                a.abs_diff(b) <= u64::from(max_ulps)
+            }
+        }
+    }
+}
+
+impl_approx_float!(f32);
+impl_approx_float!(f64);
+
+/// Compares pairs of elements.
+fn all_pairs<'a, T, I, F>(pairs: I, eq: F) -> bool
+    where T: 'a,
+          I: IntoIterator<Item = (&'a T, &'a T)>,
+          F: Fn(&T, &T) -> bool
+{
+    pairs.into_iter().all(|(a, b)| eq(a, b))
+}
+
+impl<T, const N: usize, Type> ApproxEq for CoreVector<T, N, Type>
+    where T: Float + ApproxEq<Epsilon = T>
+{
+    type Epsilon = T;
+
+    fn default_epsilon() -> T {
+        T::default_epsilon()
+    }
+
+    fn default_max_relative() -> T {
+        T::default_max_relative()
+    }
+
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+        all_pairs(self.iter().zip(other.iter()), |a, b| a.abs_diff_eq(b, epsilon))
+    }
+
+    fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool {
+        all_pairs(self.iter().zip(other.iter()),
+                  |a, b| a.relative_eq(b, epsilon, max_relative))
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: T, max_ulps: u32) -> bool {
+        all_pairs(self.iter().zip(other.iter()), |a, b| a.ulps_eq(b, epsilon, max_ulps))
+    }
+}
+
+/// Implements `ApproxEq` for a type by comparing the `ApproxEq` parts returned by
+/// `$parts`, all of which have the epsilon `T`.
+macro_rules! impl_approx_parts{
+    ([$($generics: tt)*], $ty: ty, |$x: ident| $parts: expr) => {
+        impl<$($generics)*> ApproxEq for $ty
+            where T: Float + ApproxEq<Epsilon = T>
+        {
+            type Epsilon = T;
+
+            fn default_epsilon() -> T {
+                T::default_epsilon()
+            }
+
+            fn default_max_relative() -> T {
+                T::default_max_relative()
+            }
+
+            fn default_max_ulps() -> u32 {
+                T::default_max_ulps()
+            }
+
+            fn abs_diff_eq(&self, other: &Self, epsilon: T) -> bool {
+                let parts = |$x: &Self| $parts;
+                all_pairs(parts(self).iter().zip(parts(other).iter()),
+                          |a, b| a.abs_diff_eq(b, epsilon))
+            }
+
+            fn relative_eq(&self, other: &Self, epsilon: T, max_relative: T) -> bool {
+                let parts = |$x: &Self| $parts;
+                all_pairs(parts(self).iter().zip(parts(other).iter()),
+                          |a, b| a.relative_eq(b, epsilon, max_relative))
+            }
+
+            fn ulps_eq(&self, other: &Self, epsilon: T, max_ulps: u32) -> bool {
+                let parts = |$x: &Self| $parts;
+                all_pairs(parts(self).iter().zip(parts(other).iter()),
+                          |a, b| a.ulps_eq(b, epsilon, max_ulps))
+            }
+        }
+    }
+}
+
+impl_approx_parts!([T, const N: usize, const M: usize], Matrix<T, N, M>, |m| *m.rows());
+impl_approx_parts!([T], Quaternion<T>, |q| [q.v().extend(q.w())]);
+impl_approx_parts!([T], DualQuaternion<T>, |dq| {
+    [dq.real().v().extend(dq.real().w()), dq.dual().v().extend(dq.dual().w())]
+});
+impl_approx_parts!([T], Aabb<T>, |aabb| [aabb.min(), aabb.max()]);
+impl_approx_parts!([T], Transform<T>, |t| [*t.matrix(), *t.inverse_matrix()]);
+
+/// `ApproxEq::abs_diff_eq` with the default epsilon of `A`.
+#[doc(hidden)]
+pub fn abs_diff_eq_default<A: ApproxEq>(a: &A, b: &A) -> bool {
+    a.abs_diff_eq(b, A::default_epsilon())
+}
+
+/// `ApproxEq::relative_eq` with the default tolerances of `A`.
+#[doc(hidden)]
+pub fn relative_eq_default<A: ApproxEq>(a: &A, b: &A) -> bool {
+    a.relative_eq(b, A::default_epsilon(), A::default_max_relative())
+}
+
+/// `ApproxEq::ulps_eq` with the default tolerances of `A`.
+#[doc(hidden)]
+pub fn ulps_eq_default<A: ApproxEq>(a: &A, b: &A) -> bool {
+    a.ulps_eq(b, A::default_epsilon(), A::default_max_ulps())
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_approx {
+    ($name: expr, $check: path, $a: expr, $b: expr $(, $arg: expr)*) => {{
+        let (a, b) = (&$a, &$b);
+        if !$check(a, b $(, $arg)*) {
+            panic!("assertion failed: `{}(left, right)`\n  left: `{:?}`\n right: `{:?}`",
+                   $name,
+                   a,
+                   b);
+        }
+    }};
+}
+
+/// Asserts `ApproxEq::abs_diff_eq`, with the default epsilon unless `epsilon = ...` is
+/// given.
+#[macro_export]
+macro_rules! assert_abs_diff_eq {
+    ($a: expr, $b: expr) => {
+        $crate::__assert_approx!("abs_diff_eq", $crate::approx::abs_diff_eq_default, $a, $b)
+    };
+    ($a: expr, $b: expr, epsilon = $epsilon: expr) => {
+        $crate::__assert_approx!("abs_diff_eq",
+                                 $crate::approx::ApproxEq::abs_diff_eq,
+                                 $a,
+                                 $b,
+                                 $epsilon)
+    };
+}
+
+/// Asserts `ApproxEq::relative_eq`, with the default tolerances unless
+/// `epsilon = ..., max_relative = ...` are given.
+#[macro_export]
+macro_rules! assert_relative_eq {
+    ($a: expr, $b: expr) => {
+        $crate::__assert_approx!("relative_eq", $crate::approx::relative_eq_default, $a, $b)
+    };
+    ($a: expr, $b: expr, epsilon = $epsilon: expr, max_relative = $max_relative: expr) => {
+        $crate::__assert_approx!("relative_eq",
+                                 $crate::approx::ApproxEq::relative_eq,
+                                 $a,
+                                 $b,
+                                 $epsilon,
+                                 $max_relative)
+    };
+}
+
+/// Asserts `ApproxEq::ulps_eq`, with the default tolerances unless
+/// `epsilon = ..., max_ulps = ...` are given.
+#[macro_export]
+macro_rules! assert_ulps_eq {
+    ($a: expr, $b: expr) => {
+        $crate::__assert_approx!("ulps_eq", $crate::approx::ulps_eq_default, $a, $b)
+    };
+    ($a: expr, $b: expr, epsilon = $epsilon: expr, max_ulps = $max_ulps: expr) => {
+        $crate::__assert_approx!("ulps_eq",
+                                 $crate::approx::ApproxEq::ulps_eq,
+                                 $a,
+                                 $b,
+                                 $epsilon,
+                                 $max_ulps)
+    };
+}
+
+/// `assert_relative_eq!` with the default tolerances, or `assert_abs_diff_eq!` if an
+/// absolute epsilon is given.
+/// ```
+/// #[macro_use]
+/// extern crate rla;
+/// use rla::vector::*;
+///
+/// fn main() {
+///     let v = Vec3f::new(0.1, 0.2, 0.3) * 10.0;
+///     assert_approx_eq!(v, Vec3f::new(1.0, 2.0, 3.0));
+///     assert_approx_eq!(v.length(), 3.7416, 1e-4);
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($a: expr, $b: expr) => {
+        $crate::assert_relative_eq!($a, $b)
+    };
+    ($a: expr, $b: expr, $epsilon: expr) => {
+        $crate::assert_abs_diff_eq!($a, $b, epsilon = $epsilon)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use approx::*;
+    use quaternion::Quaternion;
+    use aabb::Aabb;
+    use transform::Transform;
+    use unit::Radians;
+
+    #[test]
+    fn floats() {
+        assert!(1.0f32.abs_diff_eq(&1.05, 0.1));
+        assert!(!1.0f32.abs_diff_eq(&1.2, 0.1));
+        assert!(1e6f32.relative_eq(&(1e6 + 0.05), f32::EPSILON, 1e-6));
+        assert!(!1e-6f32.relative_eq(&2e-6, f32::EPSILON, 1e-6));
+        let next = f64::from_bits(1.0f64.to_bits() + 3);
+        assert!(1.0f64.ulps_eq(&next, 0.0, 4));
+        assert!(!1.0f64.ulps_eq(&next, 0.0, 2));
+        assert!(!1.0f32.ulps_eq(&-1.0, 0.0, 4));
+        assert!(f32::INFINITY.approx_eq(&f32::INFINITY));
+        assert!(!f32::NAN.approx_eq(&f32::NAN));
+        assert!(!f32::INFINITY.approx_eq(&f32::MAX));
+        assert!((0.1f32 + 0.2).approx_eq(&0.3));
+    }
+
+    #[test]
+    fn compound() {
+        let v = Vec3f::new(0.1, 0.2, 0.3) * 3.;
+        assert!(v != Vec3f::new(0.3, 0.6, 0.9));
+        assert_approx_eq!(v, Vec3f::new(0.3, 0.6, 0.9));
+        assert_ulps_eq!(v, Vec3f::new(0.3, 0.6, 0.9));
+        assert!(!v.abs_diff_eq(&Vec3f::new(0.3, 0.6, 1.), 1e-3));
+        let aabb = Aabb::new(Point3f::zero(), v.to_point());
+        assert_approx_eq!(aabb, Aabb::new(Point3f::zero(), Point3f::new(0.3, 0.6, 0.9)));
+
+        let t = &Transform::rotation_y(Radians::new(0.3)) *
+                &Transform::translate(Vec3f::new(1., 2., 3.));
+        assert_approx_eq!(&t * &t.inverse(), Transform::identity(), 1e-6);
+        assert!(!t.approx_eq(&Transform::identity()));
+
+        let q = Quaternion::new(Vec3f::new(0., 1., 0.), Radians::new(0.5));
+        assert_relative_eq!(q * q.inverse(), Quaternion::identity(), epsilon = 1e-6,
+                            max_relative = 0.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn assert_fails() {
+        assert_approx_eq!(Vec2f::new(1., 2.), Vec2f::new(1., 2.001));
+    }
+}
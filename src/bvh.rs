@@ -0,0 +1,500 @@
+use num::{Float, NumCast};
+use aabb::{Aabb, Bounded};
+use ray::{Ray, RayHit};
+use shapes::Shape;
+use vector::Point3;
+
+/// Number of bins the centroids are sorted into when searching for a split.
+const BIN_COUNT: usize = 12;
+/// Nodes with more primitives than this are always split.
+const MAX_LEAF_SIZE: usize = 4;
+/// Cost of visiting a node, relative to the cost of intersecting a primitive.
+const TRAVERSAL_COST: f64 = 0.125;
+
+/// A node of the flattened tree. The left child of an interior node directly follows its
+/// parent, `offset` is the index of the right child for interior nodes and the index of the
+/// first primitive for leafs.
+#[derive(Copy, Clone, Debug)]
+struct Node<T>
+    where T: Float
+{
+    bounds: Aabb<T>,
+    offset: u32,
+    /// Zero for interior nodes.
+    count: u16,
+    /// The split axis of interior nodes.
+    axis: u8,
+}
+
+impl<T> Node<T>
+    where T: Float
+{
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+
+    fn primitives(&self) -> ::std::ops::Range<usize> {
+        let first = self.offset as usize;
+        first..first + self.count as usize
+    }
+}
+
+struct BuildPrimitive<T>
+    where T: Float
+{
+    index: usize,
+    bounds: Aabb<T>,
+    centroid: Point3<T>,
+}
+
+/// A bounding volume hierarchy, built with the binned surface area heuristic. The nodes
+/// are stored depth first in a single array and the primitives are reordered so that every
+/// leaf references a contiguous range of them.
+#[derive(Clone, Debug)]
+pub struct Bvh<T, P>
+    where T: Float,
+          P: Bounded<T>
+{
+    nodes: Vec<Node<T>>,
+    primitives: Vec<P>,
+}
+
+impl<T, P> Bvh<T, P>
+    where T: Float,
+          P: Bounded<T>
+{
+    pub fn new(primitives: Vec<P>) -> Self {
+        let mut build: Vec<_> = primitives.iter()
+            .enumerate()
+            .map(|(index, primitive)| {
+                let bounds = primitive.bounds();
+                BuildPrimitive {
+                    index,
+                    bounds,
+                    centroid: bounds.center(),
+                }
+            })
+            .collect();
+        let mut nodes = Vec::with_capacity(2 * build.len());
+        if !build.is_empty() {
+            Self::build(&mut nodes, &mut build, 0);
+        }
+
+        let mut slots: Vec<Option<P>> = primitives.into_iter().map(Some).collect();
+        let primitives = build.iter()
+            .map(|b| slots[b.index].take().expect("Each primitive is referenced once"))
+            .collect();
+        Bvh {
+            nodes,
+            primitives,
+        }
+    }
+
+    /// Builds the subtree for `build`, the primitives starting at `first`. Returns the index
+    /// of the root of the subtree.
+    fn build(nodes: &mut Vec<Node<T>>, build: &mut [BuildPrimitive<T>], first: usize) -> usize {
+        let bounds = build.iter()
+            .skip(1)
+            .fold(build[0].bounds, |acc, b| Aabb::union_aabb(acc, b.bounds));
+        let index = nodes.len();
+        nodes.push(Node {
+            bounds,
+            offset: first as u32,
+            // Leafs never hold more than `MAX_LEAF_SIZE` primitives, interior nodes overwrite
+            // the count below.
+            count: build.len().min(MAX_LEAF_SIZE) as u16,
+            axis: 0,
+        });
+        if build.len() == 1 {
+            return index;
+        }
+
+        let centroid_bounds = build.iter()
+            .skip(1)
+            .fold(Aabb::single(build[0].centroid), |acc, b| acc.union_point(b.centroid));
+        let axis = centroid_bounds.maximum_exent() as usize;
+        let mid = if centroid_bounds.min()[axis] == centroid_bounds.max()[axis] {
+            // All centroids coincide, there is no position to split at.
+            if build.len() <= MAX_LEAF_SIZE {
+                return index;
+            }
+            build.len() / 2
+        } else {
+            match Self::split(bounds, centroid_bounds, axis, build) {
+                Some(mid) => mid,
+                None => return index,
+            }
+        };
+
+        Self::build(nodes, &mut build[..mid], first);
+        let right = Self::build(nodes, &mut build[mid..], first + mid);
+        nodes[index] = Node {
+            bounds,
+            offset: right as u32,
+            count: 0,
+            axis: axis as u8,
+        };
+        index
+    }
+
+    /// Finds the cheapest split of the centroids along `axis` and partitions `build` by it.
+    /// Returns the number of primitives on the left side, or `None` if a leaf is cheaper.
+    fn split(bounds: Aabb<T>,
+             centroid_bounds: Aabb<T>,
+             axis: usize,
+             build: &mut [BuildPrimitive<T>])
+             -> Option<usize> {
+        let bin_count: T = NumCast::from(BIN_COUNT).unwrap();
+        let bin = |centroid: Point3<T>| {
+            let b: usize = NumCast::from(centroid_bounds.offset(centroid)[axis] * bin_count)
+                .unwrap_or(0);
+            b.min(BIN_COUNT - 1)
+        };
+        let mut counts = [0usize; BIN_COUNT];
+        let mut bin_bounds: [Option<Aabb<T>>; BIN_COUNT] = [None; BIN_COUNT];
+        for b in build.iter() {
+            let i = bin(b.centroid);
+            counts[i] += 1;
+            bin_bounds[i] = Some(bin_bounds[i]
+                .map_or(b.bounds, |acc| Aabb::union_aabb(acc, b.bounds)));
+        }
+
+        // Sweeps from the right to get the area and count of every right side, then from
+        // the left to evaluate the cost of each split.
+        let union = |acc: Option<Aabb<T>>, b: Option<Aabb<T>>| match (acc, b) {
+            (Some(a), Some(b)) => Some(Aabb::union_aabb(a, b)),
+            (a, b) => a.or(b),
+        };
+        let area = |b: Option<Aabb<T>>| b.map_or(T::zero(), |b| b.surface_area());
+        let mut right_area = [T::zero(); BIN_COUNT];
+        let mut right_count = [0usize; BIN_COUNT];
+        let mut acc = None;
+        let mut count = 0;
+        for i in (1..BIN_COUNT).rev() {
+            acc = union(acc, bin_bounds[i]);
+            count += counts[i];
+            right_area[i] = area(acc);
+            right_count[i] = count;
+        }
+
+        let mut best: Option<(T, usize)> = None;
+        let mut acc = None;
+        let mut count = 0;
+        for i in 0..BIN_COUNT - 1 {
+            acc = union(acc, bin_bounds[i]);
+            count += counts[i];
+            if count == 0 || right_count[i + 1] == 0 {
+                continue;
+            }
+            let cost = area(acc) * NumCast::from(count).unwrap() +
+                       right_area[i + 1] * NumCast::from(right_count[i + 1]).unwrap();
+            if best.is_none_or(|(best_cost, _)| cost < best_cost) {
+                best = Some((cost, i));
+            }
+        }
+        let (cost, split) = best?;
+
+        let total_area = bounds.surface_area();
+        let traversal: T = NumCast::from(TRAVERSAL_COST).unwrap();
+        let cost = if total_area > T::zero() {
+            traversal + cost / total_area
+        } else {
+            traversal
+        };
+        let leaf_cost: T = NumCast::from(build.len()).unwrap();
+        if build.len() <= MAX_LEAF_SIZE && cost >= leaf_cost {
+            return None;
+        }
+
+        let mut mid = 0;
+        for i in 0..build.len() {
+            if bin(build[i].centroid) <= split {
+                build.swap(i, mid);
+                mid += 1;
+            }
+        }
+        Some(mid)
+    }
+
+    /// The primitives in the order of the tree, which generally differs from the order they
+    /// were passed to `new`.
+    pub fn primitives(&self) -> &[P] {
+        &self.primitives
+    }
+
+    /// Mutable access to the primitives, call `refit` after moving them.
+    pub fn primitives_mut(&mut self) -> &mut [P] {
+        &mut self.primitives
+    }
+
+    /// The bounds of all primitives, `None` if the tree is empty.
+    pub fn bounds(&self) -> Option<Aabb<T>> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    /// Recomputes the bounds of every node after the primitives have moved, keeping the
+    /// topology. The tree gets less efficient the further the primitives move, rebuild it
+    /// with `new` if they moved a lot.
+    pub fn refit(&mut self) {
+        // Children are always stored after their parent.
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            let bounds = if node.is_leaf() {
+                let primitives = &self.primitives[node.primitives()];
+                primitives.iter()
+                    .skip(1)
+                    .fold(primitives[0].bounds(), |acc, p| Aabb::union_aabb(acc, p.bounds()))
+            } else {
+                Aabb::union_aabb(self.nodes[index + 1].bounds,
+                                 self.nodes[node.offset as usize].bounds)
+            };
+            self.nodes[index].bounds = bounds;
+        }
+    }
+
+    /// The nearest hit along the ray. `hit` intersects a single primitive, the ray it is
+    /// called with is shortened to the closest hit found so far.
+    pub fn intersect_with<F>(&self, ray: &Ray<T>, mut hit: F) -> Option<(&P, RayHit<T>)>
+        where F: FnMut(&P, &Ray<T>) -> Option<RayHit<T>>
+    {
+        let mut closest = None;
+        self.traverse(ray,
+                      |node, ray| node.bounds.intersect_ray(ray).is_some(),
+                      |primitive, current| {
+            if let Some(h) = hit(primitive, current) {
+                current.max_time = h.time;
+                closest = Some((primitive, h));
+            }
+            false
+        });
+        closest
+    }
+
+    /// `true` if `hit` returns `true` for any primitive whose bounds the ray passes through.
+    /// Stops at the first hit, which is not necessarily the nearest one.
+    pub fn intersect_p_with<F>(&self, ray: &Ray<T>, mut hit: F) -> bool
+        where F: FnMut(&P, &Ray<T>) -> bool
+    {
+        let mut found = false;
+        self.traverse(ray,
+                      |node, ray| node.bounds.intersect_ray(ray).is_some(),
+                      |primitive, ray| {
+                          found = hit(primitive, ray);
+                          found
+                      });
+        found
+    }
+
+    /// All primitives whose bounds overlap `aabb`.
+    pub fn query_aabb(&self, aabb: Aabb<T>) -> Vec<&P> {
+        self.query(|bounds| bounds.overlap(aabb))
+    }
+
+    /// All primitives whose bounds overlap the sphere.
+    pub fn query_sphere(&self, center: Point3<T>, radius: T) -> Vec<&P> {
+        self.query(|bounds| bounds.overlap_sphere(center, radius))
+    }
+
+    fn query<F>(&self, overlap: F) -> Vec<&P>
+        where F: Fn(Aabb<T>) -> bool
+    {
+        let mut result = Vec::new();
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !overlap(node.bounds) {
+                continue;
+            }
+            if node.is_leaf() {
+                result.extend(self.primitives[node.primitives()]
+                    .iter()
+                    .filter(|primitive| overlap(primitive.bounds())));
+            } else {
+                stack.push(node.offset as usize);
+                stack.push(index + 1);
+            }
+        }
+        result
+    }
+
+    /// Depth first traversal, visiting the near child first. `visit_node` culls subtrees,
+    /// `visit_primitive` may shorten the ray and returns `true` to stop the traversal.
+    fn traverse<'a, N, V>(&'a self, ray: &Ray<T>, visit_node: N, mut visit_primitive: V)
+        where N: Fn(&Node<T>, &Ray<T>) -> bool,
+              V: FnMut(&'a P, &mut Ray<T>) -> bool
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut ray = *ray;
+        let mut stack = Vec::with_capacity(64);
+        let mut current = 0;
+        loop {
+            let node = &self.nodes[current];
+            if visit_node(node, &ray) {
+                if node.is_leaf() {
+                    for primitive in &self.primitives[node.primitives()] {
+                        if visit_primitive(primitive, &mut ray) {
+                            return;
+                        }
+                    }
+                } else {
+                    let (near, far) = if ray.direction[node.axis as usize] < T::zero() {
+                        (node.offset as usize, current + 1)
+                    } else {
+                        (current + 1, node.offset as usize)
+                    };
+                    stack.push(far);
+                    current = near;
+                    continue;
+                }
+            }
+            match stack.pop() {
+                Some(next) => current = next,
+                None => return,
+            }
+        }
+    }
+}
+
+impl<T, P> Bvh<T, P>
+    where T: Float,
+          P: Shape<T>
+{
+    /// The nearest hit of the ray with any of the shapes.
+    pub fn intersect(&self, ray: &Ray<T>) -> Option<(&P, RayHit<T>)> {
+        self.intersect_with(ray, |shape, ray| shape.intersect(ray))
+    }
+
+    /// `true` if the ray hits any of the shapes.
+    pub fn intersect_p(&self, ray: &Ray<T>) -> bool {
+        self.intersect_p_with(ray, |shape, ray| shape.intersect_p(ray))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use bvh::*;
+    use shapes::Sphere;
+    use transform::Transform;
+    use std::rc::Rc;
+    use test_util::Lcg;
+
+    fn spheres(count: usize) -> Vec<Sphere<f32>> {
+        let mut rng = Lcg(7);
+        (0..count)
+            .map(|_| {
+                let center = rng.point(20.).to_vector();
+                Sphere::new(Rc::new(Transform::translate(center)), 0.2 + rng.next())
+            })
+            .collect()
+    }
+
+    fn boxes(count: usize) -> Vec<Aabb<f32>> {
+        let mut rng = Lcg(11);
+        (0..count)
+            .map(|_| {
+                let min = rng.point(20.);
+                Aabb::new(min, min + rng.point(2.).to_vector())
+            })
+            .collect()
+    }
+
+    fn rays(count: usize) -> Vec<Ray<f32>> {
+        let mut rng = Lcg(3);
+        (0..count)
+            .map(|_| {
+                let origin = rng.point(30.) - Vec3f::new(5., 5., 5.);
+                let target = rng.point(20.);
+                Ray::from_start_end(origin, target)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nearest_hit() {
+        let bvh = Bvh::new(spheres(200));
+        let mut hits = 0;
+        for ray in rays(200) {
+            let brute = bvh.primitives()
+                .iter()
+                .filter_map(|s| s.intersect(&ray))
+                .fold(None, |best: Option<RayHit<f32>>, h| match best {
+                    Some(b) if b.time <= h.time => Some(b),
+                    _ => Some(h),
+                });
+            let hit = bvh.intersect(&ray);
+            assert!(hit.map(|h| h.1.time) == brute.map(|h| h.time));
+            assert!(bvh.intersect_p(&ray) == brute.is_some());
+            hits += brute.is_some() as usize;
+        }
+        // Makes sure the test is not vacuous.
+        assert!(hits > 20 && hits < 200);
+    }
+
+    /// The sorted indices of `found` in the primitives of `bvh`.
+    fn indices(bvh: &Bvh<f32, Aabb<f32>>, found: Vec<&Aabb<f32>>) -> Vec<usize> {
+        let mut indices: Vec<_> = found.into_iter()
+            .map(|b| bvh.primitives().iter().position(|p| ::std::ptr::eq(p, b)).unwrap())
+            .collect();
+        indices.sort();
+        indices
+    }
+
+    #[test]
+    fn overlap_queries() {
+        let bvh = Bvh::new(boxes(300));
+        let brute = |overlap: &dyn Fn(&Aabb<f32>) -> bool| -> Vec<usize> {
+            (0..bvh.primitives().len()).filter(|&i| overlap(&bvh.primitives()[i])).collect()
+        };
+        let mut rng = Lcg(5);
+        let mut found = 0;
+        for _ in 0..50 {
+            let min = rng.point(20.);
+            let query = Aabb::new(min, min + Vec3f::new(3., 3., 3.));
+            let expected = brute(&|b| b.overlap(query));
+            assert!(indices(&bvh, bvh.query_aabb(query)) == expected);
+            found += expected.len();
+
+            let (center, radius) = (rng.point(20.), 2.);
+            let expected = brute(&|b| b.overlap_sphere(center, radius));
+            assert!(indices(&bvh, bvh.query_sphere(center, radius)) == expected);
+            found += expected.len();
+        }
+        // Makes sure the test is not vacuous.
+        assert!(found > 50);
+    }
+
+    #[test]
+    fn refit() {
+        let mut bvh = Bvh::new(boxes(100));
+        let offset = Vec3f::new(100., 0., 0.);
+        for b in bvh.primitives_mut() {
+            *b = Aabb::new(b.min() + offset, b.max() + offset);
+        }
+        bvh.refit();
+        let all = bvh.primitives()
+            .iter()
+            .skip(1)
+            .fold(bvh.primitives()[0], |acc, b| Aabb::union_aabb(acc, *b));
+        assert!(bvh.bounds() == Some(all));
+        let query = Aabb::new(Point3f::new(95., -5., -5.), Point3f::new(130., 30., 30.));
+        assert!(bvh.query_aabb(query).len() == 100);
+    }
+
+    #[test]
+    fn degenerate() {
+        let empty: Bvh<f32, Aabb<f32>> = Bvh::new(Vec::new());
+        assert!(empty.bounds().is_none());
+        assert!(empty.query_aabb(Aabb::single(Point3f::zero())).is_empty());
+
+        // Identical primitives cannot be split by position.
+        let same = vec![Aabb::new(Point3f::zero(), Point3f::new(1., 1., 1.)); 50];
+        let bvh = Bvh::new(same);
+        assert!(bvh.query_sphere(Point3f::new(0.5, 0.5, 0.5), 0.1).len() == 50);
+    }
+}
@@ -0,0 +1,706 @@
+//! Collision detection between convex shapes described by their support mapping.
+//! `intersects` and `distance` use GJK, `penetration` uses EPA.
+use num::{Float, NumCast};
+use std::cmp::Ordering;
+use aabb::{Aabb, Sphere};
+use obb::Obb;
+use vector::{Vec3, Point3};
+
+/// A convex shape described by the point furthest along a direction.
+pub trait SupportMap<T>
+    where T: Float
+{
+    /// The point of the shape that lies furthest in the direction `dir`. `dir` does not
+    /// have to be normalized.
+    fn support(&self, dir: Vec3<T>) -> Point3<T>;
+}
+
+impl<T, S> SupportMap<T> for &S
+    where T: Float,
+          S: SupportMap<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        (*self).support(dir)
+    }
+}
+
+impl<T> SupportMap<T> for Aabb<T>
+    where T: Float
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        let (mut p, max) = (self.min(), self.max());
+        for i in 0..3 {
+            if dir[i] > T::zero() {
+                p[i] = max[i];
+            }
+        }
+        p
+    }
+}
+
+impl<T> SupportMap<T> for Sphere<T>
+    where T: Float
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        self.center() + dir.normalize().map_or(Vec3::zero(), |d| d * self.radius())
+    }
+}
+
+impl<T> SupportMap<T> for Obb<T>
+    where T: Float
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        let half_extents = self.half_extents();
+        let axes = self.axes();
+        (0..3).fold(self.center(), |p, i| {
+            let extent = if axes[i].dot(dir) >= T::zero() {
+                half_extents[i]
+            } else {
+                -half_extents[i]
+            };
+            p + axes[i] * extent
+        })
+    }
+}
+
+/// A line segment from `a` to `b`, inflated by `radius`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Capsule<T>
+    where T: Float
+{
+    a: Point3<T>,
+    b: Point3<T>,
+    radius: T,
+}
+
+impl<T> Capsule<T>
+    where T: Float
+{
+    pub fn new(a: Point3<T>, b: Point3<T>, radius: T) -> Self {
+        Capsule {
+            a,
+            b,
+            radius,
+        }
+    }
+
+    pub fn a(&self) -> Point3<T> {
+        self.a
+    }
+
+    pub fn b(&self) -> Point3<T> {
+        self.b
+    }
+
+    pub fn radius(&self) -> T {
+        self.radius
+    }
+}
+
+impl<T> SupportMap<T> for Capsule<T>
+    where T: Float
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        let end = if dir.dot(self.a) >= dir.dot(self.b) {
+            self.a
+        } else {
+            self.b
+        };
+        end + dir.normalize().map_or(Vec3::zero(), |d| d * self.radius)
+    }
+}
+
+/// The convex hull of a point cloud. The points do not have to be on the hull, interior
+/// points are never returned as support points.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvexHull<T>
+    where T: Float
+{
+    points: Vec<Point3<T>>,
+}
+
+impl<T> ConvexHull<T>
+    where T: Float
+{
+    /// Returns `None` if `points` is empty.
+    pub fn new(points: Vec<Point3<T>>) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        Some(ConvexHull { points })
+    }
+
+    pub fn points(&self) -> &[Point3<T>] {
+        &self.points
+    }
+}
+
+impl<T> SupportMap<T> for ConvexHull<T>
+    where T: Float
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        self.points
+            .iter()
+            .skip(1)
+            .fold(self.points[0], |best, &p| if dir.dot(p) > dir.dot(best) { p } else { best })
+    }
+}
+
+/// The Minkowski sum of two shapes, e.g. a box with rounded corners is the sum of a box
+/// and a sphere at the origin.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MinkowskiSum<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> MinkowskiSum<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        MinkowskiSum { a, b }
+    }
+}
+
+impl<T, A, B> SupportMap<T> for MinkowskiSum<A, B>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    fn support(&self, dir: Vec3<T>) -> Point3<T> {
+        self.a.support(dir) + self.b.support(dir).to_vector()
+    }
+}
+
+/// The closest points of two separated shapes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClosestPoints<T>
+    where T: Float
+{
+    pub distance: T,
+    pub point_a: Point3<T>,
+    pub point_b: Point3<T>,
+}
+
+/// The penetration of two overlapping shapes. Moving the second shape by
+/// `normal * depth` separates them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Penetration<T>
+    where T: Float
+{
+    pub depth: T,
+    /// The normalized direction from the first to the second shape.
+    pub normal: Vec3<T>,
+    /// The deepest point of the first shape inside the second.
+    pub point_a: Point3<T>,
+    /// The deepest point of the second shape inside the first.
+    pub point_b: Point3<T>,
+}
+
+/// `true` if the shapes overlap or touch.
+pub fn intersects<T, A, B>(a: &A, b: &B) -> bool
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    match gjk(a, b, true) {
+        Gjk::Intersecting(_) => true,
+        Gjk::Separated(_) => false,
+    }
+}
+
+/// The distance and closest points of two shapes, `None` if they overlap or touch.
+pub fn distance<T, A, B>(a: &A, b: &B) -> Option<ClosestPoints<T>>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    match gjk(a, b, false) {
+        Gjk::Intersecting(_) => None,
+        Gjk::Separated(simplex) => {
+            let (point_a, point_b) = simplex.iter()
+                .fold((Vec3::zero(), Vec3::zero()), |(pa, pb), &(v, weight)| {
+                    (pa + v.a.to_vector() * weight, pb + v.b.to_vector() * weight)
+                });
+            Some(ClosestPoints {
+                distance: combine(&simplex).length(),
+                point_a: point_a.to_point(),
+                point_b: point_b.to_point(),
+            })
+        }
+    }
+}
+
+/// The penetration depth and direction of two shapes, `None` if they are separated. The
+/// depth is zero if the shapes only touch.
+pub fn penetration<T, A, B>(a: &A, b: &B) -> Option<Penetration<T>>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    match gjk(a, b, true) {
+        Gjk::Intersecting(simplex) => Some(epa(a, b, simplex)),
+        Gjk::Separated(_) => None,
+    }
+}
+
+const MAX_GJK_ITERATIONS: usize = 64;
+const MAX_EPA_ITERATIONS: usize = 128;
+
+/// A point of the Minkowski difference `a - b` and the support points it was built from.
+#[derive(Copy, Clone, Debug)]
+struct Vertex<T>
+    where T: Float
+{
+    w: Vec3<T>,
+    a: Point3<T>,
+    b: Point3<T>,
+}
+
+fn support_vertex<T, A, B>(a: &A, b: &B, dir: Vec3<T>) -> Vertex<T>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    let pa = a.support(dir);
+    let pb = b.support(-dir);
+    Vertex {
+        w: pa - pb,
+        a: pa,
+        b: pb,
+    }
+}
+
+/// A simplex with the barycentric weights of the point closest to the origin.
+type Simplex<T> = Vec<(Vertex<T>, T)>;
+
+fn combine<T: Float>(simplex: &[(Vertex<T>, T)]) -> Vec3<T> {
+    simplex.iter().fold(Vec3::zero(), |acc, &(v, weight)| acc + v.w * weight)
+}
+
+enum Gjk<T>
+    where T: Float
+{
+    /// The simplex that encloses the origin.
+    Intersecting(Vec<Vertex<T>>),
+    /// The simplex closest to the origin.
+    Separated(Simplex<T>),
+}
+
+/// Gilbert–Johnson–Keerthi on the Minkowski difference `a - b`. With `early_out` it stops
+/// as soon as a separating axis is found, the simplex is then not the closest one.
+fn gjk<T, A, B>(a: &A, b: &B, early_out: bool) -> Gjk<T>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    let tolerance = T::epsilon() * NumCast::from(100).unwrap();
+    let first = support_vertex(a, b, Vec3::new(T::one(), T::zero(), T::zero()));
+    let mut simplex = vec![(first, T::one())];
+    let mut v = first.w;
+    for _ in 0..MAX_GJK_ITERATIONS {
+        let vv = v.length_sq();
+        let max_w = simplex.iter().fold(T::zero(), |acc, &(s, _)| acc.max(s.w.length_sq()));
+        if vv <= tolerance * tolerance * max_w || vv == T::zero() {
+            return Gjk::Intersecting(simplex.into_iter().map(|(s, _)| s).collect());
+        }
+        let w = support_vertex(a, b, -v);
+        let vw = v.dot(w.w);
+        if early_out && vw > T::zero() {
+            return Gjk::Separated(simplex);
+        }
+        // No more progress towards the origin, `v` is the closest point.
+        if vv - vw <= tolerance * vv || simplex.iter().any(|&(s, _)| s.w == w.w) {
+            return Gjk::Separated(simplex);
+        }
+        simplex.push((w, T::zero()));
+        simplex = match closest_simplex(&simplex) {
+            Some(closest) => closest,
+            None => return Gjk::Intersecting(simplex.into_iter().map(|(s, _)| s).collect()),
+        };
+        v = combine(&simplex);
+    }
+    Gjk::Separated(simplex)
+}
+
+/// The smallest sub simplex that contains the point closest to the origin, `None` if the
+/// tetrahedron contains the origin.
+fn closest_simplex<T: Float>(simplex: &[(Vertex<T>, T)]) -> Option<Simplex<T>> {
+    match simplex.len() {
+        1 => Some(vec![(simplex[0].0, T::one())]),
+        2 => Some(closest_segment(simplex[0].0, simplex[1].0)),
+        3 => Some(closest_triangle(simplex[0].0, simplex[1].0, simplex[2].0)),
+        _ => closest_tetrahedron(simplex[0].0, simplex[1].0, simplex[2].0, simplex[3].0),
+    }
+}
+
+fn closest_segment<T: Float>(a: Vertex<T>, b: Vertex<T>) -> Simplex<T> {
+    let ab = b.w - a.w;
+    let t = -a.w.dot(ab);
+    if t <= T::zero() {
+        return vec![(a, T::one())];
+    }
+    let length_sq = ab.length_sq();
+    if t >= length_sq {
+        return vec![(b, T::one())];
+    }
+    let t = t / length_sq;
+    vec![(a, T::one() - t), (b, t)]
+}
+
+/// The closest point on a triangle to the origin, Ericson, Real-Time Collision Detection 5.1.5.
+fn closest_triangle<T: Float>(a: Vertex<T>, b: Vertex<T>, c: Vertex<T>) -> Simplex<T> {
+    let zero = T::zero();
+    let ab = b.w - a.w;
+    let ac = c.w - a.w;
+    let d1 = -ab.dot(a.w);
+    let d2 = -ac.dot(a.w);
+    if d1 <= zero && d2 <= zero {
+        return vec![(a, T::one())];
+    }
+    let d3 = -ab.dot(b.w);
+    let d4 = -ac.dot(b.w);
+    if d3 >= zero && d4 <= d3 {
+        return vec![(b, T::one())];
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= zero && d1 >= zero && d3 <= zero {
+        let t = d1 / (d1 - d3);
+        return vec![(a, T::one() - t), (b, t)];
+    }
+    let d5 = -ab.dot(c.w);
+    let d6 = -ac.dot(c.w);
+    if d6 >= zero && d5 <= d6 {
+        return vec![(c, T::one())];
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= zero && d2 >= zero && d6 <= zero {
+        let t = d2 / (d2 - d6);
+        return vec![(a, T::one() - t), (c, t)];
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= zero && d4 - d3 >= zero && d5 - d6 >= zero {
+        let t = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return vec![(b, T::one() - t), (c, t)];
+    }
+    let denom = T::one() / (va + vb + vc);
+    let (v, w) = (vb * denom, vc * denom);
+    vec![(a, T::one() - v - w), (b, v), (c, w)]
+}
+
+fn closest_tetrahedron<T: Float>(a: Vertex<T>,
+                                 b: Vertex<T>,
+                                 c: Vertex<T>,
+                                 d: Vertex<T>)
+                                 -> Option<Simplex<T>> {
+    let faces = [(a, b, c, d), (a, d, b, c), (a, c, d, b), (b, d, c, a)];
+    let mut best: Option<(T, Simplex<T>)> = None;
+    for &(p, q, r, opposite) in faces.iter() {
+        let n = (q.w - p.w).cross(r.w - p.w);
+        let side_origin = -n.dot(p.w);
+        let side_opposite = n.dot(opposite.w - p.w);
+        // The origin can only be closest to faces it lies in front of. A flat tetrahedron
+        // has no front, all of its faces are checked.
+        if side_opposite != T::zero() && side_origin * side_opposite >= T::zero() {
+            continue;
+        }
+        let closest = closest_triangle(p, q, r);
+        let dist = combine(&closest).length_sq();
+        if best.as_ref().is_none_or(|&(best_dist, _)| dist < best_dist) {
+            best = Some((dist, closest));
+        }
+    }
+    best.map(|(_, simplex)| simplex)
+}
+
+/// A triangle of the expanding polytope, `normal` points away from the origin.
+#[derive(Copy, Clone, Debug)]
+struct Face<T>
+    where T: Float
+{
+    indices: [usize; 3],
+    normal: Vec3<T>,
+    distance: T,
+}
+
+impl<T> Face<T>
+    where T: Float
+{
+    /// Orients the face away from `interior`, a point inside the polytope.
+    fn new(vertices: &[Vertex<T>], mut indices: [usize; 3], interior: Vec3<T>) -> Self {
+        let [a, b, c] = [vertices[indices[0]].w, vertices[indices[1]].w, vertices[indices[2]].w];
+        let mut normal = (b - a).cross(c - a);
+        if normal.dot(a - interior) < T::zero() {
+            normal = -normal;
+            indices.swap(1, 2);
+        }
+        match normal.normalize() {
+            Some(normal) => {
+                Face {
+                    indices,
+                    normal,
+                    distance: normal.dot(a),
+                }
+            }
+            // Degenerate faces are never the closest one.
+            None => {
+                Face {
+                    indices,
+                    normal: Vec3::zero(),
+                    distance: T::infinity(),
+                }
+            }
+        }
+    }
+}
+
+/// Expanding polytope algorithm, starting from the GJK simplex that encloses the origin.
+fn epa<T, A, B>(a: &A, b: &B, simplex: Vec<Vertex<T>>) -> Penetration<T>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    let mut vertices = match blow_up(a, b, simplex) {
+        Ok(vertices) => vertices,
+        Err(touching) => {
+            // The Minkowski difference is flat or the origin is on a single point of its
+            // boundary, the shapes touch without overlapping.
+            return Penetration {
+                depth: T::zero(),
+                normal: Vec3::new(T::one(), T::zero(), T::zero()),
+                point_a: touching.a,
+                point_b: touching.b,
+            };
+        }
+    };
+    let quarter: T = NumCast::from(0.25).unwrap();
+    let interior = (vertices[0].w + vertices[1].w + vertices[2].w + vertices[3].w) * quarter;
+    let mut faces: Vec<Face<T>> = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]]
+        .iter()
+        .map(|&indices| Face::new(&vertices, indices, interior))
+        .collect();
+    let tolerance = T::epsilon().sqrt();
+
+    let mut closest = faces[0];
+    for _ in 0..MAX_EPA_ITERATIONS {
+        closest = *faces.iter()
+            .min_by(|f0, f1| f0.distance.partial_cmp(&f1.distance).unwrap_or(Ordering::Greater))
+            .expect("The polytope has faces");
+        let v = support_vertex(a, b, closest.normal);
+        let support_distance = closest.normal.dot(v.w);
+        if support_distance - closest.distance <= tolerance * support_distance.max(T::one()) {
+            break;
+        }
+
+        // Removes every face the new vertex can see and closes the hole along the horizon,
+        // the edges that belonged to exactly one removed face.
+        let index = vertices.len();
+        vertices.push(v);
+        let mut horizon: Vec<(usize, usize)> = Vec::new();
+        faces.retain(|face| {
+            let visible = face.distance.is_infinite() ||
+                          face.normal.dot(v.w - vertices[face.indices[0]].w) > T::zero();
+            if visible {
+                for k in 0..3 {
+                    let edge = (face.indices[k], face.indices[(k + 1) % 3]);
+                    match horizon.iter().position(|&(i, j)| (j, i) == edge) {
+                        Some(shared) => {
+                            horizon.swap_remove(shared);
+                        }
+                        None => horizon.push(edge),
+                    }
+                }
+            }
+            !visible
+        });
+        if horizon.is_empty() {
+            break;
+        }
+        for (i, j) in horizon {
+            faces.push(Face::new(&vertices, [i, j, index], interior));
+        }
+    }
+
+    let [i, j, k] = closest.indices;
+    let (va, vb, vc) = (vertices[i], vertices[j], vertices[k]);
+    let (u, v, w) = barycentric(closest.normal * closest.distance, va.w, vb.w, vc.w);
+    let point = |pa: Point3<T>, pb: Point3<T>, pc: Point3<T>| {
+        (pa.to_vector() * u + pb.to_vector() * v + pc.to_vector() * w).to_point()
+    };
+    Penetration {
+        depth: closest.distance,
+        normal: closest.normal,
+        point_a: point(va.a, vb.a, vc.a),
+        point_b: point(va.b, vb.b, vc.b),
+    }
+}
+
+/// Extends the GJK simplex to a tetrahedron that still encloses the origin. Returns the
+/// touching vertex if the Minkowski difference has no volume around the origin.
+fn blow_up<T, A, B>(a: &A, b: &B, mut simplex: Vec<Vertex<T>>) -> Result<Vec<Vertex<T>>, Vertex<T>>
+    where T: Float,
+          A: SupportMap<T>,
+          B: SupportMap<T>
+{
+    let (zero, one) = (T::zero(), T::one());
+    let axes = [Vec3::new(one, zero, zero), Vec3::new(zero, one, zero), Vec3::new(zero, zero, one)];
+    let epsilon = T::epsilon();
+
+    if simplex.len() == 1 {
+        for dir in axes.iter().flat_map(|&axis| vec![axis, -axis]) {
+            let v = support_vertex(a, b, dir);
+            if (v.w - simplex[0].w).length_sq() > epsilon {
+                simplex.push(v);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 2 {
+        let d = simplex[1].w - simplex[0].w;
+        // Searches perpendicular to the segment, starting from the least aligned axis.
+        let axis = axes.iter()
+            .cloned()
+            .min_by(|x, y| {
+                d.dot(*x).abs().partial_cmp(&d.dot(*y).abs()).unwrap_or(Ordering::Greater)
+            })
+            .expect("Three axes");
+        let e = d.cross(axis);
+        let f = d.cross(e);
+        for &dir in [e, -e, f, -f].iter() {
+            let v = support_vertex(a, b, dir);
+            if d.cross(v.w - simplex[0].w).length_sq() > epsilon * d.length_sq() {
+                simplex.push(v);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 3 {
+        let n = (simplex[1].w - simplex[0].w).cross(simplex[2].w - simplex[0].w);
+        for &dir in [n, -n].iter() {
+            let v = support_vertex(a, b, dir);
+            if n.dot(v.w - simplex[0].w).abs() > epsilon * n.length() {
+                simplex.push(v);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 4 {
+        Ok(simplex)
+    } else {
+        Err(simplex[0])
+    }
+}
+
+/// The barycentric coordinates of `p` projected into the triangle `a, b, c`.
+fn barycentric<T: Float>(p: Vec3<T>, a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> (T, T, T) {
+    let (v0, v1, v2) = (b - a, c - a, p - a);
+    let (d00, d01, d11) = (v0.dot(v0), v0.dot(v1), v1.dot(v1));
+    let (d20, d21) = (v2.dot(v0), v2.dot(v1));
+    let denom = d00 * d11 - d01 * d01;
+    if denom == T::zero() {
+        return (T::one(), T::zero(), T::zero());
+    }
+    let v = (d11 * d20 - d01 * d21) / denom;
+    let w = (d00 * d21 - d01 * d20) / denom;
+    (T::one() - v - w, v, w)
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use collision::*;
+    use quaternion::Quaternion;
+    use unit::Degrees;
+
+    fn unit_box(x: f32, y: f32, z: f32) -> Aabb<f32> {
+        Aabb::new(Point3f::new(x, y, z), Point3f::new(x + 1., y + 1., z + 1.))
+    }
+
+    #[test]
+    fn support() {
+        let aabb = unit_box(0., 0., 0.);
+        assert!(aabb.support(Vec3f::new(1., -1., 1.)) == Point3f::new(1., 0., 1.));
+        let sphere = Sphere::new(Point3f::new(1., 0., 0.), 2.);
+        assert!(sphere.support(Vec3f::new(0., 3., 0.)) == Point3f::new(1., 2., 0.));
+        let capsule = Capsule::new(Point3f::zero(), Point3f::new(0., 0., 4.), 1.);
+        assert!(capsule.support(Vec3f::new(0., 0., 1.)) == Point3f::new(0., 0., 5.));
+        let hull = ConvexHull::new(vec![Point3f::zero(), Point3f::new(1., 1., 0.)]).unwrap();
+        assert!(hull.support(Vec3f::new(1., 0., 0.)) == Point3f::new(1., 1., 0.));
+        assert!(ConvexHull::<f32>::new(Vec::new()).is_none());
+        // A rounded box is the sum of a box and a sphere around the origin.
+        let rounded = MinkowskiSum::new(aabb, Sphere::new(Point3f::zero(), 0.5));
+        assert!(rounded.support(Vec3f::new(0., 1., 0.)) == Point3f::new(0., 1.5, 0.));
+    }
+
+    #[test]
+    fn sphere_distance() {
+        let a = Sphere::new(Point3f::new(0., 0., 0.), 1.);
+        let b = Sphere::new(Point3f::new(4., 0., 0.), 1.5);
+        assert!(!intersects(&a, &b));
+        let closest = distance(&a, &b).unwrap();
+        assert!((closest.distance - 1.5).abs() < 1e-3);
+        assert!(closest.point_a.distance(Point3f::new(1., 0., 0.)) < 1e-2);
+        assert!(closest.point_b.distance(Point3f::new(2.5, 0., 0.)) < 1e-2);
+        assert!(penetration(&a, &b).is_none());
+    }
+
+    #[test]
+    fn box_distance() {
+        let a = unit_box(0., 0., 0.);
+        let b = unit_box(3., 2., 0.5);
+        let closest = distance(&a, &b).unwrap();
+        assert!((closest.distance - 5f32.sqrt()).abs() < 1e-4);
+        assert!(closest.point_a.x() == 1. && closest.point_a.y() == 1.);
+        assert!(closest.point_b.x() == 3. && closest.point_b.y() == 2.);
+
+        let hull = ConvexHull::new(vec![Point3f::new(0., 0., 3.),
+                                        Point3f::new(1., 0., 3.),
+                                        Point3f::new(0., 1., 3.),
+                                        Point3f::new(0., 0., 4.)])
+            .unwrap();
+        assert!((distance(&a, &hull).unwrap().distance - 2.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn capsule_obb() {
+        let capsule = Capsule::new(Point3f::new(-5., 0., 0.), Point3f::new(5., 0., 0.), 0.5);
+        let rotation = Quaternion::new(Vec3f::new(0., 0., 1.), Degrees::new(45.));
+        let obb = Obb::new(Point3f::new(0., 2., 0.), Vec3f::new(1., 1., 1.), rotation);
+        // The corner of the rotated box points down to the capsule.
+        let closest = distance(&capsule, &obb).unwrap();
+        assert!((closest.distance - (2. - 2f32.sqrt() - 0.5)).abs() < 1e-4);
+        let lower = Obb::new(Point3f::new(0., 1.5, 0.), Vec3f::new(1., 1., 1.), rotation);
+        assert!(intersects(&capsule, &lower));
+        assert!(distance(&capsule, &lower).is_none());
+    }
+
+    #[test]
+    fn box_penetration() {
+        let a = unit_box(0., 0., 0.);
+        let b = unit_box(0.75, 0.1, -0.1);
+        assert!(intersects(&a, &b));
+        let p = penetration(&a, &b).unwrap();
+        assert!((p.depth - 0.25).abs() < 1e-5);
+        assert!(p.normal.distance(Vec3f::new(1., 0., 0.)) < 1e-5);
+        assert!((p.point_a.x() - 1.).abs() < 1e-5);
+        assert!((p.point_b.x() - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn sphere_penetration() {
+        let a = Sphere::new(Point3f::new(0., 0., 0.), 1.);
+        let b = Sphere::new(Point3f::new(0., 1.5, 0.), 1.);
+        let p = penetration(&a, &b).unwrap();
+        assert!((p.depth - 0.5).abs() < 1e-2);
+        assert!(p.normal.distance(Vec3f::new(0., 1., 0.)) < 1e-1);
+        // Moving the second sphere out along the normal separates them.
+        let moved = Sphere::new(b.center() + p.normal * (p.depth + 1e-2), 1.);
+        assert!(!intersects(&a, &moved));
+    }
+
+    #[test]
+    fn touching() {
+        let a = unit_box(0., 0., 0.);
+        let b = unit_box(1., 0., 0.);
+        assert!(intersects(&a, &b));
+        assert!(penetration(&a, &b).unwrap().depth.abs() < 1e-5);
+    }
+}
@@ -0,0 +1,307 @@
+use num::{Float, NumCast};
+use vector::{Vec3, Point3};
+use matrix::Mat4x4;
+use quaternion::Quaternion;
+use transform::Transform;
+use std::ops::{Add, Sub, Mul, Neg};
+
+/// A rigid transformation, a rotation followed by a translation, stored as `real + ε dual`.
+/// Unit dual quaternions compose like matrices, `a * b` applies `b` first.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DualQuaternion<T>
+    where T: Float
+{
+    real: Quaternion<T>,
+    dual: Quaternion<T>,
+}
+
+impl<T> DualQuaternion<T>
+    where T: Float
+{
+    pub fn raw(real: Quaternion<T>, dual: Quaternion<T>) -> Self {
+        DualQuaternion { real, dual }
+    }
+
+    pub fn identity() -> Self {
+        Self::raw(Quaternion::identity(), Quaternion::raw(Vec3::zero(), T::zero()))
+    }
+
+    /// Rotates by the normalized quaternion `rotation` and then translates by `translation`.
+    pub fn from_rotation_translation(rotation: Quaternion<T>, translation: Vec3<T>) -> Self {
+        let half: T = NumCast::from(0.5).unwrap();
+        let dual = Quaternion::raw(translation, T::zero()) * rotation * half;
+        Self::raw(rotation, dual)
+    }
+
+    pub fn from_rotation(rotation: Quaternion<T>) -> Self {
+        Self::from_rotation_translation(rotation, Vec3::zero())
+    }
+
+    pub fn from_translation(translation: Vec3<T>) -> Self {
+        Self::from_rotation_translation(Quaternion::identity(), translation)
+    }
+
+    /// Extracts the rotation and the translation of a matrix without scale, shear or
+    /// projection.
+    pub fn from_mat4(m: &Mat4x4<T>) -> Self {
+        let translation = Vec3::new(m[0][3], m[1][3], m[2][3]);
+        Self::from_rotation_translation(Quaternion::from_mat4(m), translation)
+    }
+
+    /// Expects a rigid transform, see `from_mat4`.
+    pub fn from_transform(t: &Transform<T>) -> Self {
+        Self::from_mat4(t.matrix())
+    }
+
+    /// The rotational part.
+    pub fn real(&self) -> Quaternion<T> {
+        self.real
+    }
+
+    /// The translational part, `translation * rotation / 2` for a unit dual quaternion.
+    pub fn dual(&self) -> Quaternion<T> {
+        self.dual
+    }
+
+    /// The rotation of a unit dual quaternion.
+    pub fn rotation(&self) -> Quaternion<T> {
+        self.real
+    }
+
+    /// The translation of a unit dual quaternion.
+    pub fn translation(&self) -> Vec3<T> {
+        let two: T = NumCast::from(2.0).unwrap();
+        (self.dual * self.real.conjugate()).v() * two
+    }
+
+    /// Conjugates both parts. For a unit dual quaternion this is the inverse.
+    pub fn conjugate(self) -> Self {
+        Self::raw(self.real.conjugate(), self.dual.conjugate())
+    }
+
+    pub fn inverse(self) -> Self {
+        let real = self.real.inverse();
+        Self::raw(real, -(real * self.dual * real))
+    }
+
+    /// Scales the real part to unit length and removes the part of the dual part that is
+    /// parallel to it. Returns `None` if the real part is zero.
+    pub fn normalize(self) -> Option<Self> {
+        let len = self.real.length();
+        if len == T::zero() {
+            return None;
+        }
+        let real = self.real / len;
+        let dual = self.dual / len;
+        Some(Self::raw(real, dual - real * real.dot(dual)))
+    }
+
+    pub fn transform_point(&self, p: Point3<T>) -> Point3<T> {
+        (self.real * p.to_vector() + self.translation()).to_point()
+    }
+
+    /// Transforms a direction, the translation is ignored.
+    pub fn transform_vector(&self, v: Vec3<T>) -> Vec3<T> {
+        self.real * v
+    }
+
+    /// Raises a unit dual quaternion to the power `exponent` by scaling the angle and the
+    /// distance of its screw motion.
+    pub fn powf(self, exponent: T) -> Self {
+        let two: T = NumCast::from(2.0).unwrap();
+        let dq = if self.real.w() < T::zero() { -self } else { self };
+        let translation = dq.translation();
+        let sin_half = dq.real.v().length();
+        // Without a rotation axis the screw motion degenerates to a translation.
+        let threshold: T = NumCast::from(1e-6).unwrap();
+        if sin_half < threshold {
+            let rotation = Quaternion::identity().nlerp(dq.real, exponent);
+            return Self::from_rotation_translation(rotation, translation * exponent);
+        }
+        let axis = dq.real.v() / sin_half;
+        let angle = two * sin_half.atan2(dq.real.w());
+        let pitch = translation.dot(axis);
+        let cot_half = dq.real.w() / sin_half;
+        let moment = (translation.cross(axis) + (translation - axis * pitch) * cot_half) / two;
+
+        let half_angle = angle * exponent / two;
+        let half_pitch = pitch * exponent / two;
+        let (sin, cos) = half_angle.sin_cos();
+        Self::raw(Quaternion::raw(axis * sin, cos),
+                  Quaternion::raw(moment * sin + axis * (half_pitch * cos), -half_pitch * sin))
+    }
+
+    /// Screw linear interpolation. Interpolates the rotation and the translation along a
+    /// single screw motion with constant velocity and always takes the shortest path. Both
+    /// dual quaternions are expected to be normalized.
+    pub fn sclerp(self, torwards: Self, scale: T) -> Self {
+        let torwards = if self.real.dot(torwards.real) < T::zero() {
+            -torwards
+        } else {
+            torwards
+        };
+        self * (self.conjugate() * torwards).powf(scale)
+    }
+
+    /// Dual quaternion linear blending of `(weight, transform)` pairs, as used for skinning.
+    /// Every dual quaternion is flipped into the hemisphere of the first one before the
+    /// weighted sum is normalized. Returns `None` if the blended real part is zero.
+    pub fn dlb(blend: &[(T, Self)]) -> Option<Self> {
+        let pivot = match blend.first() {
+            Some(&(_, dq)) => dq.real,
+            None => return None,
+        };
+        blend.iter()
+            .fold(Self::raw(Quaternion::raw(Vec3::zero(), T::zero()),
+                            Quaternion::raw(Vec3::zero(), T::zero())),
+                  |acc, &(weight, dq)| {
+                      let weight = if pivot.dot(dq.real) < T::zero() { -weight } else { weight };
+                      acc + dq * weight
+                  })
+            .normalize()
+    }
+
+    /// The matrix of a unit dual quaternion.
+    pub fn to_mat4(self) -> Mat4x4<T> {
+        let mut m = self.real.to_mat4();
+        let translation = self.translation();
+        m[0][3] = translation.x();
+        m[1][3] = translation.y();
+        m[2][3] = translation.z();
+        m
+    }
+}
+
+impl<T> Mul for DualQuaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::raw(self.real * other.real,
+                  self.real * other.dual + self.dual * other.real)
+    }
+}
+
+impl<T> Add for DualQuaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::raw(self.real + other.real, self.dual + other.dual)
+    }
+}
+
+impl<T> Sub for DualQuaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::raw(self.real - other.real, self.dual - other.dual)
+    }
+}
+
+impl<T> Neg for DualQuaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::raw(-self.real, -self.dual)
+    }
+}
+
+impl<T> Mul<T> for DualQuaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn mul(self, other: T) -> Self {
+        Self::raw(self.real * other, self.dual * other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use matrix::*;
+    use quaternion::Quaternion;
+    use transform::Transform;
+    use unit::Radians;
+    use dual_quaternion::*;
+    use std::f32::consts::PI;
+
+    fn dq(axis: Vec3f, angle: f32, translation: Vec3f) -> DualQuaternion<f32> {
+        let rotation = Quaternion::new(axis, Radians::new(angle));
+        DualQuaternion::from_rotation_translation(rotation, translation)
+    }
+
+    #[test]
+    fn transform_point() {
+        let a = dq(Vec3f::new(0., 0., 1.), PI / 2., Vec3f::new(1., 2., 3.));
+        let p = Point3f::new(1., 0., 0.);
+        assert_abs_diff_eq!(a.transform_point(p), Point3f::new(1., 3., 3.), epsilon = 1e-6);
+        assert_abs_diff_eq!(a.transform_vector(p.to_vector()),
+                            Vec3f::new(0., 1., 0.),
+                            epsilon = 1e-6);
+        assert_abs_diff_eq!(a.translation(), Vec3f::new(1., 2., 3.), epsilon = 1e-6);
+        assert_abs_diff_eq!(a.inverse().transform_point(Point3f::new(1., 3., 3.)),
+                            p,
+                            epsilon = 1e-6);
+        assert_abs_diff_eq!(a.conjugate(), a.inverse(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn matrices() {
+        let t = &Transform::translate(Vec3f::new(-2., 0.5, 4.)) *
+                &Transform::rotation_y(Radians::new(0.7));
+        let a = DualQuaternion::from_transform(&t);
+        assert_abs_diff_eq!(a.to_mat4(), *t.matrix(), epsilon = 1e-6);
+        let b = dq(Vec3f::new(1., 2., -1.), 2.3, Vec3f::new(0., 1., 5.));
+        let m = b.to_mat4();
+        assert_abs_diff_eq!(DualQuaternion::from_mat4(&m), b, epsilon = 1e-6);
+        // Composition matches the product of the matrices.
+        assert_abs_diff_eq!((a * b).to_mat4(), a.to_mat4() * m, epsilon = 1e-5);
+        assert_abs_diff_eq!((a * a.inverse()).to_mat4(), Mat4x4f::identity(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn normalize() {
+        let a = dq(Vec3f::new(0., 1., 0.), 1.1, Vec3f::new(3., 0., -1.));
+        let scaled = a * 2.5;
+        assert_abs_diff_eq!(scaled.normalize().unwrap(), a, epsilon = 1e-6);
+        assert_abs_diff_eq!((scaled * scaled.inverse()).to_mat4(),
+                            Mat4x4f::identity(),
+                            epsilon = 1e-6);
+        assert!((a * 0.0).normalize().is_none());
+    }
+
+    #[test]
+    fn sclerp() {
+        let a = DualQuaternion::<f32>::identity();
+        let b = dq(Vec3f::new(0., 0., 1.), PI / 2., Vec3f::new(0., 0., 4.));
+        assert_abs_diff_eq!(a.sclerp(b, 0.0), a, epsilon = 1e-6);
+        assert_abs_diff_eq!(a.sclerp(b, 1.0), b, epsilon = 1e-6);
+        // A screw along the z axis, half the angle and half the distance.
+        let half = dq(Vec3f::new(0., 0., 1.), PI / 4., Vec3f::new(0., 0., 2.));
+        assert_abs_diff_eq!(a.sclerp(b, 0.5), half, epsilon = 1e-6);
+        assert_abs_diff_eq!(a.sclerp(-b, 0.5), half, epsilon = 1e-6);
+        // Pure translations interpolate linearly.
+        let c = DualQuaternion::from_translation(Vec3f::new(2., -4., 0.));
+        assert_abs_diff_eq!(a.sclerp(c, 0.25).translation(),
+                            Vec3f::new(0.5, -1., 0.),
+                            epsilon = 1e-6);
+        // The end points are reproduced for a general pair.
+        let d = dq(Vec3f::new(1., 1., 0.), -2., Vec3f::new(1., 2., 3.));
+        assert_abs_diff_eq!(b.sclerp(d, 1.0).to_mat4(), d.to_mat4(), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn dlb() {
+        let a = dq(Vec3f::new(0., 0., 1.), 0.2, Vec3f::new(1., 0., 0.));
+        let b = dq(Vec3f::new(0., 0., 1.), 0.6, Vec3f::new(1., 0., 0.));
+        let blended = DualQuaternion::dlb(&[(0.5, a), (0.5, -b)]).unwrap();
+        let expected = dq(Vec3f::new(0., 0., 1.), 0.4, Vec3f::new(1., 0., 0.));
+        assert_abs_diff_eq!(blended, expected, epsilon = 1e-6);
+        assert_abs_diff_eq!(DualQuaternion::dlb(&[(1.0, b)]).unwrap(), b, epsilon = 1e-6);
+        assert!(DualQuaternion::<f32>::dlb(&[]).is_none());
+        assert!(DualQuaternion::dlb(&[(0.0, a)]).is_none());
+    }
+}
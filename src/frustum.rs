@@ -0,0 +1,224 @@
+use num::Float;
+use vector::{Vec4, Point3};
+use matrix::{Mat4x4, DepthRange};
+use aabb::{Aabb, Sphere};
+use plane::{Plane, Side};
+
+/// How a volume relates to a frustum.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Containment {
+    Inside,
+    Outside,
+    /// The volume crosses at least one plane. Volumes close to the edges of the frustum may
+    /// be reported as intersecting even though they are outside.
+    Intersecting,
+}
+
+/// The volume a projection matrix maps into the clip volume. The normals of the planes
+/// point inside.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum<T>
+    where T: Float
+{
+    /// Left, right, bottom, top, near and far. `None` for a plane at infinity, e.g. the far
+    /// plane of an infinite perspective projection.
+    planes: [Option<Plane<T>>; 6],
+}
+
+impl<T> Frustum<T>
+    where T: Float
+{
+    /// Extracts the planes of a projection or view projection matrix, Gribb and Hartmann,
+    /// "Fast Extraction of Viewing Frustum Planes from the World-View-Projection Matrix".
+    /// `depth` has to match the projection. For reversed z projections the near and the far
+    /// plane swap places.
+    pub fn from_matrix(m: &Mat4x4<T>, depth: DepthRange) -> Self {
+        let (r0, r1, r2, r3) = (m[0], m[1], m[2], m[3]);
+        let near: Vec4<T> = match depth {
+            DepthRange::NegativeOneToOne => r3 + r2,
+            DepthRange::ZeroToOne => r2,
+        };
+        let plane = Plane::from_vec4;
+        Frustum {
+            planes: [plane(r3 + r0),
+                     plane(r3 - r0),
+                     plane(r3 + r1),
+                     plane(r3 - r1),
+                     plane(near),
+                     plane(r3 - r2)],
+        }
+    }
+
+    /// Left, right, bottom, top, near and far.
+    pub fn planes(&self) -> &[Option<Plane<T>>; 6] {
+        &self.planes
+    }
+
+    pub fn contains_point(&self, point: Point3<T>) -> bool {
+        self.planes.iter().flatten().all(|plane| plane.signed_distance(point) >= T::zero())
+    }
+
+    pub fn intersects_aabb(&self, aabb: Aabb<T>) -> Containment {
+        self.classify(|plane| plane.classify_aabb(aabb))
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere<T>) -> Containment {
+        self.classify(|plane| plane.classify_sphere(sphere))
+    }
+
+    fn classify<F>(&self, side: F) -> Containment
+        where F: Fn(&Plane<T>) -> Side
+    {
+        let mut result = Containment::Inside;
+        for plane in self.planes.iter().flatten() {
+            match side(plane) {
+                Side::Back => return Containment::Outside,
+                Side::Intersecting => result = Containment::Intersecting,
+                Side::Front => (),
+            }
+        }
+        result
+    }
+
+    /// `intersects_aabb` for many boxes, the planes are only prepared once.
+    pub fn intersects_aabbs(&self, aabbs: &[Aabb<T>]) -> Vec<Containment> {
+        // The normal and its absolute value for the projection of the half extents.
+        let planes: Vec<_> = self.planes
+            .iter()
+            .flatten()
+            .map(|plane| {
+                let n = plane.normal().to_vector();
+                (n, n.map(|v| v.abs()), plane.distance())
+            })
+            .collect();
+        let two = T::one() + T::one();
+        aabbs.iter()
+            .map(|aabb| {
+                // Twice the center and twice the half extents, saves the multiplications.
+                let center = (aabb.min() + aabb.max().to_vector()).to_vector();
+                let extents = aabb.diagonale();
+                let mut result = Containment::Inside;
+                for &(n, abs_n, distance) in planes.iter() {
+                    let d = n.dot(center) + distance * two;
+                    let r = abs_n.dot(extents);
+                    if d < -r {
+                        return Containment::Outside;
+                    }
+                    if d <= r {
+                        result = Containment::Intersecting;
+                    }
+                }
+                result
+            })
+            .collect()
+    }
+
+    /// The corners of the near plane followed by the corners of the far plane, each in the
+    /// order left bottom, right bottom, left top, right top. `None` if a plane is at
+    /// infinity.
+    pub fn corners(&self) -> Option<[Point3<T>; 8]> {
+        let p = &self.planes;
+        let mut corners = [Point3::zero(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let x = p[i & 1].as_ref()?;
+            let y = p[2 + (i >> 1 & 1)].as_ref()?;
+            let z = p[4 + (i >> 2)].as_ref()?;
+            *corner = Plane::intersect_planes(x, y, z)?;
+        }
+        Some(corners)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use frustum::*;
+    use matrix::Handedness;
+    use transform::Transform;
+    use unit::Degrees;
+
+    fn perspective(depth: DepthRange) -> Transform<f32> {
+        Transform::perspective(Degrees::new(90.), 1., 1., 10., Handedness::Right, depth)
+    }
+
+    #[test]
+    fn points() {
+        for &depth in [DepthRange::NegativeOneToOne, DepthRange::ZeroToOne].iter() {
+            let frustum = Frustum::from_matrix(perspective(depth).matrix(), depth);
+            assert!(frustum.contains_point(Point3f::new(0., 0., -5.)));
+            assert!(frustum.contains_point(Point3f::new(4.9, 0., -5.)));
+            assert!(!frustum.contains_point(Point3f::new(5.1, 0., -5.)));
+            assert!(!frustum.contains_point(Point3f::new(0., 0., -0.5)));
+            assert!(!frustum.contains_point(Point3f::new(0., 0., -11.)));
+            assert!(!frustum.contains_point(Point3f::new(0., 0., 5.)));
+        }
+    }
+
+    #[test]
+    fn view_projection() {
+        let depth = DepthRange::ZeroToOne;
+        let view = Transform::look_at(Point3f::new(10., 0., 0.),
+                                      Point3f::zero(),
+                                      Vec3f::new(0., 1., 0.),
+                                      Handedness::Right);
+        let view_projection = &perspective(depth) * &view;
+        let frustum = Frustum::from_matrix(view_projection.matrix(), depth);
+        assert!(frustum.contains_point(Point3f::zero()));
+        assert!(!frustum.contains_point(Point3f::new(12., 0., 0.)));
+
+        let corners = frustum.corners().unwrap();
+        let expected = [Point3f::new(9., -1., 1.),
+                        Point3f::new(9., -1., -1.),
+                        Point3f::new(9., 1., 1.),
+                        Point3f::new(9., 1., -1.),
+                        Point3f::new(0., -10., 10.),
+                        Point3f::new(0., -10., -10.),
+                        Point3f::new(0., 10., 10.),
+                        Point3f::new(0., 10., -10.)];
+        for (corner, expected) in corners.iter().zip(expected.iter()) {
+            assert!(corner.distance(*expected) < 1e-4);
+        }
+    }
+
+    #[test]
+    fn volumes() {
+        let depth = DepthRange::NegativeOneToOne;
+        let frustum = Frustum::from_matrix(perspective(depth).matrix(), depth);
+        let cube = |x: f32, z: f32| {
+            Aabb::new(Point3f::new(x - 0.5, -0.5, z - 0.5), Point3f::new(x + 0.5, 0.5, z + 0.5))
+        };
+        let aabbs = [cube(0., -5.), cube(0., 5.), cube(5., -5.), cube(0., -10.), cube(-20., -5.)];
+        let expected = [Containment::Inside,
+                        Containment::Outside,
+                        Containment::Intersecting,
+                        Containment::Intersecting,
+                        Containment::Outside];
+        for (aabb, expected) in aabbs.iter().zip(expected.iter()) {
+            assert!(frustum.intersects_aabb(*aabb) == *expected);
+        }
+        assert!(frustum.intersects_aabbs(&aabbs) == expected.to_vec());
+
+        let sphere = |x: f32, z: f32| Sphere::new(Point3f::new(x, 0., z), 1.);
+        assert!(frustum.intersects_sphere(&sphere(0., -5.)) == Containment::Inside);
+        assert!(frustum.intersects_sphere(&sphere(0., -1.)) == Containment::Intersecting);
+        assert!(frustum.intersects_sphere(&sphere(0., 2.)) == Containment::Outside);
+    }
+
+    #[test]
+    fn infinite() {
+        let depth = DepthRange::ZeroToOne;
+        for &reversed in [false, true].iter() {
+            let (fovy, rh) = (Degrees::new(90.), Handedness::Right);
+            let projection = if reversed {
+                Transform::perspective_infinite_reversed_z(fovy, 1., 1., rh, depth)
+            } else {
+                Transform::perspective_infinite(fovy, 1., 1., rh, depth)
+            };
+            let frustum = Frustum::from_matrix(projection.matrix(), depth);
+            assert!(frustum.planes().iter().filter(|p| p.is_none()).count() == 1);
+            assert!(frustum.contains_point(Point3f::new(0., 0., -1e6)));
+            assert!(!frustum.contains_point(Point3f::new(0., 0., -0.5)));
+            assert!(frustum.corners().is_none());
+        }
+    }
+}
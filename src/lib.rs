@@ -1,15 +1,33 @@
 extern crate num;
 extern crate typenum;
-#[macro_use]
 extern crate generic_array;
 extern crate asprim;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+#[cfg(all(test, feature = "serde"))]
+extern crate bincode;
 
+#[macro_use]
+pub mod approx;
 pub mod vector;
 pub mod ray;
 pub mod aabb;
 pub mod matrix;
 pub mod transform;
 pub mod quaternion;
+pub mod dual_quaternion;
 pub mod unit;
 pub mod shapes;
+pub mod bvh;
+pub mod obb;
+pub mod collision;
+pub mod plane;
+pub mod frustum;
+pub mod simd;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(test)]
+mod test_util;
 
@@ -1,147 +1,462 @@
 #![allow(dead_code)]
-use num::Float;
-use typenum::*;
-use generic_array::*;
-use std::ops;
-use std::ops::{Index, IndexMut, Mul};
-use std::mem;
+use num::{Float, NumCast};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Neg, Index, IndexMut, Mul, MulAssign};
+use std::array;
 
 use vector::*;
+use unit::Angle;
 
-#[derive(PartialEq, Eq, Debug, Clone)]
-pub struct Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T>,
-          M: ArrayLength<Vector<T, N>>,
-          N::ArrayType: Copy,
-          M::ArrayType: Copy
+/// `M` rows of `N` elements.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Matrix<T, const N: usize, const M: usize>
+    where T: Float
 {
-    data: GenericArray<Vector<T, N>, M>,
+    data: [Vector<T, N>; M],
 }
 
-impl<T, N, M> Mul<T> for Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T>,
-          M: ArrayLength<Vector<T, N>>,
-          N::ArrayType: Copy,
-          M::ArrayType: Copy,
-          Vector<T, N>: Copy
+impl<T, const N: usize, const M: usize> Mul<T> for Matrix<T, N, M>
+    where T: Float
 {
     type Output = Matrix<T, N, M>;
     fn mul(self, scalar: T) -> Self::Output {
-        let mut m = Self::zero();
-        for (index, new_val) in self.data.into_iter().map(|v| v * scalar).enumerate() {
-            m.data[index] = new_val;
-        }
-        m
+        Matrix { data: array::from_fn(|index| self.data[index] * scalar) }
     }
 }
-impl<T, N, M> Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T>,
-          M: ArrayLength<Vector<T, N>>,
-          N::ArrayType: Copy,
-          M::ArrayType: Copy,
-          Vector<T, N>: Copy
+impl<T, const N: usize, const M: usize> Matrix<T, N, M>
+    where T: Float
 {
+    /// Panics if `slice` does not contain `M` rows.
     pub fn new(slice: &[Vector<T, N>]) -> Matrix<T, N, M> {
-        Matrix::<T, N, M> { data: GenericArray::from_slice(slice) }
+        let mut data = [Vector::zero(); M];
+        data.copy_from_slice(slice);
+        Matrix { data }
     }
 
     pub fn zero() -> Matrix<T, N, M> {
-        unsafe {
-            let mut mat: Matrix<T, N, M> = mem::uninitialized();
-            for j in 0..M::to_usize() {
-                mat.data[j] = Vector::<T, N>::zero();
-            }
-            mat
-        }
+        Matrix { data: [Vector::zero(); M] }
+    }
+
+    pub fn rows(&self) -> &[Vector<T, N>; M] {
+        &self.data
     }
 }
-impl<T, N> Matrix<T, N, N>
-    where T: Float,
-          N: ArrayLength<T> + ArrayLength<Vector<T, N>>,
-          <N as ArrayLength<T>>::ArrayType: Copy,
-          <N as ArrayLength<Vector<T, N>>>::ArrayType: Copy,
-          Vector<T, N>: Copy
+impl<T, const N: usize> Matrix<T, N, N>
+    where T: Float
 {
-    fn identity() -> Matrix<T, N, N> {
+    pub fn identity() -> Matrix<T, N, N> {
         let mut mat = Matrix::<T, N, N>::zero();
-        for index in 0..N::to_usize() {
+        for index in 0..N {
             mat.data[index].data[index] = T::one();
         }
         mat
     }
 }
 
-impl<T, N, M> Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T> + ArrayLength<Vector<T, M>>,
-          M: ArrayLength<T> + ArrayLength<Vector<T, N>>,
-          <N as ArrayLength<Vector<T, M>>>::ArrayType: Copy,
-          <M as ArrayLength<Vector<T, N>>>::ArrayType: Copy,
-          <N as ArrayLength<T>>::ArrayType: Copy,
-          <M as ArrayLength<T>>::ArrayType: Copy
+impl<T, const N: usize> Matrix<T, N, N>
+    where T: Float
 {
-    fn transpose(&self) -> Matrix<T, M, N> {
-        unsafe {
-            let mut data: GenericArray<Vector<T, M>, N> = mem::uninitialized();
-            for j in 0..N::to_usize() {
-                for i in 0..M::to_usize() {
-                    data[j].data[i] = self.data[i].data[j];
+    /// Decides if `try_inverse` treats the matrix as singular.
+    pub(crate) fn is_singular(&self) -> bool {
+        if N > 4 {
+            self.lu().is_singular()
+        } else {
+            self.is_singular_det(self.determinant())
+        }
+    }
+
+    /// `true` if `det`, the closed form determinant of the matrix, vanishes relative to the
+    /// rounding error of its expansion. Independent of the scale of the matrix and exact for
+    /// triangular matrices like translations. Only for `N <= 4`.
+    pub(crate) fn is_singular_det(&self, det: T) -> bool {
+        let n: T = NumCast::from(N).unwrap();
+        det.is_nan() || det.abs() <= T::epsilon() * n * self.determinant_bound()
+    }
+
+    /// The determinant expansion of `|A|` with every term made positive, which bounds the
+    /// terms that cancel in the determinant.
+    fn determinant_bound(&self) -> T {
+        let a = |i: usize, j: usize| self[i][j].abs();
+        match N {
+            1 => a(0, 0),
+            2 => a(0, 0) * a(1, 1) + a(0, 1) * a(1, 0),
+            3 => {
+                a(0, 0) * (a(1, 1) * a(2, 2) + a(1, 2) * a(2, 1)) +
+                a(0, 1) * (a(1, 0) * a(2, 2) + a(1, 2) * a(2, 0)) +
+                a(0, 2) * (a(1, 0) * a(2, 1) + a(1, 1) * a(2, 0))
+            }
+            4 => {
+                let (s, c) = self.minors_4x4_by(|a, b, c, d| (a * d).abs() + (b * c).abs());
+                s[0] * c[5] + s[1] * c[4] + s[2] * c[3] + s[3] * c[2] + s[4] * c[1] + s[5] * c[0]
+            }
+            _ => panic!("The determinant bound is only used for the closed form determinants"),
+        }
+    }
+
+    /// Decomposes the matrix into `P * A = L * U` with partial pivoting.
+    pub fn lu(&self) -> Lu<T, N> {
+        let n = N;
+        let mut lu = *self;
+        // The sum of the magnitudes of all terms that were added up in every element, it
+        // bounds the rounding error of the element.
+        let mut magnitude = Self { data: array::from_fn(|i| self.data[i].map(|v| v.abs())) };
+        let mut permutation: [usize; N] = array::from_fn(|index| index);
+        let mut swaps = 0;
+        for k in 0..n {
+            let pivot = (k..n).fold(k, |p, i| if lu[i][k].abs() > lu[p][k].abs() { i } else { p });
+            // The remaining column is already zero, there is nothing to eliminate.
+            if lu[pivot][k] == T::zero() {
+                continue;
+            }
+            if pivot != k {
+                lu.data.swap(pivot, k);
+                magnitude.data.swap(pivot, k);
+                permutation.swap(pivot, k);
+                swaps += 1;
+            }
+            for i in k + 1..n {
+                let factor = lu[i][k] / lu[k][k];
+                lu[i][k] = factor;
+                for j in k + 1..n {
+                    lu[i][j] = lu[i][j] - factor * lu[k][j];
+                    magnitude[i][j] = magnitude[i][j] + factor.abs() * magnitude[k][j];
                 }
             }
-            Matrix::<T, M, N> { data: data }
         }
+        Lu {
+            lu,
+            permutation,
+            odd: swaps % 2 == 1,
+            pivot_magnitude: array::from_fn(|k| magnitude[k][k]),
+        }
+    }
+
+    pub fn determinant(&self) -> T {
+        let a = |i: usize, j: usize| self[i][j];
+        match N {
+            1 => a(0, 0),
+            2 => a(0, 0) * a(1, 1) - a(0, 1) * a(1, 0),
+            3 => {
+                a(0, 0) * (a(1, 1) * a(2, 2) - a(1, 2) * a(2, 1)) -
+                a(0, 1) * (a(1, 0) * a(2, 2) - a(1, 2) * a(2, 0)) +
+                a(0, 2) * (a(1, 0) * a(2, 1) - a(1, 1) * a(2, 0))
+            }
+            4 => {
+                let (s, c) = self.minors_4x4();
+                s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+            }
+            _ => self.lu().determinant(),
+        }
+    }
+
+    /// Returns `None` if the matrix is singular.
+    pub fn try_inverse(&self) -> Option<Self> {
+        let n = N;
+        if n > 4 {
+            return self.lu().inverse();
+        }
+        let det = self.determinant();
+        if self.is_singular_det(det) {
+            return None;
+        }
+        let inv_det = T::one() / det;
+        let a = |i: usize, j: usize| self[i][j];
+        let mut inv = Self::zero();
+        match n {
+            1 => inv[0][0] = inv_det,
+            2 => {
+                inv[0][0] = a(1, 1) * inv_det;
+                inv[0][1] = -a(0, 1) * inv_det;
+                inv[1][0] = -a(1, 0) * inv_det;
+                inv[1][1] = a(0, 0) * inv_det;
+            }
+            3 => {
+                inv[0][0] = (a(1, 1) * a(2, 2) - a(1, 2) * a(2, 1)) * inv_det;
+                inv[0][1] = (a(0, 2) * a(2, 1) - a(0, 1) * a(2, 2)) * inv_det;
+                inv[0][2] = (a(0, 1) * a(1, 2) - a(0, 2) * a(1, 1)) * inv_det;
+                inv[1][0] = (a(1, 2) * a(2, 0) - a(1, 0) * a(2, 2)) * inv_det;
+                inv[1][1] = (a(0, 0) * a(2, 2) - a(0, 2) * a(2, 0)) * inv_det;
+                inv[1][2] = (a(0, 2) * a(1, 0) - a(0, 0) * a(1, 2)) * inv_det;
+                inv[2][0] = (a(1, 0) * a(2, 1) - a(1, 1) * a(2, 0)) * inv_det;
+                inv[2][1] = (a(0, 1) * a(2, 0) - a(0, 0) * a(2, 1)) * inv_det;
+                inv[2][2] = (a(0, 0) * a(1, 1) - a(0, 1) * a(1, 0)) * inv_det;
+            }
+            _ => {
+                let (s, c) = self.minors_4x4();
+                inv[0][0] = (a(1, 1) * c[5] - a(1, 2) * c[4] + a(1, 3) * c[3]) * inv_det;
+                inv[0][1] = (-a(0, 1) * c[5] + a(0, 2) * c[4] - a(0, 3) * c[3]) * inv_det;
+                inv[0][2] = (a(3, 1) * s[5] - a(3, 2) * s[4] + a(3, 3) * s[3]) * inv_det;
+                inv[0][3] = (-a(2, 1) * s[5] + a(2, 2) * s[4] - a(2, 3) * s[3]) * inv_det;
+                inv[1][0] = (-a(1, 0) * c[5] + a(1, 2) * c[2] - a(1, 3) * c[1]) * inv_det;
+                inv[1][1] = (a(0, 0) * c[5] - a(0, 2) * c[2] + a(0, 3) * c[1]) * inv_det;
+                inv[1][2] = (-a(3, 0) * s[5] + a(3, 2) * s[2] - a(3, 3) * s[1]) * inv_det;
+                inv[1][3] = (a(2, 0) * s[5] - a(2, 2) * s[2] + a(2, 3) * s[1]) * inv_det;
+                inv[2][0] = (a(1, 0) * c[4] - a(1, 1) * c[2] + a(1, 3) * c[0]) * inv_det;
+                inv[2][1] = (-a(0, 0) * c[4] + a(0, 1) * c[2] - a(0, 3) * c[0]) * inv_det;
+                inv[2][2] = (a(3, 0) * s[4] - a(3, 1) * s[2] + a(3, 3) * s[0]) * inv_det;
+                inv[2][3] = (-a(2, 0) * s[4] + a(2, 1) * s[2] - a(2, 3) * s[0]) * inv_det;
+                inv[3][0] = (-a(1, 0) * c[3] + a(1, 1) * c[1] - a(1, 2) * c[0]) * inv_det;
+                inv[3][1] = (a(0, 0) * c[3] - a(0, 1) * c[1] + a(0, 2) * c[0]) * inv_det;
+                inv[3][2] = (-a(3, 0) * s[3] + a(3, 1) * s[1] - a(3, 2) * s[0]) * inv_det;
+                inv[3][3] = (a(2, 0) * s[3] - a(2, 1) * s[1] + a(2, 2) * s[0]) * inv_det;
+            }
+        }
+        Some(inv)
+    }
+
+    /// Panics if the matrix is singular, see `try_inverse`.
+    pub fn inverse(&self) -> Self {
+        self.try_inverse().expect("Singular matrix")
+    }
+
+    /// The 2x2 minors of the upper two rows (`s`) and the lower two rows (`c`) of a 4x4
+    /// matrix, shared by the determinant and the inverse.
+    fn minors_4x4(&self) -> ([T; 6], [T; 6]) {
+        self.minors_4x4_by(|a, b, c, d| a * d - c * b)
+    }
+
+    /// Combines the elements `[[a, b], [c, d]]` of every 2x2 submatrix with `minor`.
+    fn minors_4x4_by<F>(&self, minor: F) -> ([T; 6], [T; 6])
+        where F: Fn(T, T, T, T) -> T
+    {
+        let a = |i: usize, j: usize| self[i][j];
+        let minor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            minor(a(r0, c0), a(r0, c1), a(r1, c0), a(r1, c1))
+        };
+        let s = [minor(0, 1, 0, 1),
+                 minor(0, 1, 0, 2),
+                 minor(0, 1, 0, 3),
+                 minor(0, 1, 1, 2),
+                 minor(0, 1, 1, 3),
+                 minor(0, 1, 2, 3)];
+        let c = [minor(2, 3, 0, 1),
+                 minor(2, 3, 0, 2),
+                 minor(2, 3, 0, 3),
+                 minor(2, 3, 1, 2),
+                 minor(2, 3, 1, 3),
+                 minor(2, 3, 2, 3)];
+        (s, c)
     }
 }
-// funny stuff is happening here
-impl<T, N, M> Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T> + ArrayLength<Vector<T, M>>,
-          M: ArrayLength<T> + ArrayLength<Vector<T, N>>,
-          <N as ArrayLength<Vector<T, M>>>::ArrayType: Copy,
-          <M as ArrayLength<Vector<T, N>>>::ArrayType: Copy,
-          <N as ArrayLength<T>>::ArrayType: Copy,
-          <M as ArrayLength<T>>::ArrayType: Copy
+
+/// The LU decomposition `P * A = L * U` of a square matrix `A`. `L` and `U` are stored in
+/// a single matrix, the unit diagonal of `L` is implicit.
+#[derive(Debug, Clone)]
+pub struct Lu<T, const N: usize>
+    where T: Float
 {
-    pub fn mul_v(&self, other: Vector<T, N>) -> Vector<T, N>
-        where Vector<T, N>: Copy
-    {
-        let mut v = Vector::<T, N>::zero();
-        for index in 0..N::to_usize() {
-            v.data[index] = self.data[index].dot(other);
+    lu: Matrix<T, N, N>,
+    permutation: [usize; N],
+    odd: bool,
+    /// The magnitude of the terms that were summed into every pivot.
+    pivot_magnitude: [T; N],
+}
+
+impl<T, const N: usize> Lu<T, N>
+    where T: Float
+{
+    /// The lower triangular matrix with a unit diagonal.
+    pub fn l(&self) -> Matrix<T, N, N> {
+        let mut l = Matrix::identity();
+        for i in 0..N {
+            for j in 0..i {
+                l[i][j] = self.lu[i][j];
+            }
         }
-        v
-    }
-    pub fn mul<N1>(&self, other: &Matrix<T, N1, N>) -> Matrix<T, M, N1>
-        where N1: ArrayLength<T> + ArrayLength<Vector<T, M>> + ArrayLength<Vector<T, N>>,
-              <N1 as ArrayLength<T>>::ArrayType: Copy,
-              <N1 as ArrayLength<Vector<T, M>>>::ArrayType: Copy,
-              <N1 as ArrayLength<Vector<T, N>>>::ArrayType: Copy,
-              N: ArrayLength<Vector<T, N1>>,
-              <N as ArrayLength<Vector<T, N1>>>::ArrayType: Copy,
-              Vector<T, N>: Copy
-    {
-        unsafe {
-            let mut new_matrix: Matrix<T, M, N1> = mem::uninitialized();
-            let other_transposed: Matrix<T, N, N1> = other.transpose();
-            for j in 0..N1::to_usize() {
-                for i in 0..M::to_usize() {
-                    new_matrix.data[j].data[i] = self.data[j].dot(other_transposed.data[i]);
-                }
+        l
+    }
+
+    /// The upper triangular matrix.
+    pub fn u(&self) -> Matrix<T, N, N> {
+        let mut u = Matrix::zero();
+        for i in 0..N {
+            for j in i..N {
+                u[i][j] = self.lu[i][j];
+            }
+        }
+        u
+    }
+
+    /// Row `i` of `P * A` is row `permutation()[i]` of `A`.
+    pub fn permutation(&self) -> &[usize] {
+        &self.permutation
+    }
+
+    pub fn determinant(&self) -> T {
+        let det = (0..N).fold(T::one(), |acc, i| acc * self.lu[i][i]);
+        if self.odd { -det } else { det }
+    }
+
+    /// A pivot that vanishes relative to the rounding error of the elimination makes the
+    /// matrix singular. The test does not depend on the scale of the rows, and pivots that
+    /// were never eliminated, like the ones of a translation, are exact.
+    pub fn is_singular(&self) -> bool {
+        let n: T = NumCast::from(N).unwrap();
+        (0..N).any(|k| {
+            let pivot = self.lu[k][k].abs();
+            pivot.is_nan() || pivot <= T::epsilon() * n * self.pivot_magnitude[k]
+        })
+    }
+
+    /// Solves `A * x = b`, returns `None` if `A` is singular.
+    pub fn solve(&self, b: Vector<T, N>) -> Option<Vector<T, N>> {
+        if self.is_singular() {
+            return None;
+        }
+        Some(self.substitute(b))
+    }
+
+    /// Returns `None` if the decomposed matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix<T, N, N>> {
+        if self.is_singular() {
+            return None;
+        }
+        let n = N;
+        let mut inv = Matrix::<T, N, N>::zero();
+        for j in 0..n {
+            let mut e = Vector::<T, N>::zero();
+            e[j] = T::one();
+            let column = self.substitute(e);
+            for i in 0..n {
+                inv[i][j] = column[i];
+            }
+        }
+        Some(inv)
+    }
+
+    /// Forward and back substitution, expects a non singular matrix.
+    fn substitute(&self, b: Vector<T, N>) -> Vector<T, N> {
+        let n = N;
+        let mut x = Vector::<T, N>::zero();
+        for i in 0..n {
+            let sum = (0..i).fold(b[self.permutation[i]], |acc, j| acc - self.lu[i][j] * x[j]);
+            x[i] = sum;
+        }
+        for i in (0..n).rev() {
+            let sum = (i + 1..n).fold(x[i], |acc, j| acc - self.lu[i][j] * x[j]);
+            x[i] = sum / self.lu[i][i];
+        }
+        x
+    }
+}
+
+impl<T, const N: usize, const M: usize> Matrix<T, N, M>
+    where T: Float
+{
+    pub fn transpose(&self) -> Matrix<T, M, N> {
+        Matrix { data: array::from_fn(|j| Vector::from_fn(|i| self.data[i].data[j])) }
+    }
+
+    /// `self * v`, the dot products of the rows with `v`.
+    pub fn mul_v(&self, v: Vector<T, N>) -> Vector<T, M> {
+        Vector::from_fn(|index| self.data[index].dot(v))
+    }
+}
+
+macro_rules! impl_op_mat{
+    ($trait_name: ident, $fn_name: ident, $assign_trait: ident, $assign_fn: ident, $op: tt) => {
+        impl<T, const N: usize, const M: usize> $trait_name for &Matrix<T, N, M>
+            where T: Float
+        {
+            type Output = Matrix<T, N, M>;
+            fn $fn_name(self, other: Self) -> Matrix<T, N, M> {
+                Matrix { data: array::from_fn(|index| self.data[index] $op other.data[index]) }
+            }
+        }
+
+        impl<T, const N: usize, const M: usize> $trait_name for Matrix<T, N, M>
+            where T: Float
+        {
+            type Output = Matrix<T, N, M>;
+            fn $fn_name(self, other: Self) -> Matrix<T, N, M> {
+                &self $op &other
+            }
+        }
+
+        impl<T, const N: usize, const M: usize> $assign_trait for Matrix<T, N, M>
+            where T: Float
+        {
+            fn $assign_fn(&mut self, other: Self) {
+                *self = &*self $op &other;
             }
-            new_matrix
         }
     }
+ }
+
+impl_op_mat!(Add, add, AddAssign, add_assign, +);
+impl_op_mat!(Sub, sub, SubAssign, sub_assign, -);
+
+impl<T, const N: usize, const M: usize> Neg for Matrix<T, N, M>
+    where T: Float
+{
+    type Output = Matrix<T, N, M>;
+    fn neg(self) -> Self {
+        self * -T::one()
+    }
 }
 
-impl<T, N, M> Index<usize> for Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T>,
-          M: ArrayLength<Vector<T, N>>,
-          N::ArrayType: Copy,
-          M::ArrayType: Copy
+impl<T, const N: usize, const M: usize> MulAssign<T> for Matrix<T, N, M>
+    where T: Float
+{
+    fn mul_assign(&mut self, scalar: T) {
+        for row in self.data.iter_mut() {
+            *row = *row * scalar;
+        }
+    }
+}
+
+/// The product of a `M x N` and a `N x P` matrix is a `M x P` matrix. Every row of the
+/// result is a linear combination of the rows of `other`.
+impl<T, const N: usize, const M: usize, const P: usize> Mul<&Matrix<T, P, N>> for &Matrix<T, N, M>
+    where T: Float
+{
+    type Output = Matrix<T, P, M>;
+    fn mul(self, other: &Matrix<T, P, N>) -> Matrix<T, P, M> {
+        let row = |j: usize| {
+            Iterator::zip(self.data[j].into_iter(), other.data.iter())
+                .fold(Vector::zero(), |acc, (scale, row)| acc + *row * scale)
+        };
+        Matrix { data: array::from_fn(row) }
+    }
+}
+
+impl<T, const N: usize, const M: usize, const P: usize> Mul<Matrix<T, P, N>> for Matrix<T, N, M>
+    where T: Float
+{
+    type Output = Matrix<T, P, M>;
+    fn mul(self, other: Matrix<T, P, N>) -> Matrix<T, P, M> {
+        &self * &other
+    }
+}
+
+/// Only square matrices keep the shape of `self`.
+impl<T, const N: usize, const M: usize> MulAssign<Matrix<T, N, N>> for Matrix<T, N, M>
+    where T: Float
+{
+    fn mul_assign(&mut self, other: Matrix<T, N, N>) {
+        *self = *self * other;
+    }
+}
+
+impl<T, const N: usize, const M: usize> Mul<Vector<T, N>> for &Matrix<T, N, M>
+    where T: Float
+{
+    type Output = Vector<T, M>;
+    fn mul(self, v: Vector<T, N>) -> Vector<T, M> {
+        self.mul_v(v)
+    }
+}
+
+impl<T, const N: usize, const M: usize> Mul<Vector<T, N>> for Matrix<T, N, M>
+    where T: Float
+{
+    type Output = Vector<T, M>;
+    fn mul(self, v: Vector<T, N>) -> Vector<T, M> {
+        self.mul_v(v)
+    }
+}
+
+impl<T, const N: usize, const M: usize> Index<usize> for Matrix<T, N, M>
+    where T: Float
 {
     type Output = Vector<T, N>;
 
@@ -149,19 +464,81 @@ impl<T, N, M> Index<usize> for Matrix<T, N, M>
         &self.data[idx]
     }
 }
-impl<T, N, M> IndexMut<usize> for Matrix<T, N, M>
-    where T: Float,
-          N: ArrayLength<T>,
-          M: ArrayLength<Vector<T, N>>,
-          N::ArrayType: Copy,
-          M::ArrayType: Copy
+impl<T, const N: usize, const M: usize> IndexMut<usize> for Matrix<T, N, M>
+    where T: Float
 {
     fn index_mut(&mut self, idx: usize) -> &mut Vector<T, N> {
         &mut self.data[idx]
     }
 }
 
-impl<T> Matrix<T, U4, U4>
+impl<T, const N: usize, const M: usize> From<[Vector<T, N>; M]> for Matrix<T, N, M>
+    where T: Float
+{
+    fn from(data: [Vector<T, N>; M]) -> Self {
+        Matrix { data }
+    }
+}
+
+/// The rows of the matrix.
+impl<T, const N: usize, const M: usize> From<[[T; N]; M]> for Matrix<T, N, M>
+    where T: Float
+{
+    fn from(rows: [[T; N]; M]) -> Self {
+        Matrix { data: array::from_fn(|index| Vector::from(rows[index])) }
+    }
+}
+
+impl<T, const N: usize, const M: usize> From<Matrix<T, N, M>> for [[T; N]; M]
+    where T: Float
+{
+    fn from(m: Matrix<T, N, M>) -> Self {
+        array::from_fn(|index| m.data[index].data)
+    }
+}
+
+/// The direction the camera looks at in view space. `Right` handed projections look down
+/// the negative z axis, `Left` handed projections down the positive z axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Handedness {
+    Left,
+    Right,
+}
+
+impl Handedness {
+    /// The sign of the view space z coordinate of points in front of the camera.
+    fn forward<T: Float>(self) -> T {
+        match self {
+            Handedness::Left => T::one(),
+            Handedness::Right => -T::one(),
+        }
+    }
+}
+
+/// The range of the normalized device depth. OpenGL uses `NegativeOneToOne`, Vulkan,
+/// Direct3D and Metal use `ZeroToOne`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DepthRange {
+    NegativeOneToOne,
+    ZeroToOne,
+}
+
+impl DepthRange {
+    /// The normalized device depth of the near and far plane.
+    fn near_far<T: Float>(self, reversed: bool) -> (T, T) {
+        let near = match self {
+            DepthRange::NegativeOneToOne => -T::one(),
+            DepthRange::ZeroToOne => T::zero(),
+        };
+        if reversed {
+            (T::one(), near)
+        } else {
+            (near, T::one())
+        }
+    }
+}
+
+impl<T> Matrix<T, 4, 4>
     where T: Float
 {
     pub fn translate(v: Vec3<T>) -> Self {
@@ -180,90 +557,157 @@ impl<T> Matrix<T, U4, U4>
         identity
     }
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    pub fn rotation_x(angle: T) -> Self {
-        let s = angle.sin();
-        let c = angle.cos();
+    #[rustfmt::skip]
+    pub fn rotation_x<A: Angle<T>>(angle: A) -> Self {
+        let (s, c) = angle.sin_cos();
         Self::new(&[Vec4::<T>::new(T::one(),  T::zero(),  T::zero(), T::zero()),
                     Vec4::<T>::new(T::zero(), c,         -s,         T::zero()),
                     Vec4::<T>::new(T::zero(), s,          c,         T::zero()),
                     Vec4::<T>::new(T::zero(), T::zero(),  T::zero(), T::one())])
     }
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    pub fn rotation_y(angle: T) -> Self {
-        let s = angle.sin();
-        let c = angle.cos();
+    #[rustfmt::skip]
+    pub fn rotation_y<A: Angle<T>>(angle: A) -> Self {
+        let (s, c) = angle.sin_cos();
         Self::new(&[Vec4::<T>::new(c,         T::zero(), s,         T::zero()),
                     Vec4::<T>::new(T::zero(), T::one(),  T::zero(), T::zero()),
                     Vec4::<T>::new(-s,        T::zero(), c,         T::zero()),
                     Vec4::<T>::new(T::zero(), T::zero(), T::zero(), T::one())])
     }
 
-    #[cfg_attr(rustfmt, rustfmt_skip)]
-    pub fn rotation_z(angle: T) -> Self {
-        let s = angle.sin();
-        let c = angle.cos();
+    #[rustfmt::skip]
+    pub fn rotation_z<A: Angle<T>>(angle: A) -> Self {
+        let (s, c) = angle.sin_cos();
         Self::new(&[Vec4::<T>::new(c,        -s,         T::zero(), T::zero()),
                     Vec4::<T>::new(s,         c,         T::zero(), T::zero()),
                     Vec4::<T>::new(T::zero(), T::zero(), T::one(),  T::zero()),
                     Vec4::<T>::new(T::zero(), T::zero(), T::zero(), T::one())])
     }
 
-    pub fn inverse(&self) -> Self {
-        let coef00 = self[2][2] * self[3][3] - self[3][2] * self[2][3];
-        let coef02 = self[1][2] * self[3][3] - self[3][2] * self[1][3];
-        let coef03 = self[1][2] * self[2][3] - self[2][2] * self[1][3];
-
-        let coef04 = self[2][1] * self[3][3] - self[3][1] * self[2][3];
-        let coef06 = self[1][1] * self[3][3] - self[3][1] * self[1][3];
-        let coef07 = self[1][1] * self[2][3] - self[2][1] * self[1][3];
-
-        let coef08 = self[2][1] * self[3][2] - self[3][1] * self[2][2];
-        let coef10 = self[1][1] * self[3][2] - self[3][1] * self[1][2];
-        let coef11 = self[1][1] * self[2][2] - self[2][1] * self[1][2];
-
-        let coef12 = self[2][0] * self[3][3] - self[3][0] * self[2][3];
-        let coef14 = self[1][0] * self[3][3] - self[3][0] * self[1][3];
-        let coef15 = self[1][0] * self[2][3] - self[2][0] * self[1][3];
-
-        let coef16 = self[2][0] * self[3][2] - self[3][0] * self[2][2];
-        let coef18 = self[1][0] * self[3][2] - self[3][0] * self[1][2];
-        let coef19 = self[1][0] * self[2][2] - self[2][0] * self[1][2];
-
-        let coef20 = self[2][0] * self[3][1] - self[3][0] * self[2][1];
-        let coef22 = self[1][0] * self[3][1] - self[3][0] * self[1][1];
-        let coef23 = self[1][0] * self[2][1] - self[2][0] * self[1][1];
+    /// A perspective projection with a vertical field of view `fovy` and the aspect ratio
+    /// `width / height`.
+    pub fn perspective<A: Angle<T>>(fovy: A,
+                                    aspect: T,
+                                    near: T,
+                                    far: T,
+                                    handedness: Handedness,
+                                    depth: DepthRange)
+                                    -> Self {
+        let (left, right, bottom, top) = Self::fov_bounds(fovy, aspect, near);
+        Self::frustum(left, right, bottom, top, near, far, handedness, depth)
+    }
 
-        let fac0 = Vec4::new(coef00, coef00, coef02, coef03);
-        let fac1 = Vec4::new(coef04, coef04, coef06, coef07);
-        let fac2 = Vec4::new(coef08, coef08, coef10, coef11);
-        let fac3 = Vec4::new(coef12, coef12, coef14, coef15);
-        let fac4 = Vec4::new(coef16, coef16, coef18, coef19);
-        let fac5 = Vec4::new(coef20, coef20, coef22, coef23);
+    /// A perspective projection with the far plane at infinity.
+    pub fn perspective_infinite<A: Angle<T>>(fovy: A,
+                                             aspect: T,
+                                             near: T,
+                                             handedness: Handedness,
+                                             depth: DepthRange)
+                                             -> Self {
+        let (left, right, bottom, top) = Self::fov_bounds(fovy, aspect, near);
+        let (z_near, z_far): (T, T) = depth.near_far(false);
+        Self::projective(left, right, bottom, top, near, z_far, (z_near - z_far) * near, handedness)
+    }
 
-        let vec0 = Vec4::new(self[1][0], self[0][0], self[0][0], self[0][0]);
-        let vec1 = Vec4::new(self[1][1], self[0][1], self[0][1], self[0][1]);
-        let vec2 = Vec4::new(self[1][2], self[0][2], self[0][2], self[0][2]);
-        let vec3 = Vec4::new(self[1][3], self[0][3], self[0][3], self[0][3]);
+    /// A perspective projection that maps the near plane to a depth of one and the far plane
+    /// to the lower end of the depth range, which distributes floating point depth precision
+    /// more evenly.
+    pub fn perspective_reversed_z<A: Angle<T>>(fovy: A,
+                                               aspect: T,
+                                               near: T,
+                                               far: T,
+                                               handedness: Handedness,
+                                               depth: DepthRange)
+                                               -> Self {
+        let (left, right, bottom, top) = Self::fov_bounds(fovy, aspect, near);
+        let (a, b) = Self::depth_coefficients(near, far, depth.near_far(true));
+        Self::projective(left, right, bottom, top, near, a, b, handedness)
+    }
 
-        let inv0 = vec1 * fac0 - vec2 * fac1 + vec3 * fac2;
-        let inv1 = vec0 * fac0 - vec2 * fac3 + vec3 * fac4;
-        let inv2 = vec0 * fac1 - vec1 * fac3 + vec3 * fac5;
-        let inv3 = vec0 * fac2 - vec1 * fac4 + vec2 * fac5;
+    /// A reversed-z perspective projection with the far plane at infinity, mapped to the
+    /// lower end of the depth range.
+    pub fn perspective_infinite_reversed_z<A: Angle<T>>(fovy: A,
+                                                        aspect: T,
+                                                        near: T,
+                                                        handedness: Handedness,
+                                                        depth: DepthRange)
+                                                        -> Self {
+        let (left, right, bottom, top) = Self::fov_bounds(fovy, aspect, near);
+        let (z_near, z_far): (T, T) = depth.near_far(true);
+        Self::projective(left, right, bottom, top, near, z_far, (z_near - z_far) * near, handedness)
+    }
 
-        let sign_a = Vec4::new(T::one(), -T::one(), T::one(), -T::one());
-        let sign_b = Vec4::new(-T::one(), T::one(), -T::one(), T::one());
+    /// A perspective projection of the view volume whose near plane is the rectangle
+    /// `left, right, bottom, top`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn frustum(left: T,
+                   right: T,
+                   bottom: T,
+                   top: T,
+                   near: T,
+                   far: T,
+                   handedness: Handedness,
+                   depth: DepthRange)
+                   -> Self {
+        let (a, b) = Self::depth_coefficients(near, far, depth.near_far(false));
+        Self::projective(left, right, bottom, top, near, a, b, handedness)
+    }
 
-        let inverse = Self::new(&[inv0 * sign_a, inv1 * sign_b, inv2 * sign_a, inv3 * sign_b]);
+    #[allow(clippy::too_many_arguments)]
+    #[rustfmt::skip]
+    pub fn orthographic(left: T,
+                        right: T,
+                        bottom: T,
+                        top: T,
+                        near: T,
+                        far: T,
+                        handedness: Handedness,
+                        depth: DepthRange)
+                        -> Self {
+        let two: T = NumCast::from(2).unwrap();
+        let s = handedness.forward::<T>();
+        let (z_near, z_far): (T, T) = depth.near_far(false);
+        let z_scale = (z_far - z_near) / (far - near);
+        Self::new(&[Vec4::new(two / (right - left), T::zero(), T::zero(), -(right + left) / (right - left)),
+                    Vec4::new(T::zero(), two / (top - bottom), T::zero(), -(top + bottom) / (top - bottom)),
+                    Vec4::new(T::zero(), T::zero(), s * z_scale,          z_near - near * z_scale),
+                    Vec4::new(T::zero(), T::zero(), T::zero(),            T::one())])
+    }
 
-        let row0 = Vec4::new(inverse[0][0], inverse[1][0], inverse[2][0], inverse[3][0]);
+    /// The near plane rectangle of a symmetric perspective projection.
+    fn fov_bounds<A: Angle<T>>(fovy: A, aspect: T, near: T) -> (T, T, T, T) {
+        let two: T = NumCast::from(2).unwrap();
+        let top = near * (fovy / two).tan();
+        let right = top * aspect;
+        (-right, right, -top, top)
+    }
 
-        let dot0 = self[0] * row0;
-        let dot1 = (dot0.x() + dot0.y()) + (dot0.z() + dot0.w());
+    /// The normalized device depth of a point at distance `d` in front of the camera is
+    /// `a + b / d`; solves for `a` and `b` so that `near` and `far` map to `z_near` and
+    /// `z_far`.
+    fn depth_coefficients(near: T, far: T, (z_near, z_far): (T, T)) -> (T, T) {
+        let a = (z_far * far - z_near * near) / (far - near);
+        let b = (z_near - z_far) * near * far / (far - near);
+        (a, b)
+    }
 
-        let one_over_det = T::one() / dot1;
-        inverse * one_over_det
+    #[allow(clippy::too_many_arguments)]
+    #[rustfmt::skip]
+    fn projective(left: T,
+                  right: T,
+                  bottom: T,
+                  top: T,
+                  near: T,
+                  a: T,
+                  b: T,
+                  handedness: Handedness)
+                  -> Self {
+        let two: T = NumCast::from(2).unwrap();
+        let s = handedness.forward::<T>();
+        Self::new(&[Vec4::new(two * near / (right - left), T::zero(), -s * (right + left) / (right - left), T::zero()),
+                    Vec4::new(T::zero(), two * near / (top - bottom), -s * (top + bottom) / (top - bottom), T::zero()),
+                    Vec4::new(T::zero(), T::zero(),                   s * a,                                b),
+                    Vec4::new(T::zero(), T::zero(),                   s,                                    T::zero())])
     }
 }
 
@@ -271,49 +715,212 @@ impl<T> Matrix<T, U4, U4>
 mod test {
     use vector::*;
     use matrix::*;
+    use unit::Radians;
     #[test]
     fn rot_x() {
         use std::f32;
         let v = Vec4f::new(0., 0., 1., 0.);
         let pi = f32::consts::PI;
-        let m = Mat4x4f::rotation_x(pi / 2.0);
+        let m = Mat4x4f::rotation_x(Radians::new(pi / 2.0));
         let v2 = m.mul_v(v);
-        println!("{:?}", v2);
+        assert_abs_diff_eq!(v2, Vec4f::new(0., -1., 0., 0.), epsilon = 1e-6);
     }
+
     #[test]
     fn inverse(){
-        let identity = Mat4x4f::rotation_y(10.0);
-        let m = identity.mul(&identity.inverse());
-        println!("{:?}", m);
+        let rotation = Mat4x4f::rotation_y(Radians::new(10.0));
+        assert_abs_diff_eq!(rotation * rotation.inverse(), Mat4x4f::identity(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn inverse_small() {
+        let m2 = Mat2x2::new(&[Vec2::new(4.0, 7.0), Vec2::new(2.0, 6.0)]);
+        assert_abs_diff_eq!(m2 * m2.inverse(), Mat2x2::identity(), epsilon = 1e-9);
+        let m3 = Mat3x3::new(&[Vec3::new(1.0, 2.0, 3.0),
+                               Vec3::new(0.0, 1.0, 4.0),
+                               Vec3::new(5.0, 6.0, 0.0)]);
+        let expected = Mat3x3::new(&[Vec3::new(-24.0, 18.0, 5.0),
+                                     Vec3::new(20.0, -15.0, -4.0),
+                                     Vec3::new(-5.0, 4.0, 1.0)]);
+        assert!(m3.determinant() == 1.0);
+        assert_abs_diff_eq!(m3.inverse(), expected, epsilon = 1e-9);
+        assert_abs_diff_eq!(m3.lu().inverse().unwrap(), expected, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn inverse_4x4_matches_lu() {
+        let m = Mat4x4::new(&[Vec4::new(2.0, -1.0, 0.0, 3.0),
+                              Vec4::new(1.0, 4.0, -2.0, 0.5),
+                              Vec4::new(0.0, 3.0, 5.0, -1.0),
+                              Vec4::new(-2.0, 1.0, 1.0, 2.0)]);
+        let lu = m.lu();
+        assert!((m.determinant() - lu.determinant()).abs() < 1e-9);
+        assert_abs_diff_eq!(m.inverse(), lu.inverse().unwrap(), epsilon = 1e-9);
+        assert_abs_diff_eq!(m * m.inverse(), Mat4x4::identity(), epsilon = 1e-9);
+    }
+
+    #[test]
+    fn singular() {
+        let m2 = Mat2x2::new(&[Vec2::new(1.0, 2.0), Vec2::new(2.0, 4.0)]);
+        assert!(m2.try_inverse().is_none());
+        let m3 = Mat3x3::new(&[Vec3::new(1.0, 2.0, 3.0),
+                               Vec3::new(4.0, 5.0, 6.0),
+                               Vec3::new(7.0, 8.0, 9.0)]);
+        assert!(m3.try_inverse().is_none());
+        assert!(m3.lu().inverse().is_none());
+        let m4 = Mat4x4::<f64>::scale(Vec3::new(1.0, 0.0, 1.0));
+        assert!(m4.try_inverse().is_none());
+        assert!(m4.determinant() == 0.0);
+        let small = Mat3x3::<f64>::identity() * 1e-3;
+        assert!(small.try_inverse().is_some());
+    }
+
+    #[test]
+    fn well_conditioned() {
+        let t = Mat4x4f::translate(Vec3f::new(60.0, 0.0, 0.0));
+        assert_abs_diff_eq!(t.inverse(), Mat4x4f::translate(Vec3f::new(-60.0, 0.0, 0.0)));
+        let far = Mat4x4::<f64>::translate(Vec3::new(1e5, -2e5, 3e5));
+        assert_abs_diff_eq!(far * far.inverse(), Mat4x4::identity(), epsilon = 1e-9);
+        let small = Mat4x4f::scale(Vec3f::new(1e-3, 1e-3, 1e-3));
+        assert_abs_diff_eq!(small * small.inverse(), Mat4x4f::identity(), epsilon = 1e-6);
+        assert!(small.lu().inverse().is_some());
+
+        let mut m = Matrix::<f32, 5, 5>::identity();
+        m[0][4] = 1e3;
+        m[3][4] = -500.0;
+        assert_abs_diff_eq!(m * m.lu().inverse().unwrap(), Matrix::identity());
+
+        // Translations are inverted exactly, no matter how far they move.
+        let huge = Mat4x4f::translate(Vec3f::new(1e7, 0.0, -3e7));
+        assert!(huge.try_inverse().unwrap() == Mat4x4f::translate(Vec3f::new(-1e7, 0.0, 3e7)));
+        m[0][4] = 1e7;
+        assert!(!m.lu().is_singular());
+        assert!(m.lu().inverse().unwrap()[0][4] == -1e7);
+    }
+
+    /// The normalized device depth of a point at `distance` in front of the camera.
+    fn ndc_depth(m: &Mat4x4<f64>, distance: f64, handedness: Handedness) -> f64 {
+        let z = match handedness {
+            Handedness::Left => distance,
+            Handedness::Right => -distance,
+        };
+        let p = m.mul_v(Vec4::new(0.0, 0.0, z, 1.0));
+        p.z() / p.w()
+    }
+
+    #[test]
+    fn projection_depth() {
+        use unit::Degrees;
+        let fovy = || Degrees::new(90.0);
+        for &handedness in [Handedness::Left, Handedness::Right].iter() {
+            let gl = DepthRange::NegativeOneToOne;
+            let vk = DepthRange::ZeroToOne;
+            let m = Mat4x4::perspective(fovy(), 2.0, 0.5, 50.0, handedness, gl);
+            assert!((ndc_depth(&m, 0.5, handedness) + 1.0).abs() < 1e-9);
+            assert!((ndc_depth(&m, 50.0, handedness) - 1.0).abs() < 1e-9);
+            let m = Mat4x4::perspective(fovy(), 2.0, 0.5, 50.0, handedness, vk);
+            assert!(ndc_depth(&m, 0.5, handedness).abs() < 1e-9);
+            assert!((ndc_depth(&m, 50.0, handedness) - 1.0).abs() < 1e-9);
+            let m = Mat4x4::perspective_reversed_z(fovy(), 2.0, 0.5, 50.0, handedness, vk);
+            assert!((ndc_depth(&m, 0.5, handedness) - 1.0).abs() < 1e-9);
+            assert!(ndc_depth(&m, 50.0, handedness).abs() < 1e-9);
+            let m = Mat4x4::perspective_infinite(fovy(), 2.0, 0.5, handedness, gl);
+            assert!((ndc_depth(&m, 0.5, handedness) + 1.0).abs() < 1e-9);
+            assert!((ndc_depth(&m, 1e12, handedness) - 1.0).abs() < 1e-9);
+            let m = Mat4x4::perspective_infinite_reversed_z(fovy(), 2.0, 0.5, handedness, vk);
+            assert!((ndc_depth(&m, 0.5, handedness) - 1.0).abs() < 1e-9);
+            assert!(ndc_depth(&m, 1e12, handedness).abs() < 1e-9);
+            let m = Mat4x4::orthographic(-1.0, 1.0, -1.0, 1.0, 2.0, 8.0, handedness, vk);
+            assert!(ndc_depth(&m, 2.0, handedness).abs() < 1e-9);
+            assert!((ndc_depth(&m, 8.0, handedness) - 1.0).abs() < 1e-9);
+
+            // The corner of the near plane maps to the corner of the viewport.
+            let (right, top) = (1.0, 0.5);
+            let z = if handedness == Handedness::Left { 0.5 } else { -0.5 };
+            let m = Mat4x4::perspective(fovy(), 2.0, 0.5, 50.0, handedness, gl);
+            let p = m.mul_v(Vec4::new(right, top, z, 1.0));
+            assert!((p.x() / p.w() - 1.0).abs() < 1e-9);
+            assert!((p.y() / p.w() - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn arrays() {
+        let rows = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        let m = Matrix::<f32, 3, 2>::from(rows);
+        assert!(m[1] == Vec3f::new(4.0, 5.0, 6.0));
+        assert!(m == Mat3x2f::new(&[Vec3f::new(1.0, 2.0, 3.0), Vec3f::new(4.0, 5.0, 6.0)]));
+        assert!(<[[f32; 3]; 2]>::from(m) == rows);
+    }
+
+    #[test]
+    fn lu_5x5() {
+        let m = Matrix::<f64, 5, 5>::new(&[Vector::from_slice(&[0.0, 2.0, 1.0, -1.0, 3.0]),
+                                             Vector::from_slice(&[1.0, 1.0, 0.0, 2.0, -1.0]),
+                                             Vector::from_slice(&[4.0, -2.0, 3.0, 0.0, 1.0]),
+                                             Vector::from_slice(&[2.0, 0.0, -1.0, 1.0, 5.0]),
+                                             Vector::from_slice(&[-3.0, 1.0, 2.0, 4.0, 0.0])]);
+        let lu = m.lu();
+        let mut pm = Matrix::<f64, 5, 5>::zero();
+        for (i, &p) in lu.permutation().iter().enumerate() {
+            pm[i] = m[p];
+        }
+        assert_abs_diff_eq!(lu.l() * lu.u(), pm, epsilon = 1e-9);
+        assert_abs_diff_eq!(m * m.inverse(), Matrix::identity(), epsilon = 1e-9);
+
+        let b = Vector::from_slice(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let x = lu.solve(b).unwrap();
+        assert!(m.mul_v(x).distance(b) < 1e-9);
     }
 }
 #[test]
 fn mul() {
-    // use vector::*;
-    // let v2 = Vec2f::new(1., 2.);
-    // let v3 = Vec3f::new(1., 2., 3.);
-    // let m1 = Mat3x2f::new(&[v3, v3]);
-    // let m2 = Mat2x3f::new(&[v2, v2, v2]);
-
-    // let m3: Mat2x2f = m1.mul(&m2);
-    // let m4 = m3.mul(&m3);
-    // println!("{:?}", m3);
+    use vector::*;
+    let v2 = Vec2f::new(1., 2.);
+    let v3 = Vec3f::new(1., 2., 3.);
+    let m1 = Mat3x2f::new(&[v3, v3]);
+    let m2 = Mat2x3f::new(&[v2, v2, v2]);
+
+    let m3: Mat2x2f = m1 * m2;
+    assert!(m3 == Mat2x2f::new(&[Vec2f::new(6., 12.), Vec2f::new(6., 12.)]));
+    let m4: Mat3x3f = m2 * m1;
+    assert!(m4 == Mat3x3f::new(&[Vec3f::new(3., 6., 9.); 3]));
+    assert!(m1 * v3 == Vec2f::new(14., 14.));
+    assert!(m2 * v2 == Vec3f::new(5., 5., 5.));
+    assert!(m1.transpose() == Mat2x3f::new(&[Vec2f::new(1., 1.),
+                                             Vec2f::new(2., 2.),
+                                             Vec2f::new(3., 3.)]));
 }
 #[test]
 fn matrix_identity() {
-    // use vector::*;
-    // let m = Mat2x2f::identity();
+    use vector::*;
+    let m = Mat2x2f::new(&[Vec2f::new(1., 2.), Vec2f::new(3., 4.)]);
+    let identity = Mat2x2f::identity();
+    assert!(identity * m == m);
+    assert!(identity.transpose() == identity);
+
+    let mut sum = m + identity;
+    assert!(sum == Mat2x2f::new(&[Vec2f::new(2., 2.), Vec2f::new(3., 5.)]));
+    sum -= identity;
+    assert!(sum == m);
+    sum += m;
+    assert!(sum == m * 2.);
+    assert!(-m - m == m * -2.);
+    sum *= 0.5;
+    sum *= identity;
+    assert!(sum == m);
 }
 
-pub type Mat4x4<T> = Matrix<T, U4, U4>;
-pub type Mat3x3<T> = Matrix<T, U3, U3>;
-pub type Mat3x2<T> = Matrix<T, U3, U2>;
-pub type Mat2x3<T> = Matrix<T, U2, U3>;
-pub type Mat2x2<T> = Matrix<T, U2, U2>;
-pub type Mat4x1<T> = Matrix<T, U4, U2>;
+pub type Mat4x4<T> = Matrix<T, 4, 4>;
+pub type Mat3x3<T> = Matrix<T, 3, 3>;
+pub type Mat3x2<T> = Matrix<T, 3, 2>;
+pub type Mat2x3<T> = Matrix<T, 2, 3>;
+pub type Mat2x2<T> = Matrix<T, 2, 2>;
+pub type Mat4x1<T> = Matrix<T, 4, 1>;
 
 pub type Mat4x4f = Mat4x4<f32>;
 pub type Mat4x1f = Mat4x1<f32>;
 pub type Mat3x2f = Mat3x2<f32>;
 pub type Mat2x3f = Mat2x3<f32>;
 pub type Mat2x2f = Mat2x2<f32>;
+pub type Mat3x3f = Mat3x3<f32>;
@@ -0,0 +1,412 @@
+use num::{Float, NumCast};
+use vector::{Vec3, Point3, Normal3};
+use matrix::Mat3x3;
+use quaternion::Quaternion;
+use aabb::Aabb;
+use transform::Transform;
+use ray::{Ray, RayHit};
+
+/// An oriented bounding box, a box with the extents `-half_extents..half_extents` in its
+/// local space, rotated by `rotation` and moved to `center`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Obb<T>
+    where T: Float
+{
+    center: Point3<T>,
+    half_extents: Vec3<T>,
+    rotation: Quaternion<T>,
+}
+
+impl<T> Obb<T>
+    where T: Float
+{
+    pub fn new(center: Point3<T>, half_extents: Vec3<T>, rotation: Quaternion<T>) -> Self {
+        Obb {
+            center,
+            half_extents,
+            rotation,
+        }
+    }
+
+    /// The tightest box around the transformed corners of `aabb` that is aligned with the
+    /// transformed x and y axes. Exact for rotations, translations and scales.
+    pub fn from_aabb(aabb: Aabb<T>, transform: &Transform<T>) -> Self {
+        let (zero, one) = (T::zero(), T::one());
+        let x = transform.transform_vector(Vec3::new(one, zero, zero))
+            .normalize()
+            .expect("Transforms are invertible");
+        let y = transform.transform_vector(Vec3::new(zero, one, zero));
+        // Gram–Schmidt, in case the transform contains a shear.
+        let y = (y - x * x.dot(y)).normalize().expect("Transforms are invertible");
+        let corners = (0..8).map(|i| {
+            let (min, max) = (aabb.min(), aabb.max());
+            let pick = |axis: usize| if i & (1 << axis) == 0 { min[axis] } else { max[axis] };
+            transform.transform_point(Point3::new(pick(0), pick(1), pick(2)))
+        });
+        Self::fit_axes([x, y, x.cross(y)], corners).expect("Eight corners")
+    }
+
+    /// Fits a box to a point cloud, aligned with the principal axes of the covariance of
+    /// the points. Returns `None` if `points` is empty.
+    pub fn from_points(points: &[Point3<T>]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let count: T = NumCast::from(points.len()).unwrap();
+        let mean = points.iter().fold(Vec3::zero(), |acc, p| acc + p.to_vector()) / count;
+        let mut covariance = [[T::zero(); 3]; 3];
+        for p in points {
+            let d = p.to_vector() - mean;
+            for (i, row) in covariance.iter_mut().enumerate() {
+                for (j, c) in row.iter_mut().enumerate() {
+                    *c = *c + d[i] * d[j] / count;
+                }
+            }
+        }
+        let [x, y, _] = eigenvectors(covariance);
+        Self::fit_axes([x, y, x.cross(y)], points.iter().cloned())
+    }
+
+    /// The box with the orthonormal `axes` that encloses all points.
+    fn fit_axes<I>(axes: [Vec3<T>; 3], points: I) -> Option<Self>
+        where I: Iterator<Item = Point3<T>>
+    {
+        let mut bounds: Option<(Vec3<T>, Vec3<T>)> = None;
+        for p in points {
+            let local = Vec3::new(axes[0].dot(p), axes[1].dot(p), axes[2].dot(p));
+            bounds = Some(bounds.map_or((local, local),
+                                        |(min, max)| (min.min(local), max.max(local))));
+        }
+        let (min, max) = bounds?;
+        let half: T = NumCast::from(0.5).unwrap();
+        let local_center = (min + max) * half;
+        let center = axes.iter()
+            .enumerate()
+            .fold(Vec3::zero(), |acc, (i, &axis)| acc + axis * local_center[i]);
+        // The axes are the columns of the rotation matrix.
+        let rotation = Mat3x3::new(&[Vec3::new(axes[0].x(), axes[1].x(), axes[2].x()),
+                                     Vec3::new(axes[0].y(), axes[1].y(), axes[2].y()),
+                                     Vec3::new(axes[0].z(), axes[1].z(), axes[2].z())]);
+        Some(Obb::new(center.to_point(),
+                      (max - min) * half,
+                      Quaternion::from_mat3(&rotation)))
+    }
+
+    pub fn center(&self) -> Point3<T> {
+        self.center
+    }
+
+    pub fn half_extents(&self) -> Vec3<T> {
+        self.half_extents
+    }
+
+    pub fn rotation(&self) -> Quaternion<T> {
+        self.rotation
+    }
+
+    /// The local x, y and z axes of the box in world space.
+    pub fn axes(&self) -> [Vec3<T>; 3] {
+        let (zero, one) = (T::zero(), T::one());
+        [self.rotation * Vec3::new(one, zero, zero),
+         self.rotation * Vec3::new(zero, one, zero),
+         self.rotation * Vec3::new(zero, zero, one)]
+    }
+
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [Point3<T>; 8] {
+        let axes = self.axes();
+        let mut corners = [self.center; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            for (axis, &dir) in axes.iter().enumerate() {
+                let extent = self.half_extents[axis];
+                let sign = if i & (1 << axis) == 0 { -extent } else { extent };
+                *corner += dir * sign;
+            }
+        }
+        corners
+    }
+
+    /// The smallest `Aabb` that contains the box.
+    pub fn to_aabb(&self) -> Aabb<T> {
+        let axes = self.axes();
+        let mut extent = Vec3::zero();
+        for (i, axis) in axes.iter().enumerate() {
+            extent += axis.map(|v| (v * self.half_extents[i]).abs());
+        }
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// `point` in the local space of the box, relative to its center.
+    fn local_point(&self, point: Point3<T>) -> Vec3<T> {
+        let d = point - self.center;
+        let axes = self.axes();
+        Vec3::new(axes[0].dot(d), axes[1].dot(d), axes[2].dot(d))
+    }
+
+    /// The point of the box closest to `point`, `point` itself if it is inside.
+    pub fn closest_point(&self, point: Point3<T>) -> Point3<T> {
+        let local = self.local_point(point);
+        let axes = self.axes();
+        (0..3).fold(self.center, |acc, i| {
+            let h = self.half_extents[i];
+            acc + axes[i] * local[i].max(-h).min(h)
+        })
+    }
+
+    pub fn contains_point(&self, point: Point3<T>) -> bool {
+        let local = self.local_point(point);
+        (0..3).all(|i| local[i].abs() <= self.half_extents[i])
+    }
+
+    /// Separating axis test between two boxes, Ericson, Real-Time Collision Detection 4.4.1.
+    pub fn overlap(&self, other: &Self) -> bool {
+        let a_axes = self.axes();
+        let b_axes = other.axes();
+        let (a, b) = (self.half_extents, other.half_extents);
+        // Rotation of `other` relative to `self`, the epsilon keeps nearly parallel edges
+        // from producing a cross product close to zero that wrongly separates.
+        let mut r = [[T::zero(); 3]; 3];
+        let mut abs_r = [[T::zero(); 3]; 3];
+        let epsilon = T::epsilon() * NumCast::from(16).unwrap();
+        for i in 0..3 {
+            for j in 0..3 {
+                r[i][j] = a_axes[i].dot(b_axes[j]);
+                abs_r[i][j] = r[i][j].abs() + epsilon;
+            }
+        }
+        let d = other.center - self.center;
+        let t = [a_axes[0].dot(d), a_axes[1].dot(d), a_axes[2].dot(d)];
+
+        // The face normals of `self`.
+        for i in 0..3 {
+            let rb = b[0] * abs_r[i][0] + b[1] * abs_r[i][1] + b[2] * abs_r[i][2];
+            if t[i].abs() > a[i] + rb {
+                return false;
+            }
+        }
+        // The face normals of `other`.
+        for j in 0..3 {
+            let ra = a[0] * abs_r[0][j] + a[1] * abs_r[1][j] + a[2] * abs_r[2][j];
+            let tb = t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j];
+            if tb.abs() > ra + b[j] {
+                return false;
+            }
+        }
+        // The cross products of the edge directions.
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = a[i1] * abs_r[i2][j] + a[i2] * abs_r[i1][j];
+                let rb = b[j1] * abs_r[i][j2] + b[j2] * abs_r[i][j1];
+                let dist = t[i2] * r[i1][j] - t[i1] * r[i2][j];
+                if dist.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    pub fn overlap_aabb(&self, aabb: Aabb<T>) -> bool {
+        let half: T = NumCast::from(0.5).unwrap();
+        let other = Obb::new(aabb.center(), aabb.diagonale() * half, Quaternion::identity());
+        self.overlap(&other)
+    }
+
+    /// The ray in the local space of the box and the box as an `Aabb` in that space.
+    fn local_ray(&self, ray: &Ray<T>) -> (Ray<T>, Aabb<T>) {
+        let axes = self.axes();
+        let d = ray.direction;
+        let local = Ray::new(self.local_point(ray.origin).to_point(),
+                             Vec3::new(axes[0].dot(d), axes[1].dot(d), axes[2].dot(d)),
+                             ray.max_time);
+        let h = self.half_extents;
+        (local, Aabb::new((-h).to_point(), h.to_point()))
+    }
+
+    /// Returns the ray parameters at which the ray enters and exits the box, clipped to
+    /// `[0, max_time]`.
+    pub fn intersect_ray(&self, ray: &Ray<T>) -> Option<(T, T)> {
+        let (local, aabb) = self.local_ray(ray);
+        aabb.intersect_ray(&local)
+    }
+
+    /// The first point where the ray crosses the surface of the box, this is the exit point
+    /// if the ray starts inside. The normal always points out of the box.
+    pub fn ray_hit(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let (local, aabb) = self.local_ray(ray);
+        let hit = aabb.ray_hit(&local)?;
+        let normal: Normal3<T> = (self.rotation * hit.normal.to_vector()).to_normal();
+        Some(RayHit::new(hit.time, ray.at(hit.time), normal))
+    }
+}
+
+/// The eigenvectors of a symmetric 3x3 matrix, sorted by descending eigenvalue. Cyclic
+/// Jacobi rotations, Ericson, Real-Time Collision Detection 4.3.4.
+fn eigenvectors<T: Float>(mut a: [[T; 3]; 3]) -> [Vec3<T>; 3] {
+    let (zero, one) = (T::zero(), T::one());
+    let mut v = [[one, zero, zero], [zero, one, zero], [zero, zero, one]];
+    let two: T = NumCast::from(2).unwrap();
+    for _ in 0..50 {
+        // The largest off diagonal element.
+        let (mut p, mut q) = (0, 1);
+        for &(i, j) in [(0, 2), (1, 2)].iter() {
+            if a[i][j].abs() > a[p][q].abs() {
+                p = i;
+                q = j;
+            }
+        }
+        let off_diagonal = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+        let diagonal = a[0][0].abs() + a[1][1].abs() + a[2][2].abs();
+        if off_diagonal <= T::epsilon() * diagonal || a[p][q] == zero {
+            break;
+        }
+        // The rotation that zeroes `a[p][q]`.
+        let r = (a[q][q] - a[p][p]) / (two * a[p][q]);
+        let t = if r >= zero {
+            one / (r + (one + r * r).sqrt())
+        } else {
+            -one / (-r + (one + r * r).sqrt())
+        };
+        let c = one / (one + t * t).sqrt();
+        let s = t * c;
+        // a = J^T a J, v = v J
+        for row in a.iter_mut() {
+            let (ap, aq) = (row[p], row[q]);
+            row[p] = c * ap - s * aq;
+            row[q] = s * ap + c * aq;
+        }
+        let (row_p, row_q) = (a[p], a[q]);
+        for (k, (&pk, &qk)) in row_p.iter().zip(row_q.iter()).enumerate() {
+            a[p][k] = c * pk - s * qk;
+            a[q][k] = s * pk + c * qk;
+        }
+        for row in v.iter_mut() {
+            let (vp, vq) = (row[p], row[q]);
+            row[p] = c * vp - s * vq;
+            row[q] = s * vp + c * vq;
+        }
+    }
+    let mut order = [0, 1, 2];
+    order.sort_by(|&i, &j| a[j][j].partial_cmp(&a[i][i]).unwrap_or(::std::cmp::Ordering::Equal));
+    let column = |i: usize| Vec3::new(v[0][i], v[1][i], v[2][i]);
+    [column(order[0]), column(order[1]), column(order[2])]
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use obb::*;
+    use unit::{Radians, Degrees};
+    use collision;
+    use test_util::Lcg;
+
+    fn rotated_z(degrees: f32) -> Quaternion<f32> {
+        Quaternion::new(Vec3f::new(0., 0., 1.), Degrees::new(degrees))
+    }
+
+    #[test]
+    fn from_aabb() {
+        let aabb = Aabb::new(Point3f::new(-1., -2., -3.), Point3f::new(1., 2., 3.));
+        let t = &Transform::translate(Vec3f::new(5., 0., 0.)) *
+                &Transform::rotation_z(Radians::new(::std::f32::consts::PI / 4.));
+        let obb = Obb::from_aabb(aabb, &t);
+        assert!(obb.center().distance(Point3f::new(5., 0., 0.)) < 1e-5);
+        assert!(obb.half_extents().distance(Vec3f::new(1., 2., 3.)) < 1e-5);
+        // The transformed aabb is much larger than the oriented box.
+        assert!(t.transform_aabb(aabb).volume() > 1.5 * aabb.volume());
+        for corner in obb.corners().iter() {
+            assert!(obb.to_aabb().expand(1e-5).inside(*corner));
+        }
+
+        let scaled = Obb::from_aabb(aabb, &Transform::scale(Vec3f::new(2., 1., 1.)));
+        assert!(scaled.half_extents().distance(Vec3f::new(2., 2., 3.)) < 1e-5);
+    }
+
+    #[test]
+    fn closest_point() {
+        let obb = Obb::new(Point3f::zero(), Vec3f::new(1., 1., 1.), rotated_z(45.));
+        let diagonal = 2f32.sqrt();
+        assert!(obb.contains_point(Point3f::new(diagonal - 0.01, 0., 0.)));
+        assert!(!obb.contains_point(Point3f::new(1.1, 1.1, 0.)));
+        let closest = obb.closest_point(Point3f::new(5., 0., 0.));
+        assert!(closest.distance(Point3f::new(diagonal, 0., 0.)) < 1e-5);
+        let inside = Point3f::new(0.2, 0.3, 0.4);
+        assert!(obb.closest_point(inside).distance(inside) < 1e-6);
+    }
+
+    #[test]
+    fn separating_axis() {
+        let a = Obb::new(Point3f::zero(), Vec3f::new(1., 1., 1.), rotated_z(45.));
+        // Axis aligned boxes would overlap, the rotated corner does not reach.
+        let b = Obb::new(Point3f::new(2.3, 1.3, 0.), Vec3f::new(1., 1., 1.), rotated_z(0.));
+        assert!(!a.overlap(&b));
+        assert!(a.to_aabb().overlap(b.to_aabb()));
+        let c = Obb::new(Point3f::new(2., 0., 0.), Vec3f::new(1., 1., 1.), rotated_z(0.));
+        assert!(a.overlap(&c) && c.overlap(&a));
+        assert!(a.overlap_aabb(Aabb::new(Point3f::new(1.3, -0.1, -1.), Point3f::new(2., 0.1, 1.))));
+        assert!(!a.overlap_aabb(Aabb::new(Point3f::new(1.2, 1.2, -1.), Point3f::new(2., 2., 1.))));
+
+        // GJK agrees on random orientations, which includes boxes that are only separated
+        // along the cross product of two edges.
+        let mut rng = Lcg(1);
+        for _ in 0..500 {
+            let mut random_obb = |spread: f32| {
+                let axis = Vec3f::new(rng.next() - 0.5, rng.next() - 0.5, rng.next() - 0.5);
+                let angle = Degrees::new(rng.next() * 360.);
+                let rotation = Quaternion::new(axis.normalize().unwrap(), angle);
+                let center = rng.point(spread);
+                let half_extents = rng.point(1.).to_vector() + Vec3f::new(0.2, 0.2, 0.2);
+                Obb::new(center, half_extents, rotation)
+            };
+            let (a, b) = (random_obb(0.), random_obb(4.));
+            if let Some(closest) = collision::distance(&a, &b) {
+                if closest.distance > 1e-3 {
+                    assert!(!a.overlap(&b));
+                }
+            } else if collision::penetration(&a, &b).unwrap().depth > 1e-3 {
+                assert!(a.overlap(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn ray() {
+        let obb = Obb::new(Point3f::zero(), Vec3f::new(1., 1., 1.), rotated_z(45.));
+        let ray = Ray::new(Point3f::new(-5., 0., 0.), Vec3f::new(1., 0., 0.), 10.);
+        let (t_enter, t_exit) = obb.intersect_ray(&ray).unwrap();
+        let diagonal = 2f32.sqrt();
+        assert!((t_enter - (5. - diagonal)).abs() < 1e-5);
+        assert!((t_exit - (5. + diagonal)).abs() < 1e-5);
+        let hit = obb.ray_hit(&ray).unwrap();
+        assert!(hit.normal.x() < 0. && (hit.normal.length() - 1.).abs() < 1e-5);
+        let miss = Ray::new(Point3f::new(-5., 1.5, 0.), Vec3f::new(1., 0., 0.), 10.);
+        assert!(obb.intersect_ray(&miss).is_none());
+    }
+
+    #[test]
+    fn from_points() {
+        // A long thin box rotated by 30 degrees around z.
+        let rotation = rotated_z(30.);
+        let mut points = Vec::new();
+        for i in 0..11 {
+            for j in 0..3 {
+                for k in 0..3 {
+                    let local = Vec3f::new(i as f32 - 5.,
+                                           (j as f32 - 1.) * 0.5,
+                                           (k as f32 - 1.) * 0.2);
+                    points.push((rotation * local + Vec3f::new(1., 2., 3.)).to_point());
+                }
+            }
+        }
+        let obb = Obb::from_points(&points).unwrap();
+        assert!(obb.center().distance(Point3f::new(1., 2., 3.)) < 1e-4);
+        assert!(obb.half_extents().distance(Vec3f::new(5., 0.5, 0.2)) < 1e-4);
+        assert!(obb.axes()[0].dot(rotation * Vec3f::new(1., 0., 0.)).abs() > 1. - 1e-5);
+        for p in points.iter() {
+            assert!(obb.closest_point(*p).distance(*p) < 1e-4);
+        }
+        assert!(Obb::<f32>::from_points(&[]).is_none());
+    }
+}
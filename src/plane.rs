@@ -0,0 +1,225 @@
+use num::{Float, NumCast};
+use vector::{Vec3, Vec4, Point3, Normal3};
+use matrix::Mat4x4;
+use aabb::{Aabb, Sphere};
+
+/// On which side of a plane something lies.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Side {
+    /// In the half space the normal points to.
+    Front,
+    Back,
+    /// A point on the plane or a volume that crosses it.
+    Intersecting,
+}
+
+/// The plane of all points `p` with `normal.dot(p) + distance == 0`, the normal is
+/// normalized. `-distance` is the signed distance of the plane from the origin.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane<T>
+    where T: Float
+{
+    normal: Normal3<T>,
+    distance: T,
+}
+
+impl<T> Plane<T>
+    where T: Float
+{
+    /// Normalizes `normal` and scales `distance` by the same factor.
+    pub fn new(normal: Normal3<T>, distance: T) -> Option<Self> {
+        let length = normal.length();
+        if length == T::zero() {
+            return None;
+        }
+        Some(Plane {
+            normal: normal / length,
+            distance: distance / length,
+        })
+    }
+
+    pub fn from_point_normal(point: Point3<T>, normal: Normal3<T>) -> Option<Self> {
+        Self::new(normal, -normal.dot(point))
+    }
+
+    /// The plane through three points, the normal follows the winding order `a, b, c`.
+    /// Returns `None` for collinear points.
+    pub fn from_points(a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Option<Self> {
+        Self::from_point_normal(a, (b - a).cross(c - a).to_normal())
+    }
+
+    /// A plane in the form `(a, b, c, d)` of `a * x + b * y + c * z + d = 0`, e.g. a
+    /// combination of the rows of a projection matrix. Returns `None` if `(a, b, c)` is zero.
+    pub fn from_vec4(v: Vec4<T>) -> Option<Self> {
+        Self::new(Normal3::new(v.x(), v.y(), v.z()), v.w())
+    }
+
+    pub fn to_vec4(&self) -> Vec4<T> {
+        self.normal.to_vector().extend(self.distance)
+    }
+
+    pub fn normal(&self) -> Normal3<T> {
+        self.normal
+    }
+
+    pub fn distance(&self) -> T {
+        self.distance
+    }
+
+    /// Positive in front of the plane, negative behind it.
+    pub fn signed_distance(&self, point: Point3<T>) -> T {
+        self.normal.dot(point) + self.distance
+    }
+
+    /// Only points exactly on the plane are `Intersecting`, use `classify_point_eps` for
+    /// computed points that may be off by rounding.
+    pub fn classify_point(&self, point: Point3<T>) -> Side {
+        let d = self.signed_distance(point);
+        if d > T::zero() {
+            Side::Front
+        } else if d < T::zero() {
+            Side::Back
+        } else {
+            Side::Intersecting
+        }
+    }
+
+    /// Treats the plane as a slab of thickness `2 * epsilon`, points within `epsilon` of it
+    /// are `Intersecting`.
+    pub fn classify_point_eps(&self, point: Point3<T>, epsilon: T) -> Side {
+        self.classify_radius(self.signed_distance(point), epsilon)
+    }
+
+    pub fn classify_aabb(&self, aabb: Aabb<T>) -> Side {
+        let half: T = NumCast::from(0.5).unwrap();
+        let extents = aabb.diagonale() * half;
+        // The projection of the half extents onto the normal.
+        let radius = (0..3).fold(T::zero(), |acc, i| acc + (self.normal[i] * extents[i]).abs());
+        self.classify_radius(self.signed_distance(aabb.center()), radius)
+    }
+
+    pub fn classify_sphere(&self, sphere: &Sphere<T>) -> Side {
+        self.classify_radius(self.signed_distance(sphere.center()), sphere.radius())
+    }
+
+    fn classify_radius(&self, distance: T, radius: T) -> Side {
+        if distance > radius {
+            Side::Front
+        } else if distance < -radius {
+            Side::Back
+        } else {
+            Side::Intersecting
+        }
+    }
+
+    /// The point on the plane closest to `point`.
+    pub fn project_point(&self, point: Point3<T>) -> Point3<T> {
+        point - self.normal.to_vector() * self.signed_distance(point)
+    }
+
+    /// The matrix that mirrors points and vectors at the plane.
+    #[rustfmt::skip]
+    pub fn reflection(&self) -> Mat4x4<T> {
+        let two: T = NumCast::from(2).unwrap();
+        let (x, y, z, d) = (self.normal.x(), self.normal.y(), self.normal.z(), self.distance);
+        let one = T::one();
+        Mat4x4::new(&[Vec4::new(one - two * x * x, -two * x * y,       -two * x * z,       -two * x * d),
+                      Vec4::new(-two * y * x,       one - two * y * y, -two * y * z,       -two * y * d),
+                      Vec4::new(-two * z * x,       -two * z * y,       one - two * z * z, -two * z * d),
+                      Vec4::new(T::zero(),          T::zero(),          T::zero(),          one)])
+    }
+
+    /// The single point shared by three planes, `None` if two of them are parallel or all
+    /// three share a line.
+    pub fn intersect_planes(a: &Self, b: &Self, c: &Self) -> Option<Point3<T>> {
+        let (n1, n2, n3) = (a.normal.to_vector(), b.normal.to_vector(), c.normal.to_vector());
+        let n2_n3 = n2.cross(n3);
+        let denom = n1.dot(n2_n3);
+        if denom.abs() <= T::epsilon() {
+            return None;
+        }
+        let p: Vec3<T> = (n2_n3 * a.distance + n3.cross(n1) * b.distance +
+                          n1.cross(n2) * c.distance) / -denom;
+        Some(p.to_point())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use plane::*;
+    use matrix::Mat4x4f;
+
+    #[test]
+    fn construction() {
+        let plane = Plane::from_points(Point3f::new(0., 0., 2.),
+                                       Point3f::new(1., 0., 2.),
+                                       Point3f::new(0., 1., 2.))
+            .unwrap();
+        assert!(plane.normal() == Normal3f::new(0., 0., 1.));
+        assert!(plane.distance() == -2.);
+        assert!(plane.signed_distance(Point3f::new(5., 5., 5.)) == 3.);
+        let same = Plane::from_point_normal(Point3f::new(3., 4., 2.), Normal3f::new(0., 0., 4.));
+        assert!(same == Some(plane));
+        assert!(Plane::from_vec4(Vec4f::new(0., 0., 2., -4.)) == Some(plane));
+        assert!(plane.to_vec4() == Vec4f::new(0., 0., 1., -2.));
+        let collinear = (Point3f::zero(), Point3f::new(1., 0., 0.), Point3f::new(2., 0., 0.));
+        assert!(Plane::from_points(collinear.0, collinear.1, collinear.2).is_none());
+    }
+
+    #[test]
+    fn classification() {
+        let plane = Plane::from_point_normal(Point3f::new(0., 1., 0.), Normal3f::new(0., 1., 0.))
+            .unwrap();
+        assert!(plane.classify_point(Point3f::new(0., 2., 0.)) == Side::Front);
+        assert!(plane.classify_point(Point3f::new(0., 0., 0.)) == Side::Back);
+        assert!(plane.classify_point(Point3f::new(7., 1., 3.)) == Side::Intersecting);
+        assert!(plane.classify_point_eps(Point3f::new(0., 1.05, 0.), 0.1) == Side::Intersecting);
+        assert!(plane.classify_point_eps(Point3f::new(0., 1.2, 0.), 0.1) == Side::Front);
+        assert!(plane.classify_point_eps(Point3f::new(0., 0.8, 0.), 0.1) == Side::Back);
+        // A projected point is only on a tilted plane up to rounding.
+        let tilted = Plane::new(Normal3f::new(1., 2., 3.), -0.7).unwrap();
+        for p in [Point3f::new(0.3, -7.1, 2.9), Point3f::new(11., 0.1, -5.)].iter() {
+            let projected = tilted.project_point(*p);
+            assert!(tilted.classify_point_eps(projected, 1e-5) == Side::Intersecting);
+        }
+        let aabb = |y: f32| Aabb::new(Point3f::new(-1., y, -1.), Point3f::new(1., y + 0.5, 1.));
+        assert!(plane.classify_aabb(aabb(1.5)) == Side::Front);
+        assert!(plane.classify_aabb(aabb(0.)) == Side::Back);
+        assert!(plane.classify_aabb(aabb(0.75)) == Side::Intersecting);
+        let sphere = |y: f32| Sphere::new(Point3f::new(0., y, 0.), 0.5);
+        assert!(plane.classify_sphere(&sphere(2.)) == Side::Front);
+        assert!(plane.classify_sphere(&sphere(0.)) == Side::Back);
+        assert!(plane.classify_sphere(&sphere(1.2)) == Side::Intersecting);
+    }
+
+    #[test]
+    fn projection_and_reflection() {
+        let plane = Plane::from_point_normal(Point3f::new(1., 1., 1.), Normal3f::new(1., 1., 0.))
+            .unwrap();
+        let p = Point3f::new(3., 1., 5.);
+        let projected = plane.project_point(p);
+        assert!(plane.signed_distance(projected).abs() < 1e-6);
+        assert!(projected.distance(Point3f::new(2., 0., 5.)) < 1e-6);
+
+        let m = plane.reflection();
+        let reflected = m.mul_v(p.to_vector().extend(1.)).truncate();
+        assert!(reflected.distance(Vec3f::new(1., -1., 5.)) < 1e-6);
+        // Reflecting twice is the identity.
+        let twice = m * m;
+        assert_abs_diff_eq!(twice, Mat4x4f::identity(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn three_planes() {
+        let x = Plane::from_point_normal(Point3f::new(1., 0., 0.), Normal3f::new(1., 0., 0.));
+        let y = Plane::from_point_normal(Point3f::new(0., 2., 0.), Normal3f::new(0., -1., 0.));
+        let z = Plane::from_point_normal(Point3f::new(0., 0., 3.), Normal3f::new(1., 1., 1.));
+        let (x, y, z) = (x.unwrap(), y.unwrap(), z.unwrap());
+        let p = Plane::intersect_planes(&x, &y, &z).unwrap();
+        assert!(p.distance(Point3f::new(1., 2., 0.)) < 1e-5);
+        let parallel = Plane::from_point_normal(Point3f::new(5., 0., 0.), Normal3f::new(1., 0., 0.))
+            .unwrap();
+        assert!(Plane::intersect_planes(&x, &y, &parallel).is_none());
+    }
+}
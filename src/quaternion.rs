@@ -1,8 +1,42 @@
-use num::Float;
+use num::{Float, NumCast};
 use vector::Vec3;
-use unit::*;
-use std::ops::{Mul, Div};
-#[derive(Copy, Clone)]
+use matrix::{Mat3x3, Mat4x4};
+use unit::{Angle, Radians};
+use std::ops::{Add, Sub, Mul, Div, Neg};
+
+/// The order in which the three elementary rotations of an euler angle triple are applied.
+/// `XYZ` rotates around the x axis first, then around the y axis and finally around the
+/// z axis, which is the same as `q_z * q_y * q_x`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EulerOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    /// Returns the axis indices in the order in which they are applied.
+    fn axes(self) -> (usize, usize, usize) {
+        match self {
+            EulerOrder::XYZ => (0, 1, 2),
+            EulerOrder::XZY => (0, 2, 1),
+            EulerOrder::YXZ => (1, 0, 2),
+            EulerOrder::YZX => (1, 2, 0),
+            EulerOrder::ZXY => (2, 0, 1),
+            EulerOrder::ZYX => (2, 1, 0),
+        }
+    }
+
+    /// `true` if the axes form a cyclic permutation of `(x, y, z)`.
+    fn is_even(self) -> bool {
+        matches!(self, EulerOrder::XYZ | EulerOrder::YZX | EulerOrder::ZXY)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Quaternion<T>
     where T: Float
 {
@@ -13,23 +47,36 @@ pub struct Quaternion<T>
 impl<T> Quaternion<T>
     where T: Float
 {
-    pub fn new<R: ToRadians<T>>(axis: Vec3<T>, angle: R) -> Self {
-        use num::NumCast;
-        let half_angle = angle.value() / NumCast::from(2.0).unwrap();
+    pub fn new<A: Angle<T>>(axis: Vec3<T>, angle: A) -> Self {
+        let half_angle = angle.radians().value / NumCast::from(2.0).unwrap();
         let w = half_angle.cos();
         let normal = axis.normalize().expect("Non zero vector");
         Quaternion {
             v: normal * half_angle.sin(),
-            w: w,
+            w,
         }
     }
 
     pub fn raw(v: Vec3<T>, w: T) -> Self {
-        Quaternion { v: v, w: w }
+        Quaternion { v, w }
+    }
+
+    pub fn identity() -> Self {
+        Self::raw(Vec3::zero(), T::one())
+    }
+
+    /// The vector part `(x, y, z)` of the quaternion.
+    pub fn v(&self) -> Vec3<T> {
+        self.v
+    }
+
+    /// The scalar part of the quaternion.
+    pub fn w(&self) -> T {
+        self.w
     }
 
     pub fn conjugate(self) -> Self {
-        Self::raw(self.v, -self.w)
+        Self::raw(-self.v, self.w)
     }
 
     pub fn inverse(self) -> Self {
@@ -47,6 +94,181 @@ impl<T> Quaternion<T>
     pub fn dot(self, other: Self) -> T {
         self.w * other.w + Vec3::dot(self.v, other.v)
     }
+
+    pub fn normalize(self) -> Option<Self> {
+        let len_sq = self.length_sq();
+        if len_sq == T::one() {
+            Some(self)
+        } else if len_sq == T::zero() {
+            None
+        } else {
+            Some(self / len_sq.sqrt())
+        }
+    }
+
+    /// Normalized linear interpolation. Always takes the shortest path, but the angular
+    /// velocity is not constant.
+    pub fn nlerp(self, torwards: Self, scale: T) -> Self {
+        let torwards = if self.dot(torwards) < T::zero() {
+            -torwards
+        } else {
+            torwards
+        };
+        (self * (T::one() - scale) + torwards * scale)
+            .normalize()
+            .expect("Non zero quaternion")
+    }
+
+    /// Spherical linear interpolation along the shortest arc, with constant angular velocity.
+    /// Both quaternions are expected to be normalized.
+    pub fn slerp(self, torwards: Self, scale: T) -> Self {
+        let mut cos_theta = self.dot(torwards);
+        let torwards = if cos_theta < T::zero() {
+            cos_theta = -cos_theta;
+            -torwards
+        } else {
+            torwards
+        };
+        // Falls back to nlerp if both rotations are almost identical, sin(theta) would be
+        // close to zero.
+        let threshold: T = NumCast::from(0.9995).unwrap();
+        if cos_theta > threshold {
+            return self.nlerp(torwards, scale);
+        }
+        let theta = cos_theta.min(T::one()).acos();
+        let sin_theta = theta.sin();
+        let a = ((T::one() - scale) * theta).sin() / sin_theta;
+        let b = (scale * theta).sin() / sin_theta;
+        self * a + torwards * b
+    }
+
+    /// Returns the normalized rotation axis and the rotation angle. The identity rotation
+    /// returns the x axis.
+    pub fn to_axis_angle(self) -> (Vec3<T>, Radians<T>) {
+        let q = self.normalize().expect("Non zero quaternion");
+        let q = if q.w < T::zero() { -q } else { q };
+        let two: T = NumCast::from(2.0).unwrap();
+        let angle = two * q.w.min(T::one()).acos();
+        let axis = match q.v.normalize() {
+            Some(axis) => axis,
+            None => Vec3::new(T::one(), T::zero(), T::zero()),
+        };
+        (axis, Radians::new(angle))
+    }
+
+    /// The shortest rotation that rotates the direction `from` onto the direction `to`.
+    pub fn from_rotation_arc(from: Vec3<T>, to: Vec3<T>) -> Self {
+        let from = from.normalize().expect("Non zero vector");
+        let to = to.normalize().expect("Non zero vector");
+        let two: T = NumCast::from(2.0).unwrap();
+        let d = from.dot(to);
+        let threshold = T::one() - NumCast::from(1e-6).unwrap();
+        if d >= threshold {
+            return Self::identity();
+        }
+        if d <= -threshold {
+            // Opposite directions, any axis orthogonal to `from` works.
+            let other = if from.x().abs() < NumCast::from(0.9).unwrap() {
+                Vec3::new(T::one(), T::zero(), T::zero())
+            } else {
+                Vec3::new(T::zero(), T::one(), T::zero())
+            };
+            let axis = other.cross(from).normalize().expect("Non zero vector");
+            return Self::raw(axis, T::zero());
+        }
+        let s = ((T::one() + d) * two).sqrt();
+        Self::raw(from.cross(to) / s, s / two)
+    }
+
+    /// Builds a rotation from euler angles, rotating around `x`, `y` and `z` in the given
+    /// `order`.
+    pub fn from_euler<A: Angle<T>>(order: EulerOrder, x: A, y: A, z: A) -> Self {
+        let axes = [Vec3::new(T::one(), T::zero(), T::zero()),
+                    Vec3::new(T::zero(), T::one(), T::zero()),
+                    Vec3::new(T::zero(), T::zero(), T::one())];
+        let rotations = [Self::new(axes[0], x), Self::new(axes[1], y), Self::new(axes[2], z)];
+        let (i, j, k) = order.axes();
+        rotations[k] * rotations[j] * rotations[i]
+    }
+
+    /// Decomposes the rotation into euler angles `(x, y, z)` for the given `order`. The
+    /// angle of the middle axis is in the range [-pi/2, pi/2]; in gimbal lock the angle of
+    /// the last axis is set to zero.
+    pub fn to_euler(self, order: EulerOrder) -> (Radians<T>, Radians<T>, Radians<T>) {
+        let m = self.to_mat3();
+        let (i, j, k) = order.axes();
+        let s = if order.is_even() { T::one() } else { -T::one() };
+        let sin_middle = (-s * m[k][i]).max(-T::one()).min(T::one());
+        let middle = sin_middle.asin();
+        let gimbal_lock: T = NumCast::from(1e-6).unwrap();
+        let (first, last) = if T::one() - sin_middle.abs() > gimbal_lock {
+            ((s * m[k][j]).atan2(m[k][k]), (s * m[j][i]).atan2(m[i][i]))
+        } else {
+            ((-s * m[j][k]).atan2(m[j][j]), T::zero())
+        };
+        let mut angles = [T::zero(); 3];
+        angles[i] = first;
+        angles[j] = middle;
+        angles[k] = last;
+        (Radians::new(angles[0]), Radians::new(angles[1]), Radians::new(angles[2]))
+    }
+
+    /// The rotation matrix of a normalized quaternion.
+    #[rustfmt::skip]
+    pub fn to_mat3(self) -> Mat3x3<T> {
+        let two: T = NumCast::from(2.0).unwrap();
+        let (x, y, z, w) = (self.v.x(), self.v.y(), self.v.z(), self.w);
+        Mat3x3::new(&[Vec3::new(T::one() - two * (y * y + z * z), two * (x * y - w * z),             two * (x * z + w * y)),
+                      Vec3::new(two * (x * y + w * z),             T::one() - two * (x * x + z * z), two * (y * z - w * x)),
+                      Vec3::new(two * (x * z - w * y),             two * (y * z + w * x),             T::one() - two * (x * x + y * y))])
+    }
+
+    pub fn to_mat4(self) -> Mat4x4<T> {
+        let m = self.to_mat3();
+        Mat4x4::new(&[m[0].extend(T::zero()),
+                      m[1].extend(T::zero()),
+                      m[2].extend(T::zero()),
+                      Vec3::zero().extend(T::one())])
+    }
+
+    /// Extracts the rotation of an orthonormal rotation matrix.
+    pub fn from_mat3(m: &Mat3x3<T>) -> Self {
+        let one = T::one();
+        let half: T = NumCast::from(0.5).unwrap();
+        let quarter: T = NumCast::from(0.25).unwrap();
+        let trace = m[0][0] + m[1][1] + m[2][2];
+        // Picks the largest of w, x, y and z to avoid dividing by a number close to zero.
+        if trace > T::zero() {
+            let s = (trace + one).sqrt() * NumCast::from(2.0).unwrap();
+            Self::raw(Vec3::new((m[2][1] - m[1][2]) / s,
+                                (m[0][2] - m[2][0]) / s,
+                                (m[1][0] - m[0][1]) / s),
+                      quarter * s)
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (one + m[0][0] - m[1][1] - m[2][2]).sqrt() / half;
+            Self::raw(Vec3::new(quarter * s,
+                                (m[0][1] + m[1][0]) / s,
+                                (m[0][2] + m[2][0]) / s),
+                      (m[2][1] - m[1][2]) / s)
+        } else if m[1][1] > m[2][2] {
+            let s = (one + m[1][1] - m[0][0] - m[2][2]).sqrt() / half;
+            Self::raw(Vec3::new((m[0][1] + m[1][0]) / s,
+                                quarter * s,
+                                (m[1][2] + m[2][1]) / s),
+                      (m[0][2] - m[2][0]) / s)
+        } else {
+            let s = (one + m[2][2] - m[0][0] - m[1][1]).sqrt() / half;
+            Self::raw(Vec3::new((m[0][2] + m[2][0]) / s,
+                                (m[1][2] + m[2][1]) / s,
+                                quarter * s),
+                      (m[1][0] - m[0][1]) / s)
+        }
+    }
+
+    /// Extracts the rotation of the upper 3x3 part of a matrix without scale or shear.
+    pub fn from_mat4(m: &Mat4x4<T>) -> Self {
+        Self::from_mat3(&Mat3x3::new(&[m[0].truncate(), m[1].truncate(), m[2].truncate()]))
+    }
 }
 
 impl<T> Mul for Quaternion<T>
@@ -60,6 +282,42 @@ impl<T> Mul for Quaternion<T>
     }
 }
 
+impl<T> Add for Quaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::raw(self.v + other.v, self.w + other.w)
+    }
+}
+
+impl<T> Sub for Quaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::raw(self.v - other.v, self.w - other.w)
+    }
+}
+
+impl<T> Neg for Quaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::raw(-self.v, -self.w)
+    }
+}
+
+impl<T> Mul<T> for Quaternion<T>
+    where T: Float
+{
+    type Output = Self;
+    fn mul(self, other: T) -> Self {
+        Self::raw(self.v * other, self.w * other)
+    }
+}
+
 impl<T> Div<T> for Quaternion<T>
     where T: Float
 {
@@ -73,10 +331,6 @@ impl<T> Mul<Vec3<T>> for Quaternion<T>
 {
     type Output = Vec3<T>;
     fn mul(self, other: Vec3<T>) -> Vec3<T> {
-        // use num::NumCast;
-        // let vcv = Vec3::cross(self.v, other);
-        // let two: T = NumCast::from(2.0).unwrap();
-        // other + vcv * (two * self.w) + Vec3::cross(self.v, vcv) * two
         let vq = Self::raw(other, T::zero());
         // q * v * q^(-1)
         let r = self * vq * self.inverse();
@@ -87,13 +341,125 @@ impl<T> Mul<Vec3<T>> for Quaternion<T>
 mod test {
     use vector::*;
     use quaternion::*;
-    use unit::*;
+    use unit::Degrees;
+    use std::f32::consts::PI;
+
+    fn vec_eq(a: Vec3f, b: Vec3f) -> bool {
+        a.distance(b) < 1e-5
+    }
+
+    fn rotation_eq(a: Quaternion<f32>, b: Quaternion<f32>) -> bool {
+        a.dot(b).abs() > 1.0 - 1e-5
+    }
+
+    #[test]
+    fn rotate_vector() {
+        let q = Quaternion::new(Vec3f::new(0., 1., 0.), Radians::new(PI / 2.));
+        let v = q * Vec3f::new(1., 0., 0.);
+        assert!(vec_eq(v, Vec3f::new(0., 0., -1.)));
+        assert!(vec_eq(q.inverse() * v, Vec3f::new(1., 0., 0.)));
+    }
+
+    #[test]
+    fn matrix_round_trip() {
+        let axes = [Vec3f::new(1., 0., 0.),
+                    Vec3f::new(0., 1., 0.),
+                    Vec3f::new(0., 0., 1.),
+                    Vec3f::new(1., 2., 3.),
+                    Vec3f::new(-1., 0.5, -2.)];
+        let v = Vec3f::new(0.3, -2., 1.5);
+        for &axis in axes.iter() {
+            for &angle in [0.3, 1.7, PI, 3.5, -2.9].iter() {
+                let q = Quaternion::new(axis, Radians::new(angle));
+                let m = q.to_mat3();
+                assert!(vec_eq(m.mul_v(v), q * v));
+                assert!(rotation_eq(Quaternion::from_mat3(&m), q));
+                assert!(rotation_eq(Quaternion::from_mat4(&q.to_mat4()), q));
+            }
+        }
+    }
+
+    #[test]
+    fn euler_round_trip() {
+        let orders = [EulerOrder::XYZ,
+                      EulerOrder::XZY,
+                      EulerOrder::YXZ,
+                      EulerOrder::YZX,
+                      EulerOrder::ZXY,
+                      EulerOrder::ZYX];
+        for &order in orders.iter() {
+            let q = Quaternion::from_euler(order,
+                                           Radians::new(0.4),
+                                           Radians::new(-1.1),
+                                           Radians::new(1.3));
+            let (x, y, z) = q.to_euler(order);
+            assert!((x.value - 0.4).abs() < 1e-4);
+            assert!((y.value + 1.1).abs() < 1e-4);
+            assert!((z.value - 1.3).abs() < 1e-4);
+            // Angles outside of the canonical range decompose into a different, but
+            // equivalent triple.
+            let q = Quaternion::from_euler(order,
+                                           Radians::new(2.9),
+                                           Radians::new(-2.1),
+                                           Radians::new(2.5));
+            let (x, y, z) = q.to_euler(order);
+            assert!(rotation_eq(Quaternion::from_euler(order, x, y, z), q));
+        }
+        let q = Quaternion::from_euler(EulerOrder::XYZ,
+                                       Degrees::new(90.0),
+                                       Degrees::new(0.0),
+                                       Degrees::new(90.0));
+        let expected = Quaternion::new(Vec3f::new(0., 0., 1.), Degrees::new(90.0)) *
+                       Quaternion::new(Vec3f::new(1., 0., 0.), Degrees::new(90.0));
+        assert!(rotation_eq(q, expected));
+    }
+
+    #[test]
+    fn euler_gimbal_lock() {
+        let q = Quaternion::from_euler(EulerOrder::XYZ,
+                                       Radians::new(0.3),
+                                       Radians::new(PI / 2.),
+                                       Radians::new(0.0));
+        let (x, y, z) = q.to_euler(EulerOrder::XYZ);
+        let q2 = Quaternion::from_euler(EulerOrder::XYZ, x, y, z);
+        assert!(rotation_eq(q, q2));
+    }
+
+    #[test]
+    fn axis_angle() {
+        let q = Quaternion::new(Vec3f::new(0., 2., 0.), Radians::new(1.2));
+        let (axis, angle) = q.to_axis_angle();
+        assert!(vec_eq(axis, Vec3f::new(0., 1., 0.)));
+        assert!((angle.value - 1.2).abs() < 1e-5);
+        let (axis, angle) = Quaternion::<f32>::identity().to_axis_angle();
+        assert!(axis == Vec3f::new(1., 0., 0.));
+        assert!(angle.value == 0.0);
+    }
+
+    #[test]
+    fn rotation_arc() {
+        let from = Vec3f::new(1., 0., 0.);
+        for &to in [Vec3f::new(0., 1., 0.),
+                    Vec3f::new(1., 1., 1.),
+                    Vec3f::new(-1., 0., 0.),
+                    Vec3f::new(1., 0., 0.)]
+            .iter() {
+            let q = Quaternion::from_rotation_arc(from, to);
+            assert!(vec_eq(q * from, to.normalize().unwrap()));
+        }
+    }
+
     #[test]
-    fn inverse() {
-        let q = Quaternion::new(Vec3f::new(0., 1., 0.), Radians::new(3.14 / 2.));
-        let v = Vec3f::new(1., 0., 0.);
-        let v2 = q * v;
-        println!("Inverse {:?}", v2);
-        println!("Rad {}", Degrees::new(180.).radians().value);
+    fn interpolation() {
+        let a = Quaternion::<f32>::identity();
+        let b = Quaternion::new(Vec3f::new(0., 0., 1.), Radians::new(PI / 2.));
+        let half = Quaternion::new(Vec3f::new(0., 0., 1.), Radians::new(PI / 4.));
+        assert!(rotation_eq(a.slerp(b, 0.0), a));
+        assert!(rotation_eq(a.slerp(b, 1.0), b));
+        assert!(rotation_eq(a.slerp(b, 0.5), half));
+        assert!(rotation_eq(a.nlerp(b, 0.5), half));
+        // -b is the same rotation, slerp has to take the short path.
+        assert!(rotation_eq(a.slerp(-b, 0.5), half));
+        assert!((a.slerp(b, 0.3).length() - 1.0).abs() < 1e-5);
     }
 }
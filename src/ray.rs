@@ -1,11 +1,13 @@
 #![allow(dead_code)]
 
-use vector::Vec3;
+use vector::{Vec3, Point3, Normal3};
 use num::Float;
+
+#[derive(Copy, Clone, Debug)]
 pub struct Ray<T>
     where T: Float
 {
-    pub origin: Vec3<T>,
+    pub origin: Point3<T>,
     pub direction: Vec3<T>,
     pub max_time: T
 }
@@ -13,27 +15,251 @@ pub struct Ray<T>
 impl<T> Ray<T>
     where T: Float
 {
-    pub fn new(o: Vec3<T>, d: Vec3<T>, max_time: T) -> Self {
+    pub fn new(o: Point3<T>, d: Vec3<T>, max_time: T) -> Self {
         Ray {
             origin: o,
             direction: d,
-            max_time: max_time
+            max_time
         }
     }
-    pub fn from_start_end(start: Vec3<T>, end: Vec3<T>) -> Self {
+    pub fn from_start_end(start: Point3<T>, end: Point3<T>) -> Self {
         let dir = end - start;
         let max_time = dir.length();
         let dir_normal = dir.normalize().expect("Should not be zero");
         Ray::new(start, dir_normal, max_time)
     }
-}
 
+    /// The point at the ray parameter `time`.
+    pub fn at(&self, time: T) -> Point3<T> {
+        self.origin + self.direction * time
+    }
 
+    /// `true` if `time` lies on the ray, between the origin and `max_time`.
+    pub fn contains_time(&self, time: T) -> bool {
+        time >= T::zero() && time <= self.max_time
+    }
+
+    /// Intersects the ray with the plane through `point` with the normal `normal`. Rays
+    /// parallel to the plane never hit. The normal of the hit is `normal` normalized.
+    pub fn hit_plane(&self, point: Point3<T>, normal: Normal3<T>) -> Option<RayHit<T>> {
+        let denom = normal.dot(self.direction);
+        if denom == T::zero() {
+            return None;
+        }
+        let time = normal.dot(point - self.origin) / denom;
+        if !self.contains_time(time) {
+            return None;
+        }
+        Some(RayHit::new(time, self.at(time), normal.normalize()?))
+    }
+
+    /// Möller–Trumbore ray triangle intersection. The normal of the hit follows the winding
+    /// order `a, b, c`, the barycentric coordinates are the weights of `a`, `b` and `c`.
+    pub fn hit_triangle(&self, a: Point3<T>, b: Point3<T>, c: Point3<T>) -> Option<RayHit<T>> {
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.direction.cross(e2);
+        let det = e1.dot(p);
+        if det == T::zero() {
+            return None;
+        }
+        let inv_det = T::one() / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if u < T::zero() || u > T::one() {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < T::zero() || u + v > T::one() {
+            return None;
+        }
+        let time = e2.dot(q) * inv_det;
+        if !self.contains_time(time) {
+            return None;
+        }
+        let normal = e1.cross(e2).normalize()?.to_normal();
+        let mut hit = RayHit::new(time, self.at(time), normal);
+        hit.barycentric = Some(Vec3::new(T::one() - u - v, u, v));
+        Some(hit)
+    }
+
+    /// Watertight ray triangle intersection by Woop, Benthin and Wald. Unlike `hit_triangle`
+    /// a ray that passes exactly through a shared edge or vertex of a closed mesh always hits
+    /// at least one of the adjacent triangles.
+    pub fn hit_triangle_watertight(&self,
+                                   a: Point3<T>,
+                                   b: Point3<T>,
+                                   c: Point3<T>)
+                                   -> Option<RayHit<T>> {
+        // Permutes the axes so that z is the largest component of the direction.
+        let d = self.direction;
+        let kz = if d.x().abs() > d.y().abs() && d.x().abs() > d.z().abs() {
+            0
+        } else if d.y().abs() > d.z().abs() {
+            1
+        } else {
+            2
+        };
+        let mut kx = (kz + 1) % 3;
+        let mut ky = (kx + 1) % 3;
+        if d[kz] < T::zero() {
+            ::std::mem::swap(&mut kx, &mut ky);
+        }
+        // Shear so that the ray points along +z.
+        let sx = d[kx] / d[kz];
+        let sy = d[ky] / d[kz];
+        let sz = T::one() / d[kz];
+
+        let va = a - self.origin;
+        let vb = b - self.origin;
+        let vc = c - self.origin;
+        let (ax, ay) = (va[kx] - sx * va[kz], va[ky] - sy * va[kz]);
+        let (bx, by) = (vb[kx] - sx * vb[kz], vb[ky] - sy * vb[kz]);
+        let (cx, cy) = (vc[kx] - sx * vc[kz], vc[ky] - sy * vc[kz]);
+
+        // Scaled barycentric coordinates, the edge functions of the projected triangle.
+        let u = cx * by - cy * bx;
+        let v = ax * cy - ay * cx;
+        let w = bx * ay - by * ax;
+        let zero = T::zero();
+        if (u < zero || v < zero || w < zero) && (u > zero || v > zero || w > zero) {
+            return None;
+        }
+        let det = u + v + w;
+        if det == zero {
+            return None;
+        }
+
+        let scaled_time = u * sz * va[kz] + v * sz * vb[kz] + w * sz * vc[kz];
+        let in_range = if det < zero {
+            scaled_time <= zero && scaled_time >= self.max_time * det
+        } else {
+            scaled_time >= zero && scaled_time <= self.max_time * det
+        };
+        if !in_range {
+            return None;
+        }
+
+        let inv_det = T::one() / det;
+        let time = scaled_time * inv_det;
+        let normal = (b - a).cross(c - a).normalize()?.to_normal();
+        let mut hit = RayHit::new(time, self.at(time), normal);
+        hit.barycentric = Some(Vec3::new(u * inv_det, v * inv_det, w * inv_det));
+        Some(hit)
+    }
+}
+
+/// The closest intersection of a ray with a surface.
+#[derive(Copy, Clone, Debug)]
 pub struct RayHit<T>
     where T: Float
 {
-    ray: Ray<T>,
-    time: T
+    /// The ray parameter of the hit, in `[0, max_time]`.
+    pub time: T,
+    pub point: Point3<T>,
+    /// The normalized surface normal.
+    pub normal: Normal3<T>,
+    /// The barycentric coordinates of the hit for triangles, `None` for other surfaces.
+    pub barycentric: Option<Vec3<T>>,
+}
+
+impl<T> RayHit<T>
+    where T: Float
+{
+    pub fn new(time: T, point: Point3<T>, normal: Normal3<T>) -> Self {
+        RayHit {
+            time,
+            point,
+            normal,
+            barycentric: None,
+        }
+    }
 }
 
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use ray::*;
+
+    fn triangle() -> (Point3f, Point3f, Point3f) {
+        (Point3f::new(0., 0., 0.), Point3f::new(2., 0., 0.), Point3f::new(0., 2., 0.))
+    }
+
+    #[test]
+    fn plane() {
+        let ray = Ray::new(Point3f::new(1., 1., 5.), Vec3f::new(0., 0., -1.), 10.);
+        let hit = ray.hit_plane(Point3f::new(0., 0., 1.), Normal3f::new(0., 0., 1.)).unwrap();
+        assert!(hit.time == 4.);
+        assert!(hit.point == Point3f::new(1., 1., 1.));
+        assert!(hit.barycentric.is_none());
+        let scaled = ray.hit_plane(Point3f::new(0., 0., 1.), Normal3f::new(0., 0., 3.)).unwrap();
+        assert!(scaled.time == 4.);
+        assert!(scaled.normal == Normal3f::new(0., 0., 1.));
+
+        let short = Ray::new(ray.origin, ray.direction, 3.);
+        assert!(short.hit_plane(Point3f::new(0., 0., 1.), Normal3f::new(0., 0., 1.)).is_none());
+        let parallel = Ray::new(ray.origin, Vec3f::new(1., 0., 0.), 10.);
+        assert!(parallel.hit_plane(Point3f::new(0., 0., 1.), Normal3f::new(0., 0., 1.)).is_none());
+        let away = Ray::new(ray.origin, Vec3f::new(0., 0., 1.), 10.);
+        assert!(away.hit_plane(Point3f::new(0., 0., 1.), Normal3f::new(0., 0., 1.)).is_none());
+    }
+
+    #[test]
+    fn triangle_hit() {
+        let (a, b, c) = triangle();
+        let ray = Ray::new(Point3f::new(0.5, 0.5, 3.), Vec3f::new(0., 0., -1.), 10.);
+        for hit in [ray.hit_triangle(a, b, c), ray.hit_triangle_watertight(a, b, c)].iter() {
+            let hit = hit.unwrap();
+            assert!((hit.time - 3.).abs() < 1e-6);
+            assert!(hit.point.distance(Point3f::new(0.5, 0.5, 0.)) < 1e-6);
+            assert!(hit.normal == Normal3f::new(0., 0., 1.));
+            let bary = hit.barycentric.unwrap();
+            assert!(bary.distance(Vec3f::new(0.5, 0.25, 0.25)) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn tiny_triangle() {
+        // The squared length of the normal underflows, the hit is still valid.
+        let a = Point3f::new(0., 0., 0.);
+        let (b, c) = (Point3f::new(1e-12, 0., 0.), Point3f::new(0., 1e-12, 0.));
+        assert!((b - a).cross(c - a).length_sq() == 0.);
+        let ray = Ray::new(Point3f::new(2e-13, 2e-13, 1.), Vec3f::new(0., 0., -1.), 2.);
+        for hit in [ray.hit_triangle(a, b, c), ray.hit_triangle_watertight(a, b, c)].iter() {
+            let hit = hit.unwrap();
+            assert!((hit.time - 1.).abs() < 1e-6);
+            assert!(hit.normal == Normal3f::new(0., 0., 1.));
+        }
+    }
+
+    #[test]
+    fn triangle_miss() {
+        let (a, b, c) = triangle();
+        let rays = [Ray::new(Point3f::new(1.5, 1.5, 3.), Vec3f::new(0., 0., -1.), 10.),
+                    Ray::new(Point3f::new(0.5, 0.5, 3.), Vec3f::new(0., 0., 1.), 10.),
+                    Ray::new(Point3f::new(0.5, 0.5, 3.), Vec3f::new(0., 0., -1.), 2.),
+                    Ray::new(Point3f::new(0.5, 0.5, 3.), Vec3f::new(1., 0., 0.), 10.)];
+        for ray in rays.iter() {
+            assert!(ray.hit_triangle(a, b, c).is_none());
+            assert!(ray.hit_triangle_watertight(a, b, c).is_none());
+        }
+    }
 
+    #[test]
+    fn watertight_shared_edge() {
+        // Two triangles sharing the diagonal of a quad, a ray through the diagonal has to hit
+        // at least one of them.
+        let p0 = Point3f::new(0., 0., 0.);
+        let p1 = Point3f::new(1., 0., 0.);
+        let p2 = Point3f::new(1., 1., 0.);
+        let p3 = Point3f::new(0., 1., 0.);
+        for i in 1..10 {
+            let t = i as f32 / 10.;
+            let ray = Ray::new(Point3f::new(t, t, 1.), Vec3f::new(0., 0., -1.), 2.);
+            let first = ray.hit_triangle_watertight(p0, p1, p2);
+            let second = ray.hit_triangle_watertight(p0, p2, p3);
+            assert!(first.is_some() || second.is_some());
+        }
+    }
+}
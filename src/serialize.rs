@@ -0,0 +1,359 @@
+//! `Serialize` and `Deserialize` for the math types, enabled by the `serde` feature.
+//! Vectors, points, normals and quaternions are arrays (`[x, y, z]`, `[x, y, z, w]`),
+//! matrices are arrays of rows, dual quaternions are `[real, dual]`, `Aabb` is
+//! `[min, max]`, `Ray` is `[origin, direction, max_time]`, a `Transform` is
+//! `[matrix, inverse]` and angles are plain numbers. Deserializing checks the lengths of all
+//! arrays and the invariants of the types.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::convert::TryInto;
+use num::Float;
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::SerializeTuple;
+use serde::de::{self, SeqAccess, Visitor};
+use vector::{CoreVector, Scalar, Vec3, Vec4, Point3};
+use matrix::{Matrix, Mat4x4};
+use quaternion::Quaternion;
+use dual_quaternion::DualQuaternion;
+use aabb::Aabb;
+use ray::Ray;
+use transform::Transform;
+use unit::{Radians, Degrees, Turns};
+
+fn serialize_array<S, T>(elements: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer,
+          T: Serialize
+{
+    let mut tuple = serializer.serialize_tuple(elements.len())?;
+    for element in elements {
+        tuple.serialize_element(element)?;
+    }
+    tuple.end()
+}
+
+/// Deserializes an array of exactly `N` elements.
+struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+impl<'de, T, const N: usize> Visitor<'de> for ArrayVisitor<T, N>
+    where T: Deserialize<'de>
+{
+    type Value = [T; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of {} elements", N)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<[T; N], A::Error>
+        where A: SeqAccess<'de>
+    {
+        let mut elements = Vec::with_capacity(N);
+        while elements.len() < N {
+            match seq.next_element()? {
+                Some(element) => elements.push(element),
+                None => return Err(de::Error::invalid_length(elements.len(), &self)),
+            }
+        }
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(elements.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+fn deserialize_array<'de, D, T, const N: usize>(deserializer: D) -> Result<[T; N], D::Error>
+    where D: Deserializer<'de>,
+          T: Deserialize<'de>
+{
+    deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+}
+
+impl<T, const N: usize, Type> Serialize for CoreVector<T, N, Type>
+    where T: Scalar + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(&self.data, serializer)
+    }
+}
+
+impl<'de, T, const N: usize, Type> Deserialize<'de> for CoreVector<T, N, Type>
+    where T: Scalar + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(deserializer).map(CoreVector::from)
+    }
+}
+
+impl<T, const N: usize, const M: usize> Serialize for Matrix<T, N, M>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(self.rows(), serializer)
+    }
+}
+
+/// Rejects matrices with the wrong number of rows or a row of the wrong length.
+impl<'de, T, const N: usize, const M: usize> Deserialize<'de> for Matrix<T, N, M>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array::<_, CoreVector<T, N, _>, M>(deserializer).map(Matrix::from)
+    }
+}
+
+impl<T> Serialize for Quaternion<T>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.v().extend(self.w()).serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Quaternion<T>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let v = Vec4::<T>::deserialize(deserializer)?;
+        Ok(Quaternion::raw(v.truncate(), v.w()))
+    }
+}
+
+impl<T> Serialize for DualQuaternion<T>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.real(), self.dual()).serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for DualQuaternion<T>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (real, dual) = <(Quaternion<T>, Quaternion<T>)>::deserialize(deserializer)?;
+        Ok(DualQuaternion::raw(real, dual))
+    }
+}
+
+impl<T> Serialize for Aabb<T>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.min(), self.max()).serialize(serializer)
+    }
+}
+
+/// Rejects boxes whose minimum is larger than their maximum on any axis, or that contain
+/// NaN.
+impl<'de, T> Deserialize<'de> for Aabb<T>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (min, max) = <(Point3<T>, Point3<T>)>::deserialize(deserializer)?;
+        if (0..3).any(|i| min[i].is_nan() || max[i].is_nan() || min[i] > max[i]) {
+            return Err(de::Error::custom("the minimum of the Aabb is larger than its maximum"));
+        }
+        Ok(Aabb::new(min, max))
+    }
+}
+
+impl<T> Serialize for Ray<T>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.origin, self.direction, self.max_time).serialize(serializer)
+    }
+}
+
+/// Rejects rays with a zero direction or a negative `max_time`.
+impl<'de, T> Deserialize<'de> for Ray<T>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (origin, direction, max_time) =
+            <(Point3<T>, Vec3<T>, T)>::deserialize(deserializer)?;
+        if direction.length_sq() == T::zero() {
+            return Err(de::Error::custom("the direction of the Ray is zero"));
+        }
+        if max_time.is_nan() || max_time < T::zero() {
+            return Err(de::Error::custom("the max_time of the Ray is negative"));
+        }
+        Ok(Ray::new(origin, direction, max_time))
+    }
+}
+
+/// Stores the matrix and its inverse, so that analytic inverses are not recomputed.
+impl<T> Serialize for Transform<T>
+    where T: Float + Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.matrix(), self.inverse_matrix()).serialize(serializer)
+    }
+}
+
+/// `true` if `m * inverse` is the identity up to the rounding of the products.
+fn is_inverse<T: Float>(m: &Mat4x4<T>, inverse: &Mat4x4<T>) -> bool {
+    let tolerance = T::epsilon().sqrt();
+    (0..4).all(|i| {
+        (0..4).all(|j| {
+            let (product, magnitude) = (0..4).fold((T::zero(), T::zero()), |(p, mag), k| {
+                (p + m[i][k] * inverse[k][j], mag + (m[i][k] * inverse[k][j]).abs())
+            });
+            let expected = if i == j { T::one() } else { T::zero() };
+            // Also false for NaN.
+            (product - expected).abs() <= tolerance * magnitude
+        })
+    })
+}
+
+/// Rejects singular matrices and inverses that do not belong to the matrix.
+impl<'de, T> Deserialize<'de> for Transform<T>
+    where T: Float + Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (m, inverse) = <(Mat4x4<T>, Mat4x4<T>)>::deserialize(deserializer)?;
+        if m.is_singular() || inverse.is_singular() {
+            return Err(de::Error::custom("the matrix of the Transform is singular"));
+        }
+        if !is_inverse(&m, &inverse) {
+            return Err(de::Error::custom("the inverse of the Transform does not match its matrix"));
+        }
+        Ok(Transform::from_parts(m, inverse))
+    }
+}
+
+macro_rules! impl_serde_angle{
+    ($angle: ident) => {
+        impl<T> Serialize for $angle<T>
+            where T: Float + Serialize
+        {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.value.serialize(serializer)
+            }
+        }
+
+        impl<'de, T> Deserialize<'de> for $angle<T>
+            where T: Float + Deserialize<'de>
+        {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                T::deserialize(deserializer).map($angle::new)
+            }
+        }
+    }
+}
+
+impl_serde_angle!(Radians);
+impl_serde_angle!(Degrees);
+impl_serde_angle!(Turns);
+
+#[cfg(test)]
+mod test {
+    use std::fmt::Debug;
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use serde_json;
+    use bincode;
+    use vector::*;
+    use matrix::*;
+    use quaternion::Quaternion;
+    use dual_quaternion::DualQuaternion;
+    use aabb::Aabb;
+    use ray::Ray;
+    use transform::Transform;
+    use unit::{Radians, Degrees, Turns};
+
+    fn round_trip<V>(value: &V, json: &str)
+        where V: Serialize + DeserializeOwned + PartialEq + Debug
+    {
+        assert_eq!(serde_json::to_string(value).unwrap(), json);
+        assert_eq!(&serde_json::from_str::<V>(json).unwrap(), value);
+        let bytes = bincode::serialize(value).unwrap();
+        assert_eq!(&bincode::deserialize::<V>(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn vectors() {
+        round_trip(&Vec3f::new(1., 2.5, -3.), "[1.0,2.5,-3.0]");
+        round_trip(&Point2::new(0.5f64, 1.), "[0.5,1.0]");
+        round_trip(&Normal3f::new(0., 0., 1.), "[0.0,0.0,1.0]");
+        round_trip(&Vector::<f32, 5>::from([1., 2., 3., 4., 5.]), "[1.0,2.0,3.0,4.0,5.0]");
+        round_trip(&Point2::new(-3i32, 7), "[-3,7]");
+        assert!(serde_json::from_str::<Vec3f>("[1.0,2.0]").is_err());
+        assert!(serde_json::from_str::<Vec3f>("[1.0,2.0,3.0,4.0]").is_err());
+        assert!(serde_json::from_str::<Vec3f>("{\"x\":1.0}").is_err());
+        // Bincode stores no lengths, a short buffer fails.
+        let bytes = bincode::serialize(&Vec2f::new(1., 2.)).unwrap();
+        assert!(bincode::deserialize::<Vec3f>(&bytes).is_err());
+    }
+
+    #[test]
+    fn matrices() {
+        let m = Mat3x2f::new(&[Vec3f::new(1., 2., 3.), Vec3f::new(4., 5., 6.)]);
+        round_trip(&m, "[[1.0,2.0,3.0],[4.0,5.0,6.0]]");
+        round_trip(&Mat4x4f::identity(),
+                   "[[1.0,0.0,0.0,0.0],[0.0,1.0,0.0,0.0],[0.0,0.0,1.0,0.0],[0.0,0.0,0.0,1.0]]");
+        assert!(serde_json::from_str::<Mat3x2f>("[[1.0,2.0,3.0]]").is_err());
+        assert!(serde_json::from_str::<Mat3x2f>("[[1.0,2.0],[3.0,4.0]]").is_err());
+        assert!(serde_json::from_str::<Mat2x2f>("[[1.0,2.0],[3.0,4.0],[5.0,6.0]]").is_err());
+    }
+
+    #[test]
+    fn quaternions() {
+        let q = Quaternion::new(Vec3f::new(0., 0., 1.), Radians::new(1.));
+        let json = serde_json::to_string(&q).unwrap();
+        assert_eq!(serde_json::from_str::<Quaternion<f32>>(&json).unwrap(), q);
+        round_trip(&Quaternion::<f64>::identity(), "[0.0,0.0,0.0,1.0]");
+        let dq = DualQuaternion::from_translation(Vec3::new(2.0f64, 0., -4.));
+        round_trip(&dq, "[[0.0,0.0,0.0,1.0],[1.0,0.0,-2.0,0.0]]");
+        assert!(serde_json::from_str::<DualQuaternion<f32>>("[[0.0,0.0,0.0,1.0]]").is_err());
+    }
+
+    #[test]
+    fn volumes() {
+        let aabb = Aabb::new(Point3f::new(-1., 0., 1.), Point3f::new(2., 3., 4.));
+        round_trip(&aabb, "[[-1.0,0.0,1.0],[2.0,3.0,4.0]]");
+        assert!(serde_json::from_str::<Aabb<f32>>("[[3.0,0.0,1.0],[2.0,3.0,4.0]]").is_err());
+
+        let ray = Ray::new(Point3f::new(1., 2., 3.), Vec3f::new(0., 1., 0.), 10.);
+        let json = serde_json::to_string(&ray).unwrap();
+        assert_eq!(json, "[[1.0,2.0,3.0],[0.0,1.0,0.0],10.0]");
+        let back: Ray<f32> = bincode::deserialize(&bincode::serialize(&ray).unwrap()).unwrap();
+        assert!(back.origin == ray.origin && back.direction == ray.direction);
+        assert!(back.max_time == ray.max_time);
+        assert!(serde_json::from_str::<Ray<f32>>("[[0.0,0.0,0.0],[0.0,0.0,0.0],1.0]").is_err());
+        assert!(serde_json::from_str::<Ray<f32>>("[[0.0,0.0,0.0],[1.0,0.0,0.0],-1.0]").is_err());
+    }
+
+    #[test]
+    fn transforms() {
+        let t = &Transform::translate(Vec3::new(1.0f64, 2., 3.)) *
+                &Transform::rotation_y(Radians::new(0.5));
+        let json = serde_json::to_string(&t).unwrap();
+        let back: Transform<f64> = serde_json::from_str(&json).unwrap();
+        assert!(back.matrix() == t.matrix());
+        assert_abs_diff_eq!(back, t, epsilon = 1e-12);
+        let bytes = bincode::serialize(&t).unwrap();
+        assert_abs_diff_eq!(bincode::deserialize::<Transform<f64>>(&bytes).unwrap(),
+                            t,
+                            epsilon = 1e-12);
+        let far = Transform::translate(Vec3f::new(60., -250., 1e3)) *
+                  Transform::rotation_x(Radians::new(0.3f32));
+        let json = serde_json::to_string(&far).unwrap();
+        assert!(serde_json::from_str::<Transform<f32>>(&json).unwrap() == far);
+        let singular = serde_json::to_string(&(Mat4x4f::zero(), Mat4x4f::identity())).unwrap();
+        assert!(serde_json::from_str::<Transform<f32>>(&singular).is_err());
+        // A valid matrix paired with the inverse of another one.
+        let tampered = (*far.matrix(), *Transform::scale(Vec3f::new(2., 2., 2.)).inverse_matrix());
+        let tampered = serde_json::to_string(&tampered).unwrap();
+        assert!(serde_json::from_str::<Transform<f32>>(&tampered).is_err());
+    }
+
+    #[test]
+    fn angles() {
+        assert_eq!(serde_json::to_string(&Radians::new(1.5f32)).unwrap(), "1.5");
+        assert!(serde_json::from_str::<Degrees<f64>>("90.0").unwrap().value == 90.);
+        let bytes = bincode::serialize(&Degrees::new(45.0f32)).unwrap();
+        assert!(bincode::deserialize::<Degrees<f32>>(&bytes).unwrap().value == 45.);
+        assert!(serde_json::from_str::<Turns<f64>>("0.25").unwrap().value == 0.25);
+    }
+}
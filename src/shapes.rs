@@ -1,31 +1,124 @@
-
-use num::Float;
+use num::{Float, NumCast};
 use transform::*;
 use std::rc::Rc;
+use aabb::{Aabb, Bounded};
+use ray::{Ray, RayHit};
+use vector::{Vec2, Vec3, Point3, Normal3};
 
+/// Solves `a * t^2 + b * t + c = 0`, returns the real roots in ascending order.
 fn quadratic<T: Float>(a: T, b: T, c: T) -> Option<(T, T)> {
-    use num::NumCast;
     let four: T = NumCast::from(4).unwrap();
     let two: T = NumCast::from(2).unwrap();
     let d: T = b.powi(2) - four * a * c;
-    let two_a = two * a;
-    if d <= T::zero() || two_a <= T::zero() {
+    if d < T::zero() || a == T::zero() {
         return None;
     }
+    // Avoids the cancellation of `-b + sqrt(d)` for the root closer to zero.
+    let q = if b < T::zero() {
+        (-b + d.sqrt()) / two
+    } else {
+        (-b - d.sqrt()) / two
+    };
+    if q == T::zero() {
+        return Some((T::zero(), T::zero()));
+    }
+    let (t0, t1) = (q / a, c / q);
+    if t0 < t1 { Some((t0, t1)) } else { Some((t1, t0)) }
+}
 
-    let d1 = d / two_a;
-    let b1 = -b / two_a;
-
-    Some((b1 - d1, b1 + d1))
+/// The closest root of a quadratic that lies on the ray.
+fn first_root<T: Float>(ray: &Ray<T>, (t0, t1): (T, T)) -> Option<T> {
+    if ray.contains_time(t0) {
+        Some(t0)
+    } else if ray.contains_time(t1) {
+        Some(t1)
+    } else {
+        None
+    }
+}
 
+fn pi<T: Float>() -> T {
+    NumCast::from(::std::f64::consts::PI).unwrap()
 }
-use ray::Ray;
-trait Shape<T>
+
+/// A surface in world space, `bounds` returns its world space bounds.
+pub trait Shape<T>: Bounded<T>
     where T: Float
 {
-    fn intersect_p(&self, ray: Ray<T>) -> Option<T>;
+    /// The closest hit of the ray with the surface, in world space.
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>>;
+
+    /// `true` if the ray hits the surface. Cheaper than `intersect` for occlusion tests.
+    fn intersect_p(&self, ray: &Ray<T>) -> bool {
+        self.intersect(ray).is_some()
+    }
+
+    /// The world space surface area.
+    fn area(&self) -> T;
+
+    /// Maps a uniform sample `u` in `[0, 1)^2` to a point and its normal on the surface,
+    /// uniformly distributed with respect to the world space surface area.
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>);
+}
+
+/// Transforms a hit from object space into world space. The ray parameter stays the same
+/// because `transform_ray` does not renormalize the direction.
+fn hit_to_world<T: Float>(object_to_world: &Transform<T>, hit: RayHit<T>) -> RayHit<T> {
+    let normal = object_to_world.transform_normal(hit.normal)
+        .normalize()
+        .expect("Non zero normal");
+    RayHit {
+        time: hit.time,
+        point: object_to_world.transform_point(hit.point),
+        normal,
+        barycentric: hit.barycentric,
+    }
+}
+
+fn sample_to_world<T: Float>(object_to_world: &Transform<T>,
+                             (point, normal): (Point3<T>, Normal3<T>))
+                             -> (Point3<T>, Normal3<T>) {
+    let normal = object_to_world.transform_normal(normal)
+        .normalize()
+        .expect("Non zero normal");
+    (object_to_world.transform_point(point), normal)
+}
+
+/// The unit vector along the object space axis `index`.
+fn axis<T: Float>(index: usize) -> Vec3<T> {
+    let mut axis = Vec3::zero();
+    axis[index] = T::one();
+    axis
+}
+
+/// The factor by which a transform scales areas in the plane of the object space axes `a`
+/// and `b`. Exact for any transform because the plane is flat.
+fn area_scale<T: Float>(object_to_world: &Transform<T>, a: usize, b: usize) -> T {
+    let ta = object_to_world.transform_vector(axis(a));
+    let tb = object_to_world.transform_vector(axis(b));
+    ta.cross(tb).length()
+}
+
+/// `true` if the vectors are orthogonal up to rounding.
+fn orthogonal<T: Float>(a: Vec3<T>, b: Vec3<T>) -> bool {
+    a.dot(b).abs() <= T::epsilon().sqrt() * a.length() * b.length()
+}
+
+/// The scale of the object space axes `a` and `b` if the transform maps them to orthogonal
+/// vectors of the same length, so that circles in their plane stay circles.
+fn uniform_scale<T: Float>(object_to_world: &Transform<T>, a: usize, b: usize) -> Option<T> {
+    let ta = object_to_world.transform_vector(axis(a));
+    let tb = object_to_world.transform_vector(axis(b));
+    let (la, lb) = (ta.length(), tb.length());
+    if orthogonal(ta, tb) && (la - lb).abs() <= T::epsilon().sqrt() * la.max(lb) {
+        Some(la)
+    } else {
+        None
+    }
 }
-struct Sphere<T>
+
+/// A sphere centered at the origin of object space.
+pub struct Sphere<T>
     where T: Float
 {
     object_to_world: Rc<Transform<T>>,
@@ -33,24 +126,585 @@ struct Sphere<T>
     radius: T,
 }
 
-impl<T> Shape<T> for Sphere<T>
+impl<T> Sphere<T>
     where T: Float
 {
-    fn intersect_p(&self, ray: Ray<T>) -> Option<T> {
-        use num::NumCast;
-        let local_ray = self.world_to_object.mul_ray(ray);
+    /// Panics if `object_to_world` does not scale uniformly, the surface area of an
+    /// ellipsoid has no closed form.
+    pub fn new(object_to_world: Rc<Transform<T>>, radius: T) -> Self {
+        assert!(Self::scale(&object_to_world).is_some(),
+                "Spheres only support uniformly scaling transforms");
+        let world_to_object = Rc::new(object_to_world.inverse());
+        Sphere {
+            object_to_world,
+            world_to_object,
+            radius,
+        }
+    }
+
+    /// The ray parameter of the closest hit, in object space.
+    fn hit_time(&self, local_ray: &Ray<T>) -> Option<T> {
+        let two: T = NumCast::from(2).unwrap();
         let a = local_ray.direction.length_sq();
-        let b: T = (local_ray.direction * local_ray.origin)
-            .into_iter()
-            .fold(T::zero(), |acc, val| acc + val) * NumCast::from(2).unwrap();
-        let c = local_ray.origin.length_sq() - self.radius;
+        let b = local_ray.direction.dot(local_ray.origin) * two;
+        let c = local_ray.origin.length_sq() - self.radius * self.radius;
+        quadratic(a, b, c).and_then(|roots| first_root(local_ray, roots))
+    }
+
+    /// The scale of the transform if it scales all axes uniformly.
+    fn scale(object_to_world: &Transform<T>) -> Option<T> {
+        uniform_scale(object_to_world, 0, 1)?;
+        uniform_scale(object_to_world, 1, 2)?;
+        uniform_scale(object_to_world, 0, 2)
+    }
+}
+
+impl<T> Bounded<T> for Sphere<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        let r = self.radius;
+        let object_bounds = Aabb::new(Point3::new(-r, -r, -r), Point3::new(r, r, r));
+        self.object_to_world.transform_aabb(object_bounds)
+    }
+}
+
+impl<T> Shape<T> for Sphere<T>
+    where T: Float
+{
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        let time = self.hit_time(&local_ray)?;
+        let point = local_ray.at(time);
+        let normal = (point.to_vector() / self.radius).to_normal();
+        Some(hit_to_world(&self.object_to_world, RayHit::new(time, point, normal)))
+    }
 
-        if let Some((t0, t1)) = quadratic(a, b, c) {
-            return match t0.max(t1) {
-                i if i < local_ray.max_time => Some(i),
-                _ => None,
-            };
+    fn intersect_p(&self, ray: &Ray<T>) -> bool {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        self.hit_time(&local_ray).is_some()
+    }
+
+    fn area(&self) -> T {
+        let four: T = NumCast::from(4).unwrap();
+        let radius = self.radius * Self::scale(&self.object_to_world).unwrap();
+        four * pi::<T>() * radius * radius
+    }
+
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>) {
+        let two: T = NumCast::from(2).unwrap();
+        let z = T::one() - two * u.x();
+        let r = (T::one() - z * z).max(T::zero()).sqrt();
+        let phi = two * pi::<T>() * u.y();
+        let normal = Normal3::new(r * phi.cos(), r * phi.sin(), z);
+        let point = (normal.to_vector() * self.radius).to_point();
+        sample_to_world(&self.object_to_world, (point, normal))
+    }
+}
+
+/// A disk in the `z = 0` plane of object space, centered at the origin and facing `+z`.
+pub struct Disk<T>
+    where T: Float
+{
+    object_to_world: Rc<Transform<T>>,
+    world_to_object: Rc<Transform<T>>,
+    radius: T,
+}
+
+impl<T> Disk<T>
+    where T: Float
+{
+    pub fn new(object_to_world: Rc<Transform<T>>, radius: T) -> Self {
+        let world_to_object = Rc::new(object_to_world.inverse());
+        Disk {
+            object_to_world,
+            world_to_object,
+            radius,
         }
-        None
+    }
+
+    fn local_hit(&self, local_ray: &Ray<T>) -> Option<RayHit<T>> {
+        let normal = Normal3::new(T::zero(), T::zero(), T::one());
+        local_ray.hit_plane(Point3::zero(), normal)
+            .and_then(|hit| {
+                let p = hit.point;
+                if p.x() * p.x() + p.y() * p.y() <= self.radius * self.radius {
+                    Some(hit)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+impl<T> Bounded<T> for Disk<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        let r = self.radius;
+        let object_bounds = Aabb::new(Point3::new(-r, -r, T::zero()), Point3::new(r, r, T::zero()));
+        self.object_to_world.transform_aabb(object_bounds)
+    }
+}
+
+impl<T> Shape<T> for Disk<T>
+    where T: Float
+{
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        self.local_hit(&local_ray).map(|hit| hit_to_world(&self.object_to_world, hit))
+    }
+
+    fn area(&self) -> T {
+        pi::<T>() * self.radius * self.radius * area_scale(&self.object_to_world, 0, 1)
+    }
+
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>) {
+        let two: T = NumCast::from(2).unwrap();
+        let r = self.radius * u.x().sqrt();
+        let phi = two * pi::<T>() * u.y();
+        let point = Point3::new(r * phi.cos(), r * phi.sin(), T::zero());
+        let normal = Normal3::new(T::zero(), T::zero(), T::one());
+        sample_to_world(&self.object_to_world, (point, normal))
+    }
+}
+
+/// An open cylinder around the z axis of object space, between `z_min` and `z_max`.
+pub struct Cylinder<T>
+    where T: Float
+{
+    object_to_world: Rc<Transform<T>>,
+    world_to_object: Rc<Transform<T>>,
+    radius: T,
+    z_min: T,
+    z_max: T,
+}
+
+impl<T> Cylinder<T>
+    where T: Float
+{
+    /// Panics if `object_to_world` does not keep the cross section circular and the axis
+    /// perpendicular to it, the surface area of other cylinders has no closed form.
+    pub fn new(object_to_world: Rc<Transform<T>>, radius: T, z_min: T, z_max: T) -> Self {
+        assert!(Self::scale(&object_to_world).is_some(),
+                "Cylinders only support transforms that keep the cross section circular");
+        let world_to_object = Rc::new(object_to_world.inverse());
+        Cylinder {
+            object_to_world,
+            world_to_object,
+            radius,
+            z_min: z_min.min(z_max),
+            z_max: z_min.max(z_max),
+        }
+    }
+
+    fn hit_time(&self, local_ray: &Ray<T>) -> Option<T> {
+        let two: T = NumCast::from(2).unwrap();
+        let (o, d) = (local_ray.origin, local_ray.direction);
+        let a = d.x() * d.x() + d.y() * d.y();
+        let b = (d.x() * o.x() + d.y() * o.y()) * two;
+        let c = o.x() * o.x() + o.y() * o.y() - self.radius * self.radius;
+        let (t0, t1) = quadratic(a, b, c)?;
+        // A root is only a hit if it lies between the two end planes.
+        let in_height = |t: T| {
+            let z = local_ray.at(t).z();
+            local_ray.contains_time(t) && z >= self.z_min && z <= self.z_max
+        };
+        if in_height(t0) {
+            Some(t0)
+        } else if in_height(t1) {
+            Some(t1)
+        } else {
+            None
+        }
+    }
+
+    /// The scale of the cross section and of the axis.
+    fn scale(object_to_world: &Transform<T>) -> Option<(T, T)> {
+        let s = uniform_scale(object_to_world, 0, 1)?;
+        let z = object_to_world.transform_vector(axis(2));
+        let perpendicular = (0..2)
+            .all(|i| orthogonal(z, object_to_world.transform_vector(axis(i))));
+        if perpendicular { Some((s, z.length())) } else { None }
+    }
+}
+
+impl<T> Bounded<T> for Cylinder<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        let r = self.radius;
+        let object_bounds = Aabb::new(Point3::new(-r, -r, self.z_min),
+                                      Point3::new(r, r, self.z_max));
+        self.object_to_world.transform_aabb(object_bounds)
+    }
+}
+
+impl<T> Shape<T> for Cylinder<T>
+    where T: Float
+{
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        let time = self.hit_time(&local_ray)?;
+        let point = local_ray.at(time);
+        let normal = Normal3::new(point.x() / self.radius, point.y() / self.radius, T::zero());
+        Some(hit_to_world(&self.object_to_world, RayHit::new(time, point, normal)))
+    }
+
+    fn intersect_p(&self, ray: &Ray<T>) -> bool {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        self.hit_time(&local_ray).is_some()
+    }
+
+    fn area(&self) -> T {
+        let two: T = NumCast::from(2).unwrap();
+        let (s, z) = Self::scale(&self.object_to_world).unwrap();
+        two * pi::<T>() * self.radius * s * (self.z_max - self.z_min) * z
+    }
+
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>) {
+        let two: T = NumCast::from(2).unwrap();
+        let z = self.z_min + (self.z_max - self.z_min) * u.x();
+        let phi = two * pi::<T>() * u.y();
+        let point = Point3::new(self.radius * phi.cos(), self.radius * phi.sin(), z);
+        let normal = Normal3::new(phi.cos(), phi.sin(), T::zero());
+        sample_to_world(&self.object_to_world, (point, normal))
+    }
+}
+
+/// A triangle. The vertices are transformed into world space once on construction.
+pub struct Triangle<T>
+    where T: Float
+{
+    vertices: [Point3<T>; 3],
+}
+
+impl<T> Triangle<T>
+    where T: Float
+{
+    pub fn new(object_to_world: &Transform<T>,
+               a: Point3<T>,
+               b: Point3<T>,
+               c: Point3<T>)
+               -> Self {
+        Triangle {
+            vertices: [object_to_world.transform_point(a),
+                       object_to_world.transform_point(b),
+                       object_to_world.transform_point(c)],
+        }
+    }
+
+    /// The world space vertices.
+    pub fn vertices(&self) -> &[Point3<T>; 3] {
+        &self.vertices
+    }
+
+    /// Panics for degenerate triangles without area, they have no normal.
+    fn normal(&self) -> Normal3<T> {
+        let [a, b, c] = self.vertices;
+        (b - a).cross(c - a).normalize().expect("Degenerate triangle").to_normal()
+    }
+}
+
+impl<T> Bounded<T> for Triangle<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        let [a, b, c] = self.vertices;
+        Aabb::single(a).union_point(b).union_point(c)
+    }
+}
+
+impl<T> Shape<T> for Triangle<T>
+    where T: Float
+{
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let [a, b, c] = self.vertices;
+        ray.hit_triangle_watertight(a, b, c)
+    }
+
+    fn area(&self) -> T {
+        let [a, b, c] = self.vertices;
+        let half: T = NumCast::from(0.5).unwrap();
+        (b - a).cross(c - a).length() * half
+    }
+
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>) {
+        let [a, b, c] = self.vertices;
+        let su = u.x().sqrt();
+        let (b0, b1) = (T::one() - su, u.y() * su);
+        let point = a + (b - a) * b1 + (c - a) * (T::one() - b0 - b1);
+        (point, self.normal())
+    }
+}
+
+/// A box, axis aligned in object space and rotated or scaled by its transform. Named
+/// `Cuboid` to not shadow `std::boxed::Box`.
+pub struct Cuboid<T>
+    where T: Float
+{
+    object_to_world: Rc<Transform<T>>,
+    world_to_object: Rc<Transform<T>>,
+    extent: Aabb<T>,
+}
+
+impl<T> Cuboid<T>
+    where T: Float
+{
+    pub fn new(object_to_world: Rc<Transform<T>>, extent: Aabb<T>) -> Self {
+        let world_to_object = Rc::new(object_to_world.inverse());
+        Cuboid {
+            object_to_world,
+            world_to_object,
+            extent,
+        }
+    }
+}
+
+impl<T> Cuboid<T>
+    where T: Float
+{
+    /// The world space area of one face perpendicular to the x, y and z axis.
+    fn face_areas(&self) -> [T; 3] {
+        let d = self.extent.diagonale();
+        let t = &self.object_to_world;
+        [d.y() * d.z() * area_scale(t, 1, 2),
+         d.x() * d.z() * area_scale(t, 0, 2),
+         d.x() * d.y() * area_scale(t, 0, 1)]
+    }
+}
+
+impl<T> Bounded<T> for Cuboid<T>
+    where T: Float
+{
+    fn bounds(&self) -> Aabb<T> {
+        self.object_to_world.transform_aabb(self.extent)
+    }
+}
+
+impl<T> Shape<T> for Cuboid<T>
+    where T: Float
+{
+    fn intersect(&self, ray: &Ray<T>) -> Option<RayHit<T>> {
+        let local_ray = self.world_to_object.transform_ray(*ray);
+        self.extent.ray_hit(&local_ray).map(|hit| hit_to_world(&self.object_to_world, hit))
+    }
+
+    fn area(&self) -> T {
+        let two: T = NumCast::from(2).unwrap();
+        self.face_areas().iter().fold(T::zero(), |acc, &area| acc + area * two)
+    }
+
+    fn sample(&self, u: Vec2<T>) -> (Point3<T>, Normal3<T>) {
+        let d = self.extent.diagonale();
+        let face_areas = self.face_areas();
+        let total = face_areas[0] + face_areas[1] + face_areas[2];
+        // Picks one of the three face pairs proportional to its area and reuses the remaining
+        // part of `u.x` to choose the side and the first coordinate on the face.
+        let mut remaining = u.x() * total;
+        let mut axis = 2;
+        for (index, &area) in face_areas.iter().enumerate() {
+            if remaining < area || index == 2 {
+                axis = index;
+                break;
+            }
+            remaining = remaining - area;
+        }
+        let s = if face_areas[axis] > T::zero() {
+            (remaining / face_areas[axis]).min(T::one())
+        } else {
+            T::zero()
+        };
+        let half: T = NumCast::from(0.5).unwrap();
+        let (positive, s) = if s < half {
+            (false, s / half)
+        } else {
+            (true, (s - half) / half)
+        };
+        let (a1, a2) = ((axis + 1) % 3, (axis + 2) % 3);
+        let (min, max) = (self.extent.min(), self.extent.max());
+        let mut point = min;
+        point[axis] = if positive { max[axis] } else { min[axis] };
+        point[a1] = min[a1] + d[a1] * s;
+        point[a2] = min[a2] + d[a2] * u.y();
+        let mut normal = Normal3::zero();
+        normal[axis] = if positive { T::one() } else { -T::one() };
+        sample_to_world(&self.object_to_world, (point, normal))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shapes::*;
+    use vector::*;
+    use unit::Radians;
+    use std::f32::consts::PI;
+
+    fn samples() -> Vec<Vec2f> {
+        let mut samples = Vec::new();
+        for i in 0..8 {
+            for j in 0..8 {
+                samples.push(Vec2f::new(i as f32 / 8., j as f32 / 8.));
+            }
+        }
+        samples
+    }
+
+    fn translated(x: f32, y: f32, z: f32) -> Rc<Transform<f32>> {
+        Rc::new(Transform::translate(Vec3f::new(x, y, z)))
+    }
+
+    #[test]
+    fn sphere() {
+        let sphere = Sphere::new(translated(0., 0., 5.), 2.);
+        let ray = Ray::new(Point3f::new(0., 0., 0.), Vec3f::new(0., 0., 1.), 10.);
+        let hit = sphere.intersect(&ray).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-5);
+        assert!(hit.point.distance(Point3f::new(0., 0., 3.)) < 1e-5);
+        assert!(hit.normal.distance(Normal3f::new(0., 0., -1.)) < 1e-5);
+        assert!(sphere.intersect_p(&ray));
+        // Compares against the squared radius, the ray passes the center at a distance of 1.5.
+        let close = Ray::new(Point3f::new(1.5, 0., 0.), Vec3f::new(0., 0., 1.), 10.);
+        assert!(sphere.intersect_p(&close));
+        let short = Ray::new(ray.origin, ray.direction, 2.);
+        assert!(!sphere.intersect_p(&short));
+        assert!((sphere.area() - 16. * PI).abs() < 1e-4);
+        assert!(sphere.bounds() == Aabb::new(Point3f::new(-2., -2., 3.), Point3f::new(2., 2., 7.)));
+        for u in samples() {
+            let (p, n) = sphere.sample(u);
+            assert!((p.distance(Point3f::new(0., 0., 5.)) - 2.).abs() < 1e-5);
+            assert!(n.distance(((p - Point3f::new(0., 0., 5.)) / 2.).to_normal()) < 1e-5);
+        }
+    }
+
+    #[test]
+    fn scaled_sphere() {
+        let t = Rc::new(&Transform::translate(Vec3f::new(0., 0., 5.)) *
+                        &Transform::scale(Vec3f::new(2., 2., 2.)));
+        let sphere = Sphere::new(t, 1.);
+        let ray = Ray::new(Point3f::new(0., 0., 0.), Vec3f::new(0., 0., 1.), 10.);
+        let hit = sphere.intersect(&ray).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-5);
+        assert!((sphere.area() - 16. * PI).abs() < 1e-4);
+    }
+
+    #[test]
+    fn disk() {
+        let disk = Disk::new(translated(0., 0., 1.), 1.);
+        let ray = Ray::new(Point3f::new(0.5, 0.5, 3.), Vec3f::new(0., 0., -1.), 10.);
+        let hit = disk.intersect(&ray).unwrap();
+        assert!((hit.time - 2.).abs() < 1e-5);
+        assert!(hit.normal == Normal3f::new(0., 0., 1.));
+        let outside = Ray::new(Point3f::new(0.8, 0.8, 3.), Vec3f::new(0., 0., -1.), 10.);
+        assert!(!disk.intersect_p(&outside));
+        assert!((disk.area() - PI).abs() < 1e-5);
+        for u in samples() {
+            let (p, _) = disk.sample(u);
+            assert!(p.z() == 1.);
+            assert!(p.x() * p.x() + p.y() * p.y() <= 1. + 1e-5);
+        }
+    }
+
+    #[test]
+    fn cylinder() {
+        let cylinder = Cylinder::new(translated(0., 0., 0.), 1., -1., 1.);
+        let ray = Ray::new(Point3f::new(-5., 0., 0.5), Vec3f::new(1., 0., 0.), 10.);
+        let hit = cylinder.intersect(&ray).unwrap();
+        assert!((hit.time - 4.).abs() < 1e-5);
+        assert!(hit.normal.distance(Normal3f::new(-1., 0., 0.)) < 1e-5);
+        // Open ends: a ray along the axis never hits, a ray above the top misses.
+        let axis = Ray::new(Point3f::new(0., 0., -5.), Vec3f::new(0., 0., 1.), 10.);
+        assert!(!cylinder.intersect_p(&axis));
+        let above = Ray::new(Point3f::new(-5., 0., 1.5), Vec3f::new(1., 0., 0.), 10.);
+        assert!(!cylinder.intersect_p(&above));
+        // Enters through the open top and hits the inside wall.
+        let inside = Ray::new(Point3f::new(0., 0., 2.), Vec3f::new(1., 0., -1.), 10.);
+        assert!((cylinder.intersect(&inside).unwrap().time - 1.).abs() < 1e-5);
+        assert!((cylinder.area() - 4. * PI).abs() < 1e-4);
+        for u in samples() {
+            let (p, _) = cylinder.sample(u);
+            assert!((p.x() * p.x() + p.y() * p.y() - 1.).abs() < 1e-5);
+            assert!(p.z() >= -1. && p.z() <= 1.);
+        }
+    }
+
+    #[test]
+    fn triangle() {
+        let t = Transform::translate(Vec3f::new(0., 0., 2.));
+        let triangle = Triangle::new(&t,
+                                     Point3f::new(0., 0., 0.),
+                                     Point3f::new(2., 0., 0.),
+                                     Point3f::new(0., 2., 0.));
+        let ray = Ray::new(Point3f::new(0.5, 0.5, 5.), Vec3f::new(0., 0., -1.), 10.);
+        let hit = triangle.intersect(&ray).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-5);
+        assert!(hit.barycentric.is_some());
+        assert!(triangle.area() == 2.);
+        assert!(triangle.bounds() == Aabb::new(Point3f::new(0., 0., 2.), Point3f::new(2., 2., 2.)));
+        for u in samples() {
+            let (p, n) = triangle.sample(u);
+            assert!(p.z() == 2.);
+            assert!(p.x() >= 0. && p.y() >= 0. && p.x() + p.y() <= 2. + 1e-5);
+            assert!(n == Normal3f::new(0., 0., 1.));
+        }
+    }
+
+    #[test]
+    fn cuboid() {
+        let extent = Aabb::new(Point3f::new(-1., -2., -3.), Point3f::new(1., 2., 3.));
+        let cuboid = Cuboid::new(Rc::new(Transform::rotation_z(Radians::new(PI / 2.))), extent);
+        // Rotated by 90 degrees around z, the long side now points along x.
+        let ray = Ray::new(Point3f::new(-5., 0., 0.), Vec3f::new(1., 0., 0.), 10.);
+        let hit = cuboid.intersect(&ray).unwrap();
+        assert!((hit.time - 3.).abs() < 1e-5);
+        assert!(hit.normal.distance(Normal3f::new(-1., 0., 0.)) < 1e-5);
+        assert!((cuboid.area() - extent.surface_area()).abs() < 1e-4);
+        let bounds = cuboid.bounds();
+        assert!(bounds.min().distance(Point3f::new(-2., -1., -3.)) < 1e-5);
+        for u in samples() {
+            let (p, n) = cuboid.sample(u);
+            assert!(bounds.expand(1e-5).inside(p));
+            // The point lies on the face the normal points out of.
+            let q = p + n.to_vector() * 0.1;
+            assert!(!bounds.inside(q));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn stretched_sphere() {
+        Sphere::new(Rc::new(Transform::scale(Vec3f::new(1., 1., 2.))), 1.);
+    }
+
+    #[test]
+    fn scaled_areas() {
+        let scale = |x, y, z| Rc::new(Transform::scale(Vec3f::new(x, y, z)));
+        let disk = Disk::new(scale(2., 3., 5.), 1.);
+        assert!((disk.area() - 6. * PI).abs() < 1e-4);
+        // The height scales the wall, the cross section stays a circle.
+        let cylinder = Cylinder::new(scale(2., 2., 3.), 1., 0., 1.);
+        assert!((cylinder.area() - 12. * PI).abs() < 1e-4);
+        let extent = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(1., 1., 1.));
+        let cuboid = Cuboid::new(scale(1., 2., 3.), extent);
+        assert!((cuboid.area() - 2. * (6. + 3. + 2.)).abs() < 1e-4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn elliptic_cylinder() {
+        Cylinder::new(Rc::new(Transform::scale(Vec3f::new(1., 2., 1.))), 1., 0., 1.);
+    }
+
+    #[test]
+    fn cuboid_world_sampling() {
+        // Stretched along z the top and bottom only cover 1 / 21 of the surface.
+        let extent = Aabb::new(Point3f::new(0., 0., 0.), Point3f::new(1., 1., 1.));
+        let cuboid = Cuboid::new(Rc::new(Transform::scale(Vec3f::new(1., 1., 10.))), extent);
+        let n = 2100;
+        let caps = (0..n)
+            .map(|i| cuboid.sample(Vec2f::new((i as f32 + 0.5) / n as f32, 0.5)).1)
+            .filter(|normal| normal.z().abs() > 0.5)
+            .count();
+        assert!((caps as i32 - n / 21).abs() <= 1);
     }
 }
@@ -0,0 +1,488 @@
+//! SSE implementations of the `f32` hot paths of `Vec4f`, `Vec3f` and `Mat4x4f`.
+//!
+//! Without specialization the generic operators can not pick these up, so they are exposed
+//! as `*_simd` methods next to the generic ones, which stay the reference implementation.
+//! `Vec3f` is padded with a zero `w` while it is loaded into a register. SSE2 is part of every
+//! x86_64 target, other targets fall back to the generic code.
+//!
+//! `cargo run --release --example simd` compares both paths. On an x86_64 machine the SIMD
+//! versions took about 0.8x the time for `mul_simd`, 0.65x for `mul_v_simd`, 0.55x for
+//! `try_inverse_simd` and 0.7x for `Vec4f::dot_simd`, while the `Vec3f` methods are about
+//! as fast as the generic ones apart from `cross_simd`.
+
+use vector::{Vector, Vec4f};
+use matrix::Matrix;
+use self::backend::{load, load3, store, store3};
+
+#[cfg(target_arch = "x86_64")]
+mod backend {
+    //! SSE and SSE2 are always enabled on x86_64, so the intrinsics are safe to use
+    //! without runtime detection.
+    use std::arch::x86_64::*;
+    use std::ptr;
+
+    #[inline]
+    pub fn load(a: [f32; 4]) -> __m128 {
+        unsafe { _mm_loadu_ps(a.as_ptr()) }
+    }
+
+    /// Pads `a` with a zero `w` while loading, without copying it into a temporary array.
+    #[inline]
+    pub fn load3(a: [f32; 3]) -> __m128 {
+        unsafe {
+            // The array is only aligned for `f32`, `x` and `y` are read as one unaligned `i64`.
+            let xy = ptr::read_unaligned(a.as_ptr() as *const i64);
+            _mm_movelh_ps(_mm_castsi128_ps(_mm_cvtsi64_si128(xy)), _mm_load_ss(&a[2]))
+        }
+    }
+
+    #[inline]
+    pub fn store(a: __m128) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        unsafe { _mm_storeu_ps(out.as_mut_ptr(), a) };
+        out
+    }
+
+    #[inline]
+    pub fn store3(a: __m128) -> [f32; 3] {
+        let mut out = [0.0; 3];
+        unsafe {
+            let xy = _mm_cvtsi128_si64(_mm_castps_si128(a));
+            ptr::write_unaligned(out.as_mut_ptr() as *mut i64, xy);
+            _mm_store_ss(&mut out[2], _mm_movehl_ps(a, a));
+        }
+        out
+    }
+
+    /// The horizontal sum of `a * b` in the lowest lane.
+    #[inline]
+    fn dot_lane(a: __m128, b: __m128) -> __m128 {
+        unsafe {
+            let products = _mm_mul_ps(a, b);
+            let pairs = _mm_add_ps(products, _mm_shuffle_ps(products, products, 0b01_00_11_10));
+            _mm_add_ss(pairs, _mm_shuffle_ps(pairs, pairs, 0b00_00_00_01))
+        }
+    }
+
+    #[inline]
+    pub fn add(a: __m128, b: __m128) -> __m128 {
+        unsafe { _mm_add_ps(a, b) }
+    }
+
+    #[inline]
+    pub fn sub(a: __m128, b: __m128) -> __m128 {
+        unsafe { _mm_sub_ps(a, b) }
+    }
+
+    #[inline]
+    pub fn mul(a: __m128, b: __m128) -> __m128 {
+        unsafe { _mm_mul_ps(a, b) }
+    }
+
+    #[inline]
+    pub fn scale(a: __m128, scalar: f32) -> __m128 {
+        unsafe { _mm_mul_ps(a, _mm_set1_ps(scalar)) }
+    }
+
+    #[inline]
+    pub fn div(a: __m128, scalar: f32) -> __m128 {
+        unsafe { _mm_div_ps(a, _mm_set1_ps(scalar)) }
+    }
+
+    #[inline]
+    pub fn dot(a: __m128, b: __m128) -> f32 {
+        unsafe { _mm_cvtss_f32(dot_lane(a, b)) }
+    }
+
+    /// The cross product of the `xyz` lanes, `w` stays zero for zero padded inputs.
+    #[inline]
+    pub fn cross(a: __m128, b: __m128) -> __m128 {
+        unsafe {
+            let a_yzx = _mm_shuffle_ps(a, a, 0b11_00_10_01);
+            let a_zxy = _mm_shuffle_ps(a, a, 0b11_01_00_10);
+            let b_yzx = _mm_shuffle_ps(b, b, 0b11_00_10_01);
+            let b_zxy = _mm_shuffle_ps(b, b, 0b11_01_00_10);
+            _mm_sub_ps(_mm_mul_ps(a_yzx, b_zxy), _mm_mul_ps(a_zxy, b_yzx))
+        }
+    }
+
+    /// Every row of the product is a sum of the rows of `b` scaled by a row of `a`.
+    #[inline]
+    pub fn mul_m(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+        unsafe {
+            let b = [load(b[0]), load(b[1]), load(b[2]), load(b[3])];
+            let row = |r: [f32; 4]| {
+                let mut acc = _mm_mul_ps(_mm_set1_ps(r[0]), b[0]);
+                acc = _mm_add_ps(acc, _mm_mul_ps(_mm_set1_ps(r[1]), b[1]));
+                acc = _mm_add_ps(acc, _mm_mul_ps(_mm_set1_ps(r[2]), b[2]));
+                store(_mm_add_ps(acc, _mm_mul_ps(_mm_set1_ps(r[3]), b[3])))
+            };
+            [row(a[0]), row(a[1]), row(a[2]), row(a[3])]
+        }
+    }
+
+    /// The four row dot products are transposed into a single register while they are summed.
+    #[inline]
+    pub fn mul_v(m: &[[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
+        unsafe {
+            let v = load(v);
+            let p0 = _mm_mul_ps(load(m[0]), v);
+            let p1 = _mm_mul_ps(load(m[1]), v);
+            let p2 = _mm_mul_ps(load(m[2]), v);
+            let p3 = _mm_mul_ps(load(m[3]), v);
+            let s01 = _mm_add_ps(_mm_unpacklo_ps(p0, p1), _mm_unpackhi_ps(p0, p1));
+            let s23 = _mm_add_ps(_mm_unpacklo_ps(p2, p3), _mm_unpackhi_ps(p2, p3));
+            store(_mm_add_ps(_mm_movelh_ps(s01, s23), _mm_movehl_ps(s23, s01)))
+        }
+    }
+
+    /// The adjugate of `m` and the determinant, following the cofactor expansion of GLM's
+    /// `glm_mat4_inverse`. Operating on rows instead of columns yields the rows of the
+    /// adjugate, because the inverse of the transpose is the transpose of the inverse.
+    #[inline]
+    pub fn adjugate(m: &[[f32; 4]; 4]) -> ([[f32; 4]; 4], f32) {
+        unsafe {
+            let (r0, r1, r2, r3) = (load(m[0]), load(m[1]), load(m[2]), load(m[3]));
+            macro_rules! factor {
+                ($a: expr, $b: expr) => {{
+                    let swp0a = _mm_shuffle_ps(r3, r2, $a);
+                    let swp0b = _mm_shuffle_ps(r3, r2, $b);
+                    let swp00 = _mm_shuffle_ps(r2, r1, $b);
+                    let swp01 = _mm_shuffle_ps(swp0a, swp0a, 0b10_00_00_00);
+                    let swp02 = _mm_shuffle_ps(swp0b, swp0b, 0b10_00_00_00);
+                    let swp03 = _mm_shuffle_ps(r2, r1, $a);
+                    _mm_sub_ps(_mm_mul_ps(swp00, swp01), _mm_mul_ps(swp02, swp03))
+                }}
+            }
+            let fac0 = factor!(0b11_11_11_11, 0b10_10_10_10);
+            let fac1 = factor!(0b11_11_11_11, 0b01_01_01_01);
+            let fac2 = factor!(0b10_10_10_10, 0b01_01_01_01);
+            let fac3 = factor!(0b11_11_11_11, 0b00_00_00_00);
+            let fac4 = factor!(0b10_10_10_10, 0b00_00_00_00);
+            let fac5 = factor!(0b01_01_01_01, 0b00_00_00_00);
+
+            macro_rules! splat {
+                ($lane: expr) => {{
+                    let tmp = _mm_shuffle_ps(r1, r0, $lane);
+                    _mm_shuffle_ps(tmp, tmp, 0b10_10_10_00)
+                }}
+            }
+            let vec0 = splat!(0b00_00_00_00);
+            let vec1 = splat!(0b01_01_01_01);
+            let vec2 = splat!(0b10_10_10_10);
+            let vec3 = splat!(0b11_11_11_11);
+
+            let sign_a = _mm_set_ps(1.0, -1.0, 1.0, -1.0);
+            let sign_b = _mm_set_ps(-1.0, 1.0, -1.0, 1.0);
+            let cofactors = |a: __m128, fa: __m128, b: __m128, fb: __m128, c: __m128, fc: __m128| {
+                _mm_add_ps(_mm_sub_ps(_mm_mul_ps(a, fa), _mm_mul_ps(b, fb)), _mm_mul_ps(c, fc))
+            };
+            let inv0 = _mm_mul_ps(sign_b, cofactors(vec1, fac0, vec2, fac1, vec3, fac2));
+            let inv1 = _mm_mul_ps(sign_a, cofactors(vec0, fac0, vec2, fac3, vec3, fac4));
+            let inv2 = _mm_mul_ps(sign_b, cofactors(vec0, fac1, vec1, fac3, vec3, fac5));
+            let inv3 = _mm_mul_ps(sign_a, cofactors(vec0, fac2, vec1, fac4, vec2, fac5));
+
+            let col0 = _mm_shuffle_ps(_mm_shuffle_ps(inv0, inv1, 0b00_00_00_00),
+                                      _mm_shuffle_ps(inv2, inv3, 0b00_00_00_00),
+                                      0b10_00_10_00);
+            let det = _mm_cvtss_f32(dot_lane(r0, col0));
+            ([store(inv0), store(inv1), store(inv2), store(inv3)], det)
+        }
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod backend {
+    use vector::Vec4f;
+    use matrix::Mat4x4f;
+
+    #[inline]
+    pub fn load(a: [f32; 4]) -> Vec4f {
+        Vec4f::from(a)
+    }
+
+    #[inline]
+    pub fn load3(a: [f32; 3]) -> Vec4f {
+        Vec4f::new(a[0], a[1], a[2], 0.0)
+    }
+
+    #[inline]
+    pub fn store(a: Vec4f) -> [f32; 4] {
+        a.data
+    }
+
+    #[inline]
+    pub fn store3(a: Vec4f) -> [f32; 3] {
+        a.truncate().data
+    }
+
+    #[inline]
+    pub fn add(a: Vec4f, b: Vec4f) -> Vec4f {
+        a + b
+    }
+
+    #[inline]
+    pub fn sub(a: Vec4f, b: Vec4f) -> Vec4f {
+        a - b
+    }
+
+    #[inline]
+    pub fn mul(a: Vec4f, b: Vec4f) -> Vec4f {
+        a * b
+    }
+
+    #[inline]
+    pub fn scale(a: Vec4f, scalar: f32) -> Vec4f {
+        a * scalar
+    }
+
+    #[inline]
+    pub fn div(a: Vec4f, scalar: f32) -> Vec4f {
+        a / scalar
+    }
+
+    #[inline]
+    pub fn dot(a: Vec4f, b: Vec4f) -> f32 {
+        a.dot(b)
+    }
+
+    #[inline]
+    pub fn cross(a: Vec4f, b: Vec4f) -> Vec4f {
+        a.truncate().cross(b.truncate()).extend(0.0)
+    }
+
+    #[inline]
+    pub fn mul_m(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+        (Mat4x4f::from(*a) * Mat4x4f::from(*b)).into()
+    }
+
+    #[inline]
+    pub fn mul_v(m: &[[f32; 4]; 4], v: [f32; 4]) -> [f32; 4] {
+        Mat4x4f::from(*m).mul_v(Vec4f::from(v)).into()
+    }
+
+    #[inline]
+    pub fn adjugate(m: &[[f32; 4]; 4]) -> ([[f32; 4]; 4], f32) {
+        let m = Mat4x4f::from(*m);
+        let det = m.determinant();
+        let adjugate = m.try_inverse().map_or(Mat4x4f::zero(), |inv| inv * det);
+        (adjugate.into(), det)
+    }
+}
+
+impl Vector<f32, 4> {
+    #[inline]
+    pub fn add_simd(self, other: Self) -> Self {
+        Self::from(store(backend::add(load(self.data), load(other.data))))
+    }
+
+    #[inline]
+    pub fn sub_simd(self, other: Self) -> Self {
+        Self::from(store(backend::sub(load(self.data), load(other.data))))
+    }
+
+    /// Component-wise product.
+    #[inline]
+    pub fn mul_simd(self, other: Self) -> Self {
+        Self::from(store(backend::mul(load(self.data), load(other.data))))
+    }
+
+    #[inline]
+    pub fn scale_simd(self, scalar: f32) -> Self {
+        Self::from(store(backend::scale(load(self.data), scalar)))
+    }
+
+    #[inline]
+    pub fn dot_simd(self, other: Self) -> f32 {
+        backend::dot(load(self.data), load(other.data))
+    }
+
+    #[inline]
+    pub fn normalize_simd(self) -> Option<Self> {
+        let v = load(self.data);
+        let len_sq = backend::dot(v, v);
+        if len_sq == 1.0 {
+            Some(self)
+        } else if len_sq == 0.0 {
+            None
+        } else {
+            Some(Self::from(store(backend::div(v, len_sq.sqrt()))))
+        }
+    }
+}
+
+impl Vector<f32, 3> {
+    #[inline]
+    pub fn add_simd(self, other: Self) -> Self {
+        Self::from(store3(backend::add(load3(self.data), load3(other.data))))
+    }
+
+    #[inline]
+    pub fn sub_simd(self, other: Self) -> Self {
+        Self::from(store3(backend::sub(load3(self.data), load3(other.data))))
+    }
+
+    /// Component-wise product.
+    #[inline]
+    pub fn mul_simd(self, other: Self) -> Self {
+        Self::from(store3(backend::mul(load3(self.data), load3(other.data))))
+    }
+
+    #[inline]
+    pub fn scale_simd(self, scalar: f32) -> Self {
+        Self::from(store3(backend::scale(load3(self.data), scalar)))
+    }
+
+    #[inline]
+    pub fn dot_simd(self, other: Self) -> f32 {
+        backend::dot(load3(self.data), load3(other.data))
+    }
+
+    #[inline]
+    pub fn cross_simd(self, other: Self) -> Self {
+        Self::from(store3(backend::cross(load3(self.data), load3(other.data))))
+    }
+
+    #[inline]
+    pub fn normalize_simd(self) -> Option<Self> {
+        let v = load3(self.data);
+        let len_sq = backend::dot(v, v);
+        if len_sq == 1.0 {
+            Some(self)
+        } else if len_sq == 0.0 {
+            None
+        } else {
+            Some(Self::from(store3(backend::div(v, len_sq.sqrt()))))
+        }
+    }
+}
+
+impl Matrix<f32, 4, 4> {
+    /// `self * other`.
+    #[inline]
+    pub fn mul_simd(&self, other: &Self) -> Self {
+        Self::from(backend::mul_m(&(*self).into(), &(*other).into()))
+    }
+
+    /// `self * v`.
+    #[inline]
+    pub fn mul_v_simd(&self, v: Vec4f) -> Vec4f {
+        Vec4f::from(backend::mul_v(&(*self).into(), v.data))
+    }
+
+    /// Returns `None` if the matrix is singular, with the same test as `try_inverse` applied
+    /// to the determinant of the adjugate.
+    #[inline]
+    pub fn try_inverse_simd(&self) -> Option<Self> {
+        let (adjugate, det) = backend::adjugate(&(*self).into());
+        if self.is_singular_det(det) {
+            return None;
+        }
+        Some(Self::from(adjugate) * (1.0 / det))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use vector::*;
+    use matrix::*;
+    use unit::{Radians, Degrees};
+
+    fn vectors() -> Vec<Vec4f> {
+        vec![Vec4f::new(1., 2., 3., 4.),
+             Vec4f::new(-0.5, 0.25, 8., -3.),
+             Vec4f::new(0., 0., 0., 0.),
+             Vec4f::new(0., 0., 1., 0.),
+             Vec4f::new(1e-3, -7e2, 3.3, 0.1),
+             Vec4f::new(-1., -1., 2.5, 9.)]
+    }
+
+    fn matrices() -> Vec<Mat4x4f> {
+        let t = Mat4x4f::translate(Vec3f::new(1., -2., 3.)) *
+                Mat4x4f::rotation_y(Radians::new(0.8));
+        let far = Mat4x4f::translate(Vec3f::new(250., -60., 1e3)) *
+                  Mat4x4f::rotation_x(Radians::new(0.3));
+        let general = Mat4x4f::from([[2., -1., 0., 3.],
+                                      [1., 4., -2., 0.5],
+                                      [0., 3., 5., -1.],
+                                      [-2., 0.5, 1., 6.]]);
+        let singular = Mat4x4f::from([[1., 2., 3., 4.],
+                                       [2., 4., 6., 8.],
+                                       [0., 1., 0., 1.],
+                                       [3., 0., 1., 2.]]);
+        vec![Mat4x4f::identity(),
+             t,
+             far,
+             general,
+             singular,
+             Mat4x4f::perspective(Degrees::new(60.0),
+                                  1.5,
+                                  0.1,
+                                  100.0,
+                                  Handedness::Right,
+                                  DepthRange::ZeroToOne),
+             Mat4x4f::scale(Vec3f::new(2., 0.5, 4.))]
+    }
+
+    #[test]
+    fn vec4() {
+        for &a in vectors().iter() {
+            for &b in vectors().iter() {
+                assert!(a.add_simd(b) == a + b);
+                assert!(a.sub_simd(b) == a - b);
+                assert!(a.mul_simd(b) == a * b);
+                assert!(a.scale_simd(b.x()) == a * b.x());
+                assert_relative_eq!(a.dot_simd(b), a.dot(b), epsilon = 1e-3, max_relative = 1e-5);
+            }
+            assert!(a.normalize_simd().is_some() == a.normalize().is_some());
+            if let Some(n) = a.normalize_simd() {
+                assert_relative_eq!(n, a.normalize().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn vec3() {
+        for &a in vectors().iter() {
+            let a = a.truncate();
+            for &b in vectors().iter() {
+                let b = b.truncate();
+                assert!(a.add_simd(b) == a + b);
+                assert!(a.sub_simd(b) == a - b);
+                assert!(a.mul_simd(b) == a * b);
+                assert!(a.scale_simd(b.y()) == a * b.y());
+                assert!(a.cross_simd(b) == a.cross(b));
+                assert_relative_eq!(a.dot_simd(b), a.dot(b), epsilon = 1e-3, max_relative = 1e-5);
+            }
+            assert!(a.normalize_simd().is_some() == a.normalize().is_some());
+            if let Some(n) = a.normalize_simd() {
+                assert_relative_eq!(n, a.normalize().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn mat4() {
+        for a in matrices().iter() {
+            for b in matrices().iter() {
+                assert_relative_eq!(a.mul_simd(b), a * b);
+            }
+            for &v in vectors().iter() {
+                assert_relative_eq!(a.mul_v_simd(v),
+                                    a.mul_v(v),
+                                    epsilon = 1e-3,
+                                    max_relative = 1e-5);
+            }
+            match (a.try_inverse_simd(), a.try_inverse()) {
+                (Some(simd), Some(scalar)) => {
+                    assert_relative_eq!(simd, scalar, epsilon = 1e-6, max_relative = 1e-5)
+                }
+                (None, None) => (),
+                _ => panic!("Singularity differs for {:?}", a),
+            }
+        }
+        let far = Mat4x4f::translate(Vec3f::new(250., -60., 1e3));
+        assert_abs_diff_eq!(far.try_inverse_simd().unwrap(),
+                            Mat4x4f::translate(Vec3f::new(-250., 60., -1e3)));
+    }
+}
@@ -0,0 +1,19 @@
+//! Helpers shared by the tests of several modules.
+
+use vector::Point3f;
+
+/// A small deterministic generator, the tests only need well spread values.
+pub struct Lcg(pub u32);
+
+impl Lcg {
+    /// A value in `[0, 1)`.
+    pub fn next(&mut self) -> f32 {
+        self.0 = self.0.wrapping_mul(1664525).wrapping_add(1013904223);
+        (self.0 >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// A point in the cube `[0, scale)^3`.
+    pub fn point(&mut self, scale: f32) -> Point3f {
+        Point3f::new(self.next() * scale, self.next() * scale, self.next() * scale)
+    }
+}
@@ -3,6 +3,11 @@ use matrix::*;
 use num::Float;
 use vector::*;
 use ray::*;
+use aabb::{Aabb, Sphere};
+use unit::Angle;
+use std::ops::Mul;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Transform<T>
     where T: Float
 {
@@ -16,34 +21,34 @@ impl<T> Transform<T>
         let m = Mat4x4::<T>::translate(v);
         let m_inv = Mat4x4::<T>::translate(-v);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
 
-    pub fn rotation_x(angle: T) -> Self {
+    pub fn rotation_x<A: Angle<T>>(angle: A) -> Self {
         let m = Mat4x4::<T>::rotation_x(angle);
         let m_inv = Mat4x4::<T>::rotation_x(-angle);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
 
-    pub fn rotation_y(angle: T) -> Self {
+    pub fn rotation_y<A: Angle<T>>(angle: A) -> Self {
         let m = Mat4x4::<T>::rotation_y(angle);
         let m_inv = Mat4x4::<T>::rotation_y(-angle);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
 
-    pub fn rotation_z(angle: T) -> Self {
+    pub fn rotation_z<A: Angle<T>>(angle: A) -> Self {
         let m = Mat4x4::<T>::rotation_z(angle);
         let m_inv = Mat4x4::<T>::rotation_z(-angle);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
@@ -53,52 +58,450 @@ impl<T> Transform<T>
         let s_inv = s.map(|val| T::one() / val);
         let m_inv = Mat4x4::<T>::scale(s_inv);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
 
-    pub fn mul(&self, p: Vec3<T>) -> Vec3<T> {
-        let new_p = self.m.mul_v(p.extend(T::one()));
-        new_p.truncate() / new_p.w()
+    pub fn perspective<A: Angle<T>>(fovy: A,
+                                    aspect: T,
+                                    near: T,
+                                    far: T,
+                                    handedness: Handedness,
+                                    depth: DepthRange)
+                                    -> Self {
+        let m = Mat4x4::<T>::perspective(fovy, aspect, near, far, handedness, depth);
+        Self::projective(m)
     }
-    pub fn mul_ray(&self, ray: Ray<T>) -> Ray<T> {
-        let new_origin = self.m.mul_v(ray.origin.extend(T::one())).truncate();
-        let new_direction = self.m
-            .mul_v(ray.direction.extend(T::zero()))
-            .truncate()
-            .normalize()
-            .expect("Not null");
-        Ray::new(new_origin, new_direction, ray.max_time)
+
+    pub fn perspective_infinite<A: Angle<T>>(fovy: A,
+                                             aspect: T,
+                                             near: T,
+                                             handedness: Handedness,
+                                             depth: DepthRange)
+                                             -> Self {
+        let m = Mat4x4::<T>::perspective_infinite(fovy, aspect, near, handedness, depth);
+        Self::projective(m)
     }
 
-    pub fn look_at(pos: Vec3<T>, target: Vec3<T>, up: Vec3<T>) -> Self {
-        let z = (pos - target).normalize().unwrap();
-        let x = up.cross(z).normalize().unwrap();
-        let y = z.cross(x);
+    pub fn perspective_reversed_z<A: Angle<T>>(fovy: A,
+                                               aspect: T,
+                                               near: T,
+                                               far: T,
+                                               handedness: Handedness,
+                                               depth: DepthRange)
+                                               -> Self {
+        let m = Mat4x4::<T>::perspective_reversed_z(fovy, aspect, near, far, handedness, depth);
+        Self::projective(m)
+    }
 
-        let m = Mat4x4::<T>::new(&[x.extend(T::zero()),
-                                   y.extend(T::zero()),
-                                   z.extend(T::zero()),
-                                   Vec4::new(T::one(), T::zero(), T::zero(), T::zero())]);
+    pub fn perspective_infinite_reversed_z<A: Angle<T>>(fovy: A,
+                                                        aspect: T,
+                                                        near: T,
+                                                        handedness: Handedness,
+                                                        depth: DepthRange)
+                                                        -> Self {
+        let m = Mat4x4::<T>::perspective_infinite_reversed_z(fovy, aspect, near, handedness, depth);
+        Self::projective(m)
+    }
 
-        let m_inv = m.inverse();
+    #[allow(clippy::too_many_arguments)]
+    pub fn frustum(left: T,
+                   right: T,
+                   bottom: T,
+                   top: T,
+                   near: T,
+                   far: T,
+                   handedness: Handedness,
+                   depth: DepthRange)
+                   -> Self {
+        let m = Mat4x4::<T>::frustum(left, right, bottom, top, near, far, handedness, depth);
+        Self::projective(m)
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn orthographic(left: T,
+                        right: T,
+                        bottom: T,
+                        top: T,
+                        near: T,
+                        far: T,
+                        handedness: Handedness,
+                        depth: DepthRange)
+                        -> Self {
+        let m = Mat4x4::<T>::orthographic(left, right, bottom, top, near, far, handedness, depth);
+        // Every row is `scale * x + offset`.
+        let row = |i: usize| {
+            let mut v = Vec4::zero();
+            v[i] = T::one() / m[i][i];
+            v[3] = -m[i][3] / m[i][i];
+            v
+        };
+        let m_inv = Mat4x4::<T>::new(&[row(0), row(1), row(2),
+                                       Vec4::new(T::zero(), T::zero(), T::zero(), T::one())]);
         Transform {
-            m: m,
+            m,
             inverse: m_inv,
         }
     }
+
+    /// Builds the analytic inverse of a matrix with the layout produced by
+    /// `Mat4x4::frustum`.
+    #[rustfmt::skip]
+    fn projective(m: Mat4x4<T>) -> Self {
+        let x = m[0][0];
+        let y = m[1][1];
+        let (cx, cy) = (m[0][2], m[1][2]);
+        let (a, b) = (m[2][2], m[2][3]);
+        // The sign of the view space z axis is either 1 or -1 and its own inverse.
+        let s = m[3][2];
+        let m_inv = Mat4x4::<T>::new(&[Vec4::new(T::one() / x, T::zero(),     T::zero(),     -cx * s / x),
+                                       Vec4::new(T::zero(),     T::one() / y, T::zero(),     -cy * s / y),
+                                       Vec4::new(T::zero(),     T::zero(),     T::zero(),     s),
+                                       Vec4::new(T::zero(),     T::zero(),     T::one() / b, -a * s / b)]);
+        Transform {
+            m,
+            inverse: m_inv,
+        }
+    }
+
+    pub fn identity() -> Self {
+        Transform {
+            m: Mat4x4::identity(),
+            inverse: Mat4x4::identity(),
+        }
+    }
+
+    /// Builds a transform from a matrix, returns `None` if the matrix is singular.
+    pub fn from_matrix(m: Mat4x4<T>) -> Option<Self> {
+        m.try_inverse().map(|m_inv| {
+            Transform {
+                m,
+                inverse: m_inv,
+            }
+        })
+    }
+
+    /// Pairs a matrix with its inverse, the caller guarantees that they match.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_parts(m: Mat4x4<T>, inverse: Mat4x4<T>) -> Self {
+        Transform {
+            m,
+            inverse,
+        }
+    }
+
+    pub fn matrix(&self) -> &Mat4x4<T> {
+        &self.m
+    }
+
+    pub fn inverse_matrix(&self) -> &Mat4x4<T> {
+        &self.inverse
+    }
+
+    /// Swaps the matrix and its inverse.
+    pub fn inverse(&self) -> Self {
+        Transform {
+            m: self.inverse,
+            inverse: self.m,
+        }
+    }
+
+    /// Transforms a point, including the translation and the projective division.
+    pub fn transform_point(&self, p: Point3<T>) -> Point3<T> {
+        let new_p = self.m.mul_v(p.to_vector().extend(T::one()));
+        if new_p.w() == T::one() {
+            new_p.truncate().to_point()
+        } else {
+            (new_p.truncate() / new_p.w()).to_point()
+        }
+    }
+
+    /// Transforms a direction, the translation is ignored.
+    pub fn transform_vector(&self, v: Vec3<T>) -> Vec3<T> {
+        self.m.mul_v(v.extend(T::zero())).truncate()
+    }
+
+    /// Transforms a surface normal with the inverse transpose, so that it stays perpendicular
+    /// to the transformed surface. The result is not normalized.
+    pub fn transform_normal(&self, n: Normal3<T>) -> Normal3<T> {
+        let inv = &self.inverse;
+        Normal3::new(inv[0][0] * n.x() + inv[1][0] * n.y() + inv[2][0] * n.z(),
+                     inv[0][1] * n.x() + inv[1][1] * n.y() + inv[2][1] * n.z(),
+                     inv[0][2] * n.x() + inv[1][2] * n.y() + inv[2][2] * n.z())
+    }
+
+    /// The smallest box that contains all eight transformed corners of `aabb`.
+    pub fn transform_aabb(&self, aabb: Aabb<T>) -> Aabb<T> {
+        let (min, max) = (aabb.min(), aabb.max());
+        let corner = |i: usize| {
+            Point3::new(if i & 1 == 0 { min.x() } else { max.x() },
+                        if i & 2 == 0 { min.y() } else { max.y() },
+                        if i & 4 == 0 { min.z() } else { max.z() })
+        };
+        (1..8).fold(Aabb::single(self.transform_point(corner(0))),
+                    |acc, i| acc.union_point(self.transform_point(corner(i))))
+    }
+
+    /// Transforms the center of `sphere` and scales its radius by the length of the longest
+    /// transformed axis. Exact for uniform scales and the smallest enclosing sphere for a
+    /// scale followed by a rotation. Shears can stretch a direction between the axes further,
+    /// the result does not contain the transformed sphere for them.
+    pub fn transform_sphere(&self, sphere: Sphere<T>) -> Sphere<T> {
+        let scale = (0..3)
+            .map(|i| self.m[0][i] * self.m[0][i] + self.m[1][i] * self.m[1][i] +
+                     self.m[2][i] * self.m[2][i])
+            .fold(T::zero(), T::max)
+            .sqrt();
+        Sphere::new(self.transform_point(sphere.center()), sphere.radius() * scale)
+    }
+
+    /// Transforms the origin as a point and the direction as a vector. The direction is not
+    /// renormalized, so the ray parameter and `max_time` still describe the same points.
+    pub fn transform_ray(&self, ray: Ray<T>) -> Ray<T> {
+        Ray::new(self.transform_point(ray.origin),
+                 self.transform_vector(ray.direction),
+                 ray.max_time)
+    }
+
+    /// The view transform of a camera at `eye` looking at `target`. Maps `eye` to the origin
+    /// and `target` onto the negative z axis for `Handedness::Right`, or onto the positive z
+    /// axis for `Handedness::Left`.
+    pub fn look_at(eye: Point3<T>, target: Point3<T>, up: Vec3<T>, handedness: Handedness) -> Self {
+        Self::look_to(eye, target - eye, up, handedness)
+    }
+
+    /// The view transform of a camera at `eye` looking in the direction `dir`.
+    pub fn look_to(eye: Point3<T>, dir: Vec3<T>, up: Vec3<T>, handedness: Handedness) -> Self {
+        let (view, camera) = Self::view_basis(eye, dir, up, handedness);
+        Transform {
+            m: view,
+            inverse: camera,
+        }
+    }
+
+    /// The inverse of `look_to`, places a camera at `eye` looking in the direction `dir`.
+    /// Maps points from camera space into world space.
+    pub fn camera_to_world(eye: Point3<T>,
+                           dir: Vec3<T>,
+                           up: Vec3<T>,
+                           handedness: Handedness)
+                           -> Self {
+        let (view, camera) = Self::view_basis(eye, dir, up, handedness);
+        Transform {
+            m: camera,
+            inverse: view,
+        }
+    }
+
+    /// Returns the world to camera matrix and its inverse. The camera basis is orthonormal,
+    /// so the inverse of the rotation is its transpose.
+    #[rustfmt::skip]
+    fn view_basis(eye: Point3<T>,
+                  dir: Vec3<T>,
+                  up: Vec3<T>,
+                  handedness: Handedness)
+                  -> (Mat4x4<T>, Mat4x4<T>) {
+        let forward = dir.normalize().expect("Non zero view direction");
+        let (x, z) = match handedness {
+            Handedness::Right => (forward.cross(up), -forward),
+            Handedness::Left => (up.cross(forward), forward),
+        };
+        let x = x.normalize().expect("Up vector parallel to the view direction");
+        let y = z.cross(x);
+
+        let view = Mat4x4::<T>::new(&[x.extend(-x.dot(eye)),
+                                      y.extend(-y.dot(eye)),
+                                      z.extend(-z.dot(eye)),
+                                      Vec4::new(T::zero(), T::zero(), T::zero(), T::one())]);
+        let camera = Mat4x4::<T>::new(&[Vec4::new(x.x(), y.x(), z.x(), eye.x()),
+                                        Vec4::new(x.y(), y.y(), z.y(), eye.y()),
+                                        Vec4::new(x.z(), y.z(), z.z(), eye.z()),
+                                        Vec4::new(T::zero(), T::zero(), T::zero(), T::one())]);
+        (view, camera)
+    }
+}
+
+/// `a * b` applies `b` first and then `a`.
+impl<T> Mul for &Transform<T>
+    where T: Float
+{
+    type Output = Transform<T>;
+    fn mul(self, other: Self) -> Transform<T> {
+        Transform {
+            m: self.m * other.m,
+            inverse: other.inverse * self.inverse,
+        }
+    }
+}
+
+impl<T> Mul for Transform<T>
+    where T: Float
+{
+    type Output = Transform<T>;
+    fn mul(self, other: Self) -> Transform<T> {
+        &self * &other
+    }
 }
 
 #[cfg(test)]
 mod test {
     use transform::*;
-    use matrix::*;
+    use unit::Radians;
+
+    #[test]
+    fn projection_inverse() {
+        use unit::Degrees;
+        let fovy = Degrees::new(60.0);
+        for &handedness in [Handedness::Left, Handedness::Right].iter() {
+            for &depth in [DepthRange::NegativeOneToOne, DepthRange::ZeroToOne].iter() {
+                let projections =
+                    [Transform::perspective(fovy, 1.5, 0.1, 100., handedness, depth),
+                     Transform::perspective_infinite(fovy, 1.5, 0.1, handedness, depth),
+                     Transform::perspective_reversed_z(fovy, 1.5, 0.1, 100., handedness, depth),
+                     Transform::perspective_infinite_reversed_z(fovy, 1.5, 0.1, handedness, depth),
+                     Transform::frustum(-1., 2., -0.5, 1., 1., 10., handedness, depth),
+                     Transform::orthographic(-1., 2., -3., 1., 1., 10., handedness, depth)];
+                for t in projections.iter() {
+                    assert_abs_diff_eq!(t * &t.inverse(), Transform::identity(), epsilon = 1e-5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn look_at() {
+        let eye = Point3f::new(1., 2., 3.);
+        let target = Point3f::new(1., 2., -7.);
+        let up = Vec3f::new(0., 1., 0.);
+        let view = Transform::look_at(eye, target, up, Handedness::Right);
+        assert_abs_diff_eq!(&view * &view.inverse(), Transform::identity(), epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(eye), Point3f::new(0., 0., 0.), epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(target),
+                            Point3f::new(0., 0., -10.),
+                            epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(eye + up),
+                            Point3f::new(0., 1., 0.),
+                            epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(eye + Vec3f::new(1., 0., 0.)),
+                            Point3f::new(1., 0., 0.),
+                            epsilon = 1e-5);
+
+        let view = Transform::look_at(eye, target, up, Handedness::Left);
+        assert_abs_diff_eq!(&view * &view.inverse(), Transform::identity(), epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(target),
+                            Point3f::new(0., 0., 10.),
+                            epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(eye + up),
+                            Point3f::new(0., 1., 0.),
+                            epsilon = 1e-5);
+        assert_abs_diff_eq!(view.transform_point(eye + Vec3f::new(-1., 0., 0.)),
+                            Point3f::new(1., 0., 0.),
+                            epsilon = 1e-5);
+    }
+
+    #[test]
+    fn far_look_at() {
+        let eye = Point3f::new(100., 20., -80.);
+        let view = Transform::look_at(eye, Point3f::new(0., 0., 0.), Vec3f::new(0., 1., 0.),
+                                      Handedness::Right);
+        let rebuilt = Transform::from_matrix(*view.matrix()).unwrap();
+        assert_abs_diff_eq!(&rebuilt * &rebuilt.inverse(), Transform::identity(), epsilon = 1e-5);
+        assert_abs_diff_eq!(rebuilt.inverse, view.inverse, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn camera_to_world() {
+        let eye = Point3f::new(-4., 0., 2.);
+        let dir = Vec3f::new(1., 1., 0.);
+        let up = Vec3f::new(0., 0., 1.);
+        for &handedness in [Handedness::Left, Handedness::Right].iter() {
+            let camera = Transform::camera_to_world(eye, dir, up, handedness);
+            let view = Transform::look_to(eye, dir, up, handedness);
+            assert_abs_diff_eq!(&camera * &camera.inverse(), Transform::identity(), epsilon = 1e-5);
+            assert_abs_diff_eq!(camera.transform_point(Point3f::new(0., 0., 0.)),
+                                eye,
+                                epsilon = 1e-5);
+            let p = Point3f::new(3., -1., 5.);
+            assert_abs_diff_eq!(camera.transform_point(view.transform_point(p)), p, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn compose() {
+        let t = Transform::translate(Vec3f::new(1., 2., 3.));
+        let r = Transform::rotation_z(Radians::new(::std::f32::consts::PI / 2.));
+        let s = Transform::scale(Vec3f::new(2., 2., 2.));
+        let trs = &(&t * &r) * &s;
+        assert_abs_diff_eq!(&trs * &trs.inverse(), Transform::identity(), epsilon = 1e-5);
+        assert_abs_diff_eq!(&trs.inverse() * &trs, Transform::identity(), epsilon = 1e-5);
+        let p = Point3f::new(1., 0., 0.);
+        assert_abs_diff_eq!(trs.transform_point(p), Point3f::new(1., 4., 3.), epsilon = 1e-5);
+        assert_abs_diff_eq!(trs.transform_vector(p.to_vector()),
+                            Vec3f::new(0., 2., 0.),
+                            epsilon = 1e-5);
+        assert_abs_diff_eq!(trs.inverse().transform_point(Point3f::new(1., 4., 3.)),
+                            p,
+                            epsilon = 1e-5);
+        assert!(Transform::identity() * t.clone() == t);
+        assert!(trs.inverse().matrix() == trs.inverse_matrix());
+    }
+
+    #[test]
+    fn transform_normal() {
+        // A plane with normal (1, 1, 0) stretched along x, the normal has to tilt towards y.
+        let s = Transform::scale(Vec3f::new(2., 1., 1.));
+        let n = s.transform_normal(Normal3f::new(1., 1., 0.));
+        let tangent = s.transform_vector(Vec3f::new(1., -1., 0.));
+        assert!(n.dot(tangent).abs() < 1e-6);
+        assert_abs_diff_eq!(n, Normal3f::new(0.5, 1., 0.), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn transform_aabb() {
+        let aabb = Aabb::new(Point3f::new(-1., -1., -1.), Point3f::new(1., 1., 1.));
+        let t = &Transform::translate(Vec3f::new(5., 0., 0.)) *
+                &Transform::rotation_z(Radians::new(::std::f32::consts::PI / 4.));
+        let result = t.transform_aabb(aabb);
+        let d = 2f32.sqrt();
+        assert_abs_diff_eq!(result.min(), Point3f::new(5. - d, -d, -1.), epsilon = 1e-5);
+        assert_abs_diff_eq!(result.max(), Point3f::new(5. + d, d, 1.), epsilon = 1e-5);
+    }
+
+    #[test]
+    fn transform_sphere() {
+        let sphere = Sphere::new(Point3f::new(1., 0., 0.), 1.);
+        let t = &Transform::translate(Vec3f::new(0., 2., 0.)) *
+                &Transform::scale(Vec3f::new(3., 3., 3.));
+        let result = t.transform_sphere(sphere);
+        assert_abs_diff_eq!(result.center(), Point3f::new(3., 2., 0.), epsilon = 1e-5);
+        assert!((result.radius() - 3.).abs() < 1e-6);
+        // A non uniform scale uses the largest factor.
+        let stretched = Transform::scale(Vec3f::new(1., 4., 2.)).transform_sphere(sphere);
+        assert!((stretched.radius() - 4.).abs() < 1e-6);
+        // The rotation keeps the lengths of the scaled axes.
+        let rotated = &Transform::rotation_z(Radians::new(0.5)) *
+                      &Transform::scale(Vec3f::new(1., 4., 2.));
+        let rotated = rotated.transform_sphere(sphere);
+        assert!((rotated.radius() - 4.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transform_ray() {
+        let ray = Ray::new(Point3f::new(0., 0., 0.), Vec3f::new(1., 0., 0.), 3.);
+        let t = Transform::scale(Vec3f::new(2., 1., 1.));
+        let r = t.transform_ray(ray);
+        assert_abs_diff_eq!(r.direction, Vec3f::new(2., 0., 0.), epsilon = 1e-5);
+        // The end point of the ray is preserved.
+        assert_abs_diff_eq!(r.origin + r.direction * r.max_time,
+                            Point3f::new(6., 0., 0.),
+                            epsilon = 1e-5);
+    }
+
     #[test]
     fn trans() {
         use std::mem::size_of;
-        println!("{}", size_of::<Transform<f32>>());
-        println!("{}", size_of::<Mat4x4<f32>>());
+        // A transform is just the matrix and its inverse.
+        assert!(size_of::<Transform<f32>>() == 2 * size_of::<Mat4x4<f32>>());
     }
 }
@@ -1,88 +1,313 @@
 use num::{Float, NumCast};
+use std::ops::{Add, AddAssign, Sub, SubAssign, Neg, Mul, MulAssign, Div, DivAssign};
 
-pub trait ToDegrees<T: Float> {
-    fn degrees(&self) -> Degrees<T>;
-    fn value(&self) -> T;
-}
-
-pub trait ToRadians<T: Float> {
-    fn radians(&self) -> Radians<T>;
-    fn value(&self) -> T;
-}
-
-pub struct Radians<T>
+/// An angle in some unit, implemented by `Radians`, `Degrees` and `Turns`. Functions that
+/// take an `Angle` accept any of them.
+pub trait Angle<T>: Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> +
+                    Neg<Output = Self> + Mul<T, Output = Self> + Div<T, Output = Self>
     where T: Float
 {
-    pub value: T,
-}
+    /// The value of a full turn in this unit, e.g. `2π` for radians.
+    fn full_turn() -> T;
 
-impl<T> Radians<T>
-    where T: Float
-{
-    pub fn new(value: T) -> Self {
-        Radians { value: value }
+    fn from_value(value: T) -> Self;
+
+    /// The value in this unit.
+    fn value(self) -> T;
+
+    /// Converts the angle into another unit.
+    fn to<A: Angle<T>>(self) -> A {
+        if A::full_turn() == Self::full_turn() {
+            A::from_value(self.value())
+        } else {
+            A::from_value(self.value() * (A::full_turn() / Self::full_turn()))
+        }
     }
-}
-pub struct Degrees<T>
-    where T: Float
-{
-    pub value: T,
-}
 
-impl<T> Degrees<T>
-    where T: Float
-{
-    pub fn new(value: T) -> Self {
-        Degrees { value: value }
+    fn radians(self) -> Radians<T> {
+        self.to()
     }
-}
 
-impl<T> ToDegrees<T> for Degrees<T>
-    where T: Float
-{
-    fn degrees(&self) -> Self {
-        Self::new(self.value)
+    fn degrees(self) -> Degrees<T> {
+        self.to()
     }
 
-    fn value(&self) -> T {
-        self.value
+    fn turns(self) -> Turns<T> {
+        self.to()
+    }
+
+    fn sin(self) -> T {
+        self.radians().value.sin()
+    }
+
+    fn cos(self) -> T {
+        self.radians().value.cos()
+    }
+
+    fn tan(self) -> T {
+        self.radians().value.tan()
+    }
+
+    fn sin_cos(self) -> (T, T) {
+        self.radians().value.sin_cos()
+    }
+
+    /// Wraps the angle into the range `[0, full_turn)`, e.g. `[0, 2π)`.
+    fn wrap(self) -> Self {
+        let full = Self::full_turn();
+        let value = self.value() % full;
+        let value = if value < T::zero() { value + full } else { value };
+        // Adding a full turn to a tiny negative value can round up to a full turn.
+        Self::from_value(if value >= full { T::zero() } else { value })
+    }
+
+    /// Wraps the angle into the range `(-full_turn / 2, full_turn / 2]`, e.g. `(-π, π]`.
+    fn wrap_signed(self) -> Self {
+        let full = Self::full_turn();
+        let value = self.wrap().value();
+        if value > full / NumCast::from(2.0).unwrap() {
+            Self::from_value(value - full)
+        } else {
+            Self::from_value(value)
+        }
+    }
+
+    /// The signed angle of the shortest arc from `self` to `other`.
+    fn difference(self, other: Self) -> Self {
+        (other - self).wrap_signed()
     }
 }
 
-impl<T> ToDegrees<T> for Radians<T>
-    where T: Float
+macro_rules! impl_angle{
+    ($angle: ident, $full_turn: expr) => {
+        #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+        pub struct $angle<T>
+            where T: Float
+        {
+            pub value: T,
+        }
+
+        impl<T> $angle<T>
+            where T: Float
+        {
+            pub fn new(value: T) -> Self {
+                $angle { value }
+            }
+        }
+
+        impl<T> Angle<T> for $angle<T>
+            where T: Float
+        {
+            fn full_turn() -> T {
+                NumCast::from($full_turn).unwrap()
+            }
+
+            fn from_value(value: T) -> Self {
+                Self::new(value)
+            }
+
+            fn value(self) -> T {
+                self.value
+            }
+        }
+
+        impl<T> Add for $angle<T>
+            where T: Float
+        {
+            type Output = Self;
+            fn add(self, other: Self) -> Self {
+                Self::new(self.value + other.value)
+            }
+        }
+
+        impl<T> Sub for $angle<T>
+            where T: Float
+        {
+            type Output = Self;
+            fn sub(self, other: Self) -> Self {
+                Self::new(self.value - other.value)
+            }
+        }
+
+        impl<T> Neg for $angle<T>
+            where T: Float
+        {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self::new(-self.value)
+            }
+        }
+
+        impl<T> Mul<T> for $angle<T>
+            where T: Float
+        {
+            type Output = Self;
+            fn mul(self, scalar: T) -> Self {
+                Self::new(self.value * scalar)
+            }
+        }
+
+        impl<T> Div<T> for $angle<T>
+            where T: Float
+        {
+            type Output = Self;
+            fn div(self, scalar: T) -> Self {
+                Self::new(self.value / scalar)
+            }
+        }
+
+        impl<T> AddAssign for $angle<T>
+            where T: Float
+        {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T> SubAssign for $angle<T>
+            where T: Float
+        {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl<T> MulAssign<T> for $angle<T>
+            where T: Float
+        {
+            fn mul_assign(&mut self, scalar: T) {
+                *self = *self * scalar;
+            }
+        }
+
+        impl<T> DivAssign<T> for $angle<T>
+            where T: Float
+        {
+            fn div_assign(&mut self, scalar: T) {
+                *self = *self / scalar;
+            }
+        }
+    }
+}
+
+impl_angle!(Radians, 2.0 * ::std::f64::consts::PI);
+impl_angle!(Degrees, 360.0);
+impl_angle!(Turns, 1.0);
+
+/// The conversion trait from before `Angle`, kept so that existing code still compiles.
+/// Importing it together with `Angle`, e.g. through `unit::*`, makes the shared method names
+/// ambiguous.
+#[deprecated(note = "use `Angle::degrees` instead")]
+pub trait ToDegrees<T: Float> {
+    fn degrees(&self) -> Degrees<T>;
+    /// The value in degrees, unlike `Angle::value` which keeps the unit of the angle.
+    fn value(&self) -> T;
+}
+
+#[allow(deprecated)]
+impl<T, A> ToDegrees<T> for A
+    where T: Float,
+          A: Angle<T>
 {
     fn degrees(&self) -> Degrees<T> {
-        use std::f64;
-        Degrees::new(self.value * NumCast::from(180.0f64 / f64::consts::PI).unwrap())
+        Angle::to(*self)
     }
 
     fn value(&self) -> T {
-        self.degrees().value
+        ToDegrees::degrees(self).value
     }
 }
 
-impl<T> ToRadians<T> for Degrees<T>
-    where T: Float
+/// The conversion trait from before `Angle`, see `ToDegrees`.
+#[deprecated(note = "use `Angle::radians` instead")]
+pub trait ToRadians<T: Float> {
+    fn radians(&self) -> Radians<T>;
+    /// The value in radians, unlike `Angle::value` which keeps the unit of the angle.
+    fn value(&self) -> T;
+}
+
+#[allow(deprecated)]
+impl<T, A> ToRadians<T> for A
+    where T: Float,
+          A: Angle<T>
 {
     fn radians(&self) -> Radians<T> {
-        use std::f64;
-        Radians::new(self.value * NumCast::from(f64::consts::PI / 180.0f64).unwrap())
+        Angle::to(*self)
     }
 
     fn value(&self) -> T {
-        self.radians().value
+        ToRadians::radians(self).value
     }
 }
 
-impl<T> ToRadians<T> for Radians<T>
-    where T: Float
-{
-    fn radians(&self) -> Radians<T> {
-        Radians::new(self.value)
+#[cfg(test)]
+mod test {
+    use unit::{Angle, Radians, Degrees, Turns};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn conversions() {
+        assert_abs_diff_eq!(Degrees::new(180.0).radians().value, PI);
+        assert_abs_diff_eq!(Radians::new(PI / 2.).degrees().value, 90.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(Turns::new(0.25).degrees().value, 90.0);
+        assert_abs_diff_eq!(Degrees::new(-720.0).turns().value, -2.0);
+        assert!(Radians::new(1.5f32).radians() == Radians::new(1.5));
+        assert!(Degrees::new(30.0f32).to::<Degrees<f32>>() == Degrees::new(30.0));
     }
 
-    fn value(&self) -> T {
-        self.value
+    #[test]
+    fn arithmetic() {
+        let mut a = Degrees::new(30.0f32) + Degrees::new(60.0) - Degrees::new(10.0);
+        assert!(a == Degrees::new(80.0));
+        assert!(-a * 2.0 == Degrees::new(-160.0));
+        assert!(a / 4.0 == Degrees::new(20.0));
+        a += Degrees::new(10.0);
+        a *= 2.0;
+        a -= Degrees::new(90.0);
+        a /= 3.0;
+        assert!(a == Degrees::new(30.0));
+        assert!(Degrees::new(10.0) < a && a < Degrees::new(45.0));
+    }
+
+    #[test]
+    fn trigonometry() {
+        assert_abs_diff_eq!(Degrees::new(30.0).sin(), 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(Turns::new(0.5).cos(), -1.0);
+        assert_abs_diff_eq!(Degrees::new(45.0).tan(), 1.0, epsilon = 1e-12);
+        let (sin, cos) = Radians::new(0.3).sin_cos();
+        assert!(sin == 0.3f64.sin() && cos == 0.3f64.cos());
+    }
+
+    #[test]
+    fn wrap() {
+        assert!(Degrees::new(370.0).wrap() == Degrees::new(10.0));
+        assert!(Degrees::new(-90.0).wrap() == Degrees::new(270.0));
+        assert!(Degrees::new(360.0).wrap() == Degrees::new(0.0));
+        assert!(Degrees::new(-1e-20f64).wrap() == Degrees::new(0.0));
+        assert_abs_diff_eq!(Radians::new(-PI / 2.).wrap().value, 1.5 * PI);
+        assert!(Degrees::new(180.0).wrap_signed() == Degrees::new(180.0));
+        assert!(Degrees::new(-180.0).wrap_signed() == Degrees::new(180.0));
+        assert!(Degrees::new(190.0).wrap_signed() == Degrees::new(-170.0));
+        assert!(Turns::new(2.75).wrap_signed() == Turns::new(-0.25));
+    }
+
+    #[test]
+    fn difference() {
+        assert!(Degrees::new(350.0).difference(Degrees::new(10.0)) == Degrees::new(20.0));
+        assert!(Degrees::new(10.0).difference(Degrees::new(350.0)) == Degrees::new(-20.0));
+        assert!(Degrees::new(0.0).difference(Degrees::new(-540.0)) == Degrees::new(180.0));
+        assert_abs_diff_eq!(Radians::new(3.0).difference(Radians::new(-3.0)).value,
+                            2. * PI - 6.,
+                            epsilon = 1e-12);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_conversions() {
+        use unit::{ToDegrees, ToRadians};
+        assert_abs_diff_eq!(ToRadians::radians(&Degrees::new(180.0)).value, PI);
+        assert_abs_diff_eq!(ToRadians::value(&Degrees::new(90.0)), PI / 2.);
+        assert_abs_diff_eq!(ToDegrees::value(&Radians::new(PI)), 180.0, epsilon = 1e-12);
+        assert!(ToDegrees::degrees(&Turns::new(0.5f32)) == Degrees::new(180.0));
     }
 }
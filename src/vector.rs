@@ -1,132 +1,329 @@
 #![allow(dead_code)]
-use num::{Float, Zero, NumCast};
-use typenum::*;
-use generic_array::*;
+use num::{Float, Num, NumCast};
+use num::traits::{AsPrimitive, CheckedAdd, CheckedSub, CheckedMul, CheckedDiv};
+use num::traits::{SaturatingAdd, SaturatingSub, SaturatingMul};
+use num::traits::{WrappingAdd, WrappingSub, WrappingMul};
 use std::ops::{Add, Sub, Mul, Div, Neg, Index, IndexMut};
-use std::mem;
+use std::ops::{AddAssign, SubAssign, MulAssign, DivAssign};
+use std::iter::{Sum, Product};
+use std::error::Error;
+use std::fmt;
+use std::array;
+use std::hash::{Hash, Hasher};
 
-pub type Vector<T, N> = CoreVector<T, N, VectorType>;
+pub type Vector<T, const N: usize> = CoreVector<T, N, VectorType>;
 
-pub type Vec4<T> = Vector<T, U4>;
-pub type Vec3<T> = Vector<T, U3>;
-pub type Vec2<T> = Vector<T, U2>;
+pub type Vec4<T> = Vector<T, 4>;
+pub type Vec3<T> = Vector<T, 3>;
+pub type Vec2<T> = Vector<T, 2>;
 
 pub type Vec4f = Vec4<f32>;
 pub type Vec3f = Vec3<f32>;
 pub type Vec2f = Vec2<f32>;
 
-pub type Normal<T, N> = CoreVector<T, N, NormalType>;
+pub type Normal<T, const N: usize> = CoreVector<T, N, NormalType>;
 
-pub type Normal3<T> = Normal<T, U3>;
+pub type Normal3<T> = Normal<T, 3>;
 pub type Normal3f = Normal3<f32>;
-//
-pub type Point<T, N> = CoreVector<T, N, PointType>;
 
-pub type Point3<T> = Point<T, U3>;
-pub type Point2<T> = Point<T, U2>;
-//
-// pub type Point3f = Point3<f32>;
-// pub type Point2f = Point2<f32>;
+pub type Point<T, const N: usize> = CoreVector<T, N, PointType>;
 
+pub type Point3<T> = Point<T, 3>;
+pub type Point2<T> = Point<T, 2>;
+
+pub type Point3f = Point3<f32>;
+pub type Point2f = Point2<f32>;
+
+/// A displacement or direction. Supports the full set of element wise operations.
 #[derive(PartialEq, Eq, Copy, Debug, Clone)]
 pub struct VectorType;
 
+/// A position. Points can only be combined affinely: `Point - Point = Vector` and
+/// `Point ± Vector = Point`.
+///
+/// ```compile_fail
+/// use rla::vector::*;
+/// let p = Point3f::new(1.0, 2.0, 3.0);
+/// let sum = p + p;
+/// ```
 #[derive(PartialEq, Eq, Copy, Debug, Clone)]
 pub struct PointType;
 
+/// A surface normal. Normals can be scaled, negated and added to other normals, but only
+/// interact with vectors through `dot` and `Vector::reflect`. Transformations have to use
+/// the inverse transpose.
 #[derive(PartialEq, Eq, Copy, Debug, Clone)]
 pub struct NormalType;
 
+/// The element type of a vector, every primitive integer and float. Methods that need a
+/// square root, like `length` or `normalize`, are only available for `Float`s.
+pub trait Scalar: Copy + PartialOrd + Num + NumCast {}
+
+impl<T> Scalar for T where T: Copy + PartialOrd + Num + NumCast {}
+
+/// `true` only for NaN, the only value that is not comparable to itself.
+fn is_nan<T: Scalar>(x: T) -> bool {
+    x.partial_cmp(&x).is_none()
+}
+
+/// The smaller value, like `Float::min` NaN is ignored if the other value is a number.
+fn min<T: Scalar>(a: T, b: T) -> T {
+    if b < a || is_nan(a) { b } else { a }
+}
+
+/// The larger value, like `Float::max` NaN is ignored if the other value is a number.
+fn max<T: Scalar>(a: T, b: T) -> T {
+    if b > a || is_nan(a) { b } else { a }
+}
+
 use std::marker;
-#[derive(PartialEq, Eq, Copy, Debug)]
-pub struct CoreVector<T, N, Type>
-    where N::ArrayType: Copy,
-          T: Float,
-          N: ArrayLength<T>
+/// An `N` dimensional vector, `Type` marks it as a vector, a point or a normal.
+/// ```
+/// use rla::vector::*;
+/// let v: Vector<f32, 5> = Vector::from([1.0, 2.0, 3.0, 4.0, 5.0]);
+/// assert!(v.dot(v) == 55.0);
+/// ```
+#[derive(PartialEq, Eq, Debug)]
+pub struct CoreVector<T, const N: usize, Type>
+    where T: Scalar
 {
-    pub data: GenericArray<T, N>,
+    pub data: [T; N],
     _type: marker::PhantomData<Type>,
 }
 
-impl<T, N, Type> Clone for CoreVector<T, N, Type>
-    where N: ArrayLength<T>,
-          N::ArrayType: Copy,
-          T: Float
+// Implemented by hand, deriving would require `Type: Copy`.
+impl<T, const N: usize, Type> Clone for CoreVector<T, N, Type>
+    where T: Scalar
 {
     fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, const N: usize, Type> Copy for CoreVector<T, N, Type> where T: Scalar {}
+
+impl<T, const N: usize, Type> CoreVector<T, N, Type>
+    where T: Scalar
+{
+    /// Builds a vector from the value of every index.
+    pub(crate) fn from_fn<F>(f: F) -> Self
+        where F: FnMut(usize) -> T
+    {
         CoreVector {
-            data: self.data,
+            data: array::from_fn(f),
             _type: marker::PhantomData,
         }
     }
 }
 
 macro_rules! as_expr { ($e:expr) => {$e} }
+macro_rules! as_ty { ($x: ident, $t: ty) => {$t} }
 macro_rules! impl_op_vec{
-    ($trait_name: ident, $fn_name: ident, $op: tt) => {
-        impl<T, N, Type> $trait_name for CoreVector<T, N, Type>
-            where N::ArrayType: Copy,
-                  N: ArrayLength<T>,
-                  T: Float,
-                  CoreVector<T, N, Type>: Copy
+    ($lhs: ty, $rhs: ty, $output: ty, $trait_name: ident, $fn_name: ident, $op: tt) => {
+        impl<T, const N: usize> $trait_name<CoreVector<T, N, $rhs>> for CoreVector<T, N, $lhs>
+            where T: Scalar
         {
-            type Output = CoreVector<T, N, Type>;
-            fn $fn_name(self, other: Self) -> Self::Output {
-                unsafe {
-                    let mut new_data: GenericArray<T, N> = mem::uninitialized();
-                    let iter = self.data
-                        .iter()
-                        .zip(other.data.iter())
-                        .map(|(a, b)| as_expr!( *a $op *b));
-                    for (index, val) in iter.enumerate() {
-                        new_data[index] = val;
-                    }
-                    Self::from_slice(&new_data)
-                }
+            type Output = CoreVector<T, N, $output>;
+            fn $fn_name(self, other: CoreVector<T, N, $rhs>) -> Self::Output {
+                CoreVector::from_fn(|index| as_expr!(self.data[index] $op other.data[index]))
+            }
+        }
+
+        impl<T, const N: usize> $trait_name<&CoreVector<T, N, $rhs>> for CoreVector<T, N, $lhs>
+            where T: Scalar
+        {
+            type Output = CoreVector<T, N, $output>;
+            fn $fn_name(self, other: &CoreVector<T, N, $rhs>) -> Self::Output {
+                as_expr!(self $op *other)
+            }
+        }
+
+        impl<T, const N: usize> $trait_name<CoreVector<T, N, $rhs>> for &CoreVector<T, N, $lhs>
+            where T: Scalar
+        {
+            type Output = CoreVector<T, N, $output>;
+            fn $fn_name(self, other: CoreVector<T, N, $rhs>) -> Self::Output {
+                as_expr!(*self $op other)
+            }
+        }
+
+        impl<T, const N: usize> $trait_name<&CoreVector<T, N, $rhs>> for &CoreVector<T, N, $lhs>
+            where T: Scalar
+        {
+            type Output = CoreVector<T, N, $output>;
+            fn $fn_name(self, other: &CoreVector<T, N, $rhs>) -> Self::Output {
+                as_expr!(*self $op *other)
+            }
+        }
+    }
+ }
+
+impl_op_vec!(VectorType, VectorType, VectorType, Sub, sub, -);
+impl_op_vec!(VectorType, VectorType, VectorType, Add, add, +);
+impl_op_vec!(VectorType, VectorType, VectorType, Mul, mul, *);
+impl_op_vec!(VectorType, VectorType, VectorType, Div, div, /);
+
+impl_op_vec!(PointType, PointType, VectorType, Sub, sub, -);
+impl_op_vec!(PointType, VectorType, PointType, Add, add, +);
+impl_op_vec!(PointType, VectorType, PointType, Sub, sub, -);
+impl_op_vec!(VectorType, PointType, PointType, Add, add, +);
+
+impl_op_vec!(NormalType, NormalType, NormalType, Add, add, +);
+impl_op_vec!(NormalType, NormalType, NormalType, Sub, sub, -);
+
+/// `lhs op= rhs` for the operators whose output has the type of `lhs`.
+macro_rules! impl_op_assign_vec{
+    ($lhs: ty, $rhs: ty, $trait_name: ident, $fn_name: ident, $op: tt) => {
+        impl<T, const N: usize> $trait_name<CoreVector<T, N, $rhs>> for CoreVector<T, N, $lhs>
+            where T: Scalar
+        {
+            fn $fn_name(&mut self, other: CoreVector<T, N, $rhs>) {
+                *self = as_expr!(*self $op other);
+            }
+        }
+
+        impl<T, const N: usize> $trait_name<&CoreVector<T, N, $rhs>> for CoreVector<T, N, $lhs>
+            where T: Scalar
+        {
+            fn $fn_name(&mut self, other: &CoreVector<T, N, $rhs>) {
+                *self = as_expr!(*self $op *other);
             }
         }
     }
  }
 
-impl_op_vec!(Sub, sub, -);
-impl_op_vec!(Add, add, +);
-impl_op_vec!(Mul, mul, *);
-impl_op_vec!(Div, div, /);
+impl_op_assign_vec!(VectorType, VectorType, SubAssign, sub_assign, -);
+impl_op_assign_vec!(VectorType, VectorType, AddAssign, add_assign, +);
+impl_op_assign_vec!(VectorType, VectorType, MulAssign, mul_assign, *);
+impl_op_assign_vec!(VectorType, VectorType, DivAssign, div_assign, /);
+
+impl_op_assign_vec!(PointType, VectorType, AddAssign, add_assign, +);
+impl_op_assign_vec!(PointType, VectorType, SubAssign, sub_assign, -);
+
+impl_op_assign_vec!(NormalType, NormalType, AddAssign, add_assign, +);
+impl_op_assign_vec!(NormalType, NormalType, SubAssign, sub_assign, -);
 
 macro_rules! impl_op_vec_un{
-    ($trait_name: ident, $fn_name: ident, $op: tt) => {
-        impl<T, N, Type> $trait_name<T> for CoreVector<T, N, Type>
-            where N::ArrayType: Copy,
-                  N: ArrayLength<T>,
-                  T: Float,
-                  CoreVector<T, N, Type>: Copy
+    ($vec_type: ty, $trait_name: ident, $fn_name: ident,
+     $assign_trait: ident, $assign_fn: ident, $op: tt) => {
+        impl<T, const N: usize> $trait_name<T> for CoreVector<T, N, $vec_type>
+            where T: Scalar
         {
-            type Output = CoreVector<T, N, Type>;
+            type Output = CoreVector<T, N, $vec_type>;
             fn $fn_name(self, other: T) -> Self::Output {
-                unsafe {
-                    let mut new_data: GenericArray<T, N> = mem::uninitialized();
-                    let iter = self.data
-                        .iter()
-                        .map(|a| as_expr!( *a $op other));
-                    for (index, val) in iter.enumerate() {
-                        new_data[index] = val;
-                    }
-                    Self::from_slice(&new_data)
-                }
+                Self::from_fn(|index| as_expr!(self.data[index] $op other))
+            }
+        }
+
+        impl<T, const N: usize> $trait_name<T> for &CoreVector<T, N, $vec_type>
+            where T: Scalar
+        {
+            type Output = CoreVector<T, N, $vec_type>;
+            fn $fn_name(self, other: T) -> Self::Output {
+                as_expr!(*self $op other)
+            }
+        }
+
+        impl<T, const N: usize> $assign_trait<T> for CoreVector<T, N, $vec_type>
+            where T: Scalar
+        {
+            fn $assign_fn(&mut self, other: T) {
+                *self = as_expr!(*self $op other);
+            }
+        }
+    }
+ }
+
+impl_op_vec_un!(VectorType, Mul, mul, MulAssign, mul_assign, *);
+impl_op_vec_un!(VectorType, Add, add, AddAssign, add_assign, +);
+impl_op_vec_un!(VectorType, Sub, sub, SubAssign, sub_assign, -);
+impl_op_vec_un!(VectorType, Div, div, DivAssign, div_assign, /);
+
+impl_op_vec_un!(NormalType, Mul, mul, MulAssign, mul_assign, *);
+impl_op_vec_un!(NormalType, Div, div, DivAssign, div_assign, /);
+
+/// `scalar * vector`, only for the primitive types as a generic `T` can not be the left
+/// hand side of a foreign trait.
+macro_rules! impl_scalar_mul_vec{
+    ($vec_type: ty, $($scalar: ty),*) => {$(
+        impl<const N: usize> Mul<CoreVector<$scalar, N, $vec_type>> for $scalar {
+            type Output = CoreVector<$scalar, N, $vec_type>;
+            fn mul(self, v: CoreVector<$scalar, N, $vec_type>) -> Self::Output {
+                v * self
+            }
+        }
+
+        impl<const N: usize> Mul<&CoreVector<$scalar, N, $vec_type>> for $scalar {
+            type Output = CoreVector<$scalar, N, $vec_type>;
+            fn mul(self, v: &CoreVector<$scalar, N, $vec_type>) -> Self::Output {
+                *v * self
+            }
+        }
+    )*}
+ }
+
+impl_scalar_mul_vec!(VectorType, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_scalar_mul_vec!(NormalType, f32, f64);
+
+macro_rules! impl_neg_vec{
+    ($vec_type: ty) => {
+        impl<T, const N: usize> Neg for CoreVector<T, N, $vec_type>
+            where T: Scalar + Neg<Output = T>
+        {
+            type Output = CoreVector<T, N, $vec_type>;
+            fn neg(self) -> Self {
+                Self::from_fn(|index| -self.data[index])
+            }
+        }
+
+        impl<T, const N: usize> Neg for &CoreVector<T, N, $vec_type>
+            where T: Scalar + Neg<Output = T>
+        {
+            type Output = CoreVector<T, N, $vec_type>;
+            fn neg(self) -> Self::Output {
+                -*self
+            }
+        }
+    }
+}
+
+impl_neg_vec!(VectorType);
+impl_neg_vec!(NormalType);
+
+macro_rules! impl_fold_vec{
+    ($vec_type: ty, $trait_name: ident, $fn_name: ident, $init: ident, $op: tt) => {
+        impl<T, const N: usize> $trait_name for CoreVector<T, N, $vec_type>
+            where T: Scalar
+        {
+            fn $fn_name<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(CoreVector::from([T::$init(); N]), |acc, v| as_expr!(acc $op v))
+            }
+        }
+
+        impl<'a, T, const N: usize> $trait_name<&'a Self> for CoreVector<T, N, $vec_type>
+            where T: Scalar
+        {
+            fn $fn_name<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.fold(CoreVector::from([T::$init(); N]), |acc, v| as_expr!(acc $op *v))
             }
         }
     }
  }
 
-impl_op_vec_un!(Mul, mul, *);
-impl_op_vec_un!(Add, add, +);
-impl_op_vec_un!(Sub, sub, -);
-impl_op_vec_un!(Div, div, /);
+impl_fold_vec!(VectorType, Sum, sum, zero, +);
+impl_fold_vec!(VectorType, Product, product, one, *);
+impl_fold_vec!(NormalType, Sum, sum, zero, +);
 
-impl<T, Type> CoreVector<T, U3, Type>
-    where T: Float + Zero,
-          CoreVector<T, U3, Type>: Copy
+impl<T> CoreVector<T, 3, VectorType>
+    where T: Scalar
 {
+    /// Only defined for vectors, the cross product of points or normals is meaningless.
+    ///
+    /// ```compile_fail
+    /// use rla::vector::*;
+    /// let p = Point3f::new(1.0, 2.0, 3.0);
+    /// let c = p.cross(p);
+    /// ```
     pub fn cross(self, other: Self) -> Self {
         Self::new(self.y() * other.z() - self.z() * other.y(),
                   self.z() * other.x() - self.x() * other.z(),
@@ -134,48 +331,18 @@ impl<T, Type> CoreVector<T, U3, Type>
     }
 }
 
-impl<T, N, Type> CoreVector<T, N, Type>
-    where T: Float + Zero,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy,
-          CoreVector<T, N, Type>: Copy
+impl<T, const N: usize, Type> CoreVector<T, N, Type>
+    where T: Scalar
 {
-    /// Builds a `CoreVector<T, N >` from a `CoreVector<T, N-1>` with an additional value.
-    /// # Example
-    /// ```
-    /// use rla::vector::*;
-    /// let v = Vec3f::from_one_less(Vec2f::new(1.0, 2.0), 3.0);
-    /// assert!(v == Vec3f::new(1.0, 2.0, 3.0));
-    /// ```
-    pub fn from_one_less(first: CoreVector<T, Sub1<N>, Type>, val: T) -> Self
-        where N: Sub<B1>,
-              <N as Sub<B1>>::Output: ArrayLength<T>,
-              <<N as Sub<B1>>::Output as ArrayLength<T>>::ArrayType: Copy
-    {
-        unsafe {
-            let mut data: GenericArray<T, N> = mem::uninitialized();
-            for (index, val) in first.data.iter().enumerate() {
-                data[index] = *val;
-            }
-            data[N::to_usize() - 1] = val;
-            Self::from_slice(&data)
-        }
-    }
-
+    /// Panics if the length of `slice` is not `N`.
     pub fn from_slice(slice: &[T]) -> CoreVector<T, N, Type> {
-        CoreVector {
-            data: GenericArray::from_slice(slice),
-            _type: marker::PhantomData,
-        }
+        let mut data = [T::zero(); N];
+        data.copy_from_slice(slice);
+        CoreVector::from(data)
     }
+
     pub fn zero() -> Self {
-        unsafe {
-            let mut data: GenericArray<T, N> = mem::uninitialized();
-            for val in data.iter_mut() {
-                *val = T::zero();
-            }
-            Self::from_slice(&data)
-        }
+        CoreVector::from([T::zero(); N])
     }
 
     pub fn max_value(self) -> T {
@@ -188,8 +355,10 @@ impl<T, N, Type> CoreVector<T, N, Type>
         })
     }
 
-    pub fn dot(self, other: Self) -> T {
-        Iterator::zip(self.data.into_iter(), other.data.into_iter())
+    /// The dot product, also defined between different kinds of vectors, e.g. `n.dot(v)`
+    /// for a normal `n` and a vector `v`.
+    pub fn dot<Other>(self, other: CoreVector<T, N, Other>) -> T {
+        Iterator::zip(self.into_iter(), other)
             .fold(T::zero(), |acc, (x, y)| acc + x * y)
     }
 
@@ -197,113 +366,217 @@ impl<T, N, Type> CoreVector<T, N, Type>
         self.dot(self)
     }
 
-    pub fn length(self) -> T {
-        self.length_sq().sqrt()
+    pub fn distance_sq(self, other: Self) -> T {
+        Iterator::zip(self.into_iter(), other)
+            .fold(T::zero(), |acc, (x, y)| acc + (x - y) * (x - y))
     }
 
-    pub fn project(self, other: Self) -> Self {
-        other * (self.dot(other) / other.length_sq())
+    pub fn map<F, B>(self, mut f: F) -> CoreVector<B, N, Type>
+        where F: FnMut(T) -> B,
+              B: Scalar
+    {
+        CoreVector::from_fn(|index| f(self.data[index]))
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self::from_fn(|index| min(self.data[index], other.data[index]))
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self::from_fn(|index| max(self.data[index], other.data[index]))
     }
 
+    pub fn to_vector(self) -> Vector<T, N> {
+        Vector::from(self.data)
+    }
+
+    pub fn to_point(self) -> Point<T, N> {
+        Point::from(self.data)
+    }
+
+    pub fn to_normal(self) -> Normal<T, N> {
+        Normal::from(self.data)
+    }
+
+    pub fn dim(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize, Type> CoreVector<T, N, Type>
+    where T: Float
+{
+    pub fn length(self) -> T {
+        self.length_sq().sqrt()
+    }
+
+    /// Returns `None` for the zero vector. Tiny and huge vectors whose squared length does not
+    /// fit into `T` are rescaled first.
     pub fn normalize(self) -> Option<Self> {
         let len_sq = self.length_sq();
         if len_sq == T::one() {
             Some(self)
-        } else if len_sq == T::zero() {
-            None
+        } else if len_sq == T::zero() || len_sq.is_infinite() {
+            let max = self.iter().fold(T::zero(), |acc, val| acc.max(val.abs()));
+            if max == T::zero() || max.is_infinite() {
+                None
+            } else {
+                self.map(|val| val / max).normalize()
+            }
         } else {
-            Some(self / len_sq.sqrt())
+            let len = len_sq.sqrt();
+            Some(self.map(|val| val / len))
         }
     }
 
-    pub fn reflect_normal(self, normal: Self) -> Self {
-        let two: T = NumCast::from(2).unwrap();
-        self - normal * normal.dot(self) * two
-    }
-
-    pub fn distance_sq(self, other: Self) -> T {
-        (self - other).length_sq()
-    }
-
     pub fn distance(self, other: Self) -> T {
         self.distance_sq(other).sqrt()
     }
 
-    pub fn map<F, B>(self, f: F) -> CoreVector<B, N, Type>
-        where F: Fn(T) -> B,
-              N: ArrayLength<B>,
-              B: Float,
-              N: Copy,
-              <N as ArrayLength<B>>::ArrayType: Copy,
-              CoreVector<B, N, Type>: Copy
-    {
-        self.into_iter().map(f).collect()
+    pub fn lerp(self, torwards: Self, scale: T) -> Self {
+        Self::from_fn(|index| {
+            let (a, b) = (self.data[index], torwards.data[index]);
+            a + (b - a) * scale
+        })
     }
+}
 
-    pub fn min(self, other: Self) -> Self {
-        Iterator::zip(self.data.into_iter(), other.data.into_iter())
-            .map(|(a, b)| a.min(b))
-            .collect::<Self>()
+/// Element wise `checked_*` operations of vectors, `None` if any element overflows or
+/// divides by zero.
+macro_rules! impl_checked_vec{
+    ($trait_name: ident, $fn_name: ident) => {
+        impl<T, const N: usize> CoreVector<T, N, VectorType>
+            where T: Scalar + $trait_name
+        {
+            pub fn $fn_name(self, other: Self) -> Option<Self> {
+                let mut data = self.data;
+                for (x, y) in data.iter_mut().zip(other.data.iter()) {
+                    *x = x.$fn_name(y)?;
+                }
+                Some(Self::from(data))
+            }
+        }
     }
+ }
 
-    pub fn max(self, other: Self) -> Self {
-        Iterator::zip(self.data.into_iter(), other.data.into_iter())
-            .map(|(a, b)| a.max(b))
-            .collect::<Self>()
-    }
+impl_checked_vec!(CheckedAdd, checked_add);
+impl_checked_vec!(CheckedSub, checked_sub);
+impl_checked_vec!(CheckedMul, checked_mul);
+impl_checked_vec!(CheckedDiv, checked_div);
 
-    pub fn lerp(self, torwards: Self, scale: T) -> Self {
-        self + (torwards - self) * scale
+/// Element wise `saturating_*` and `wrapping_*` operations of vectors.
+macro_rules! impl_overflow_vec{
+    ($trait_name: ident, $fn_name: ident) => {
+        impl<T, const N: usize> CoreVector<T, N, VectorType>
+            where T: Scalar + $trait_name
+        {
+            pub fn $fn_name(self, other: Self) -> Self {
+                Self::from_fn(|index| self.data[index].$fn_name(&other.data[index]))
+            }
+        }
     }
+ }
 
-    pub fn dim(&self) -> usize {
-        N::to_usize()
-    }
+impl_overflow_vec!(SaturatingAdd, saturating_add);
+impl_overflow_vec!(SaturatingSub, saturating_sub);
+impl_overflow_vec!(SaturatingMul, saturating_mul);
+impl_overflow_vec!(WrappingAdd, wrapping_add);
+impl_overflow_vec!(WrappingSub, wrapping_sub);
+impl_overflow_vec!(WrappingMul, wrapping_mul);
 
-    pub fn extend(self, val: T) -> CoreVector<T, Add1<N>, Type>
-        where N: Add<B1>,
-                 <N as Add<B1>>::Output: ArrayLength<T>,
-                 <<N as Add<B1>>::Output as ArrayLength<T>>::ArrayType: Copy,
-                 CoreVector<T, Add1<N>, Type> : Copy
+impl<T, const N: usize, Type> CoreVector<T, N, Type>
+    where T: Scalar
+{
+    /// Converts every element to `B`. Returns `None` if an element can not be represented,
+    /// e.g. a negative number as an unsigned integer or NaN as an integer.
+    /// # Example
+    /// ```
+    /// use rla::vector::*;
+    /// assert!(Vec2::new(3, -1).cast::<f32>() == Some(Vec2f::new(3.0, -1.0)));
+    /// assert!(Vec2::new(3, -1).cast::<u32>().is_none());
+    /// ```
+    pub fn cast<B>(self) -> Option<CoreVector<B, N, Type>>
+        where B: Scalar
     {
-        let mut v = CoreVector::zero();
-        for (index, self_val) in self.into_iter().enumerate() {
-            v.data[index] = self_val;
+        let mut data = [B::zero(); N];
+        for (x, &y) in data.iter_mut().zip(self.data.iter()) {
+            *x = NumCast::from(y)?;
         }
-        let last_index = v.dim() - 1;
-        v.data[last_index] = val;
-        v
+        Some(CoreVector::from(data))
     }
 
-    pub fn truncate(self) -> CoreVector<T, Sub1<N>, Type>
-        where N: Sub<B1>,
-              <N as Sub<B1>>::Output: ArrayLength<T>,
-              <<N as Sub<B1>>::Output as ArrayLength<T>>::ArrayType: Copy,
-              CoreVector<T, Sub1<N>, Type> : Copy
+    /// Converts every element with `as`, large integers lose precision.
+    pub fn as_f32(self) -> CoreVector<f32, N, Type>
+        where T: AsPrimitive<f32>
     {
-        let mut v = CoreVector::zero();
-        for (index, val) in self.into_iter().enumerate() {
-            v.data[index] = val;
-        }
-        v
+        self.map(|x| x.as_())
+    }
+
+    /// Converts every element with `as`, large integers lose precision.
+    pub fn as_f64(self) -> CoreVector<f64, N, Type>
+        where T: AsPrimitive<f64>
+    {
+        self.map(|x| x.as_())
     }
 }
-impl<T, N, Type> Neg for CoreVector<T, N, Type>
-    where T: Float,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy,
-          CoreVector<T, N, Type>: Copy
+
+macro_rules! impl_vec_resize{
+    ($dim: literal, $larger: literal) => {
+        impl<T, Type> CoreVector<T, $dim, Type>
+            where T: Scalar
+        {
+            pub fn extend(self, val: T) -> CoreVector<T, $larger, Type> {
+                CoreVector::<T, $larger, Type>::from_one_less(self, val)
+            }
+        }
+
+        impl<T, Type> CoreVector<T, $larger, Type>
+            where T: Scalar
+        {
+            /// Builds a vector from a vector with one dimension less and an additional
+            /// value.
+            /// # Example
+            /// ```
+            /// use rla::vector::*;
+            /// let v = Vec3f::from_one_less(Vec2f::new(1.0, 2.0), 3.0);
+            /// assert!(v == Vec3f::new(1.0, 2.0, 3.0));
+            /// ```
+            pub fn from_one_less(first: CoreVector<T, $dim, Type>, val: T) -> Self {
+                Self::from_fn(|index| if index < $dim { first.data[index] } else { val })
+            }
+
+            pub fn truncate(self) -> CoreVector<T, $dim, Type> {
+                CoreVector::from_fn(|index| self.data[index])
+            }
+        }
+    };
+ }
+
+impl_vec_resize!(1, 2);
+impl_vec_resize!(2, 3);
+impl_vec_resize!(3, 4);
+impl_vec_resize!(4, 5);
+
+impl<T, const N: usize> CoreVector<T, N, VectorType>
+    where T: Float
 {
-    type Output = CoreVector<T, N, Type>;
-    fn neg(self) -> Self {
-        self * -T::one()
+    pub fn project(self, other: Self) -> Self {
+        other * (self.dot(other) / other.length_sq())
+    }
+
+    pub fn reflect_normal(self, normal: Self) -> Self {
+        let two: T = NumCast::from(2).unwrap();
+        self - normal * normal.dot(self) * two
+    }
+
+    /// Reflects the vector on the surface with the normalized `normal`.
+    pub fn reflect(self, normal: Normal<T, N>) -> Self {
+        self.reflect_normal(normal.to_vector())
     }
 }
 
-impl<T, N, Type> Index<usize> for CoreVector<T, N, Type>
-    where T: Float,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy
+impl<T, const N: usize, Type> Index<usize> for CoreVector<T, N, Type>
+    where T: Scalar
 {
     type Output = T;
 
@@ -311,10 +584,8 @@ impl<T, N, Type> Index<usize> for CoreVector<T, N, Type>
         &self.data[idx]
     }
 }
-impl<T, N, Type> IndexMut<usize> for CoreVector<T, N, Type>
-    where T: Float,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy
+impl<T, const N: usize, Type> IndexMut<usize> for CoreVector<T, N, Type>
+    where T: Scalar
 {
     fn index_mut(&mut self, idx: usize) -> &mut T {
         &mut self.data[idx]
@@ -322,40 +593,194 @@ impl<T, N, Type> IndexMut<usize> for CoreVector<T, N, Type>
 }
 use std::ops::Deref;
 
-impl<T, N, Type> Deref for CoreVector<T, N, Type>
-    where T: Float,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy
+impl<T, const N: usize, Type> Deref for CoreVector<T, N, Type>
+    where T: Scalar
 {
-    type Target = GenericArray<T, N>;
-    fn deref(&self) -> &GenericArray<T, N> {
+    type Target = [T; N];
+    fn deref(&self) -> &[T; N] {
         &self.data
     }
 }
-use std::iter::FromIterator;
-impl<T, N, Type> FromIterator<T> for CoreVector<T, N, Type>
-    where T: Float,
-          N: ArrayLength<T>,
-          N::ArrayType: Copy,
-          CoreVector<T, N, Type>: Copy
+
+impl<T, const N: usize, Type> IntoIterator for CoreVector<T, N, Type>
+    where T: Scalar
 {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        unsafe {
-            let mut data: GenericArray<T, N> = mem::uninitialized();
-            let mut index = 0;
-            for val in iter {
-                data[index] = val;
-                index += 1;
+    type Item = T;
+    type IntoIter = array::IntoIter<T, N>;
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self.data)
+    }
+}
+
+impl<'a, T, const N: usize, Type> IntoIterator for &'a CoreVector<T, N, Type>
+    where T: Scalar
+{
+    type Item = &'a T;
+    type IntoIter = ::std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+/// Integer vectors can be used as keys, e.g. for tiles or voxels.
+impl<T, const N: usize, Type> Hash for CoreVector<T, N, Type>
+    where T: Scalar + Hash
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+impl<T, const N: usize, Type> From<[T; N]> for CoreVector<T, N, Type>
+    where T: Scalar
+{
+    fn from(data: [T; N]) -> Self {
+        CoreVector {
+            data,
+            _type: marker::PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize, Type> From<CoreVector<T, N, Type>> for [T; N]
+    where T: Scalar
+{
+    fn from(v: CoreVector<T, N, Type>) -> Self {
+        v.data
+    }
+}
+
+macro_rules! impl_vec_tuple{
+    ($dim: literal, $($x: ident),*) => {
+        impl<T, Type> From<($(as_ty!($x, T)),*)> for CoreVector<T, $dim, Type>
+            where T: Scalar
+        {
+            fn from(($($x),*): ($(as_ty!($x, T)),*)) -> Self {
+                CoreVector::from([$($x),*])
+            }
+        }
+
+        impl<T, Type> From<CoreVector<T, $dim, Type>> for ($(as_ty!($x, T)),*)
+            where T: Scalar
+        {
+            fn from(v: CoreVector<T, $dim, Type>) -> Self {
+                let [$($x),*] = v.data;
+                ($($x),*)
+            }
+        }
+    };
+ }
+impl_vec_tuple!(2, x, y);
+impl_vec_tuple!(3, x, y, z);
+impl_vec_tuple!(4, x, y, z, w);
+
+/// Conversions from and to the `GenericArray` storage of earlier versions.
+mod generic_array_compat {
+    use generic_array::GenericArray;
+    use typenum::{U1, U2, U3, U4};
+    use super::{CoreVector, Scalar};
+
+    macro_rules! impl_generic_array{
+        ($dim: literal, $len: ty) => {
+            impl<T, Type> From<GenericArray<T, $len>> for CoreVector<T, $dim, Type>
+                where T: Scalar
+            {
+                fn from(data: GenericArray<T, $len>) -> Self {
+                    CoreVector::from_slice(&data)
+                }
             }
-            CoreVector::from_slice(&data)
+
+            impl<T, Type> From<CoreVector<T, $dim, Type>> for GenericArray<T, $len>
+                where T: Scalar
+            {
+                fn from(v: CoreVector<T, $dim, Type>) -> Self {
+                    GenericArray::from_slice(&v.data)
+                }
+            }
+        };
+     }
+
+    impl_generic_array!(1, U1);
+    impl_generic_array!(2, U2);
+    impl_generic_array!(3, U3);
+    impl_generic_array!(4, U4);
+}
+
+/// The error of `CoreVector::try_from_iter` if the iterator does not yield exactly as
+/// many elements as the vector has.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LengthError {
+    TooShort { expected: usize, found: usize },
+    /// The iterator yielded more than `expected` elements.
+    TooLong { expected: usize },
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LengthError::TooShort { expected, found } => {
+                write!(f, "expected {} elements, found {}", expected, found)
+            }
+            LengthError::TooLong { expected } => {
+                write!(f, "expected {} elements, found more", expected)
+            }
+        }
+    }
+}
+
+impl Error for LengthError {}
+
+impl<T, const N: usize, Type> CoreVector<T, N, Type>
+    where T: Scalar
+{
+    /// Collects exactly `N` elements, the iterator is consumed at most one element past
+    /// `N`.
+    /// # Example
+    /// ```
+    /// use rla::vector::*;
+    /// assert!(Vec3f::try_from_iter(vec![1.0, 2.0, 3.0]) == Ok(Vec3f::new(1.0, 2.0, 3.0)));
+    /// assert!(Vec3f::try_from_iter(vec![1.0, 2.0]).is_err());
+    /// ```
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, LengthError>
+        where I: IntoIterator<Item = T>
+    {
+        let mut iter = iter.into_iter();
+        let mut found = 0;
+        let v = Self::from_fn(|index| match iter.next() {
+            Some(val) => {
+                found = index + 1;
+                val
+            }
+            None => T::zero(),
+        });
+        if found < N {
+            Err(LengthError::TooShort {
+                expected: N,
+                found,
+            })
+        } else if iter.next().is_some() {
+            Err(LengthError::TooLong { expected: N })
+        } else {
+            Ok(v)
         }
     }
 }
 
+use std::iter::FromIterator;
+/// Panics if the iterator does not yield exactly `N` elements, see
+/// `CoreVector::try_from_iter`.
+impl<T, const N: usize, Type> FromIterator<T> for CoreVector<T, N, Type>
+    where T: Scalar
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::try_from_iter(iter).unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
 macro_rules! impl_vec_accessor{
-    ($dim: ident, $(( $access: ident, $index: expr ) ),*) => {
+    ($dim: literal, $(( $access: ident, $index: expr ) ),*) => {
         impl<T, Type> CoreVector<T, $dim, Type>
-            where T: Float,
+            where T: Scalar
         {
             $(
                 pub fn $access(&self) -> T {
@@ -366,9 +791,9 @@ macro_rules! impl_vec_accessor{
     };
  }
 macro_rules! impl_vec_accessor_mut{
-    ($dim: ident, $(( $access: ident, $index: expr ) ),*) => {
+    ($dim: literal, $(( $access: ident, $index: expr ) ),*) => {
         impl<T, Type> CoreVector<T, $dim, Type>
-            where T: Float
+            where T: Scalar
         {
             $(
                 pub fn $access(&mut self) -> &mut T {
@@ -379,30 +804,170 @@ macro_rules! impl_vec_accessor_mut{
     };
  }
 
-impl_vec_accessor_mut!(U2, (x_m, 0), (y_m, 1));
-impl_vec_accessor_mut!(U3, (x_m, 0), (y_m, 1), (z_m, 2));
-impl_vec_accessor_mut!(U4, (x_m, 0), (y_m, 1), (z_m, 2), (w_m, 3));
+impl_vec_accessor_mut!(2, (x_m, 0), (y_m, 1));
+impl_vec_accessor_mut!(3, (x_m, 0), (y_m, 1), (z_m, 2));
+impl_vec_accessor_mut!(4, (x_m, 0), (y_m, 1), (z_m, 2), (w_m, 3));
+
+impl_vec_accessor!(2, (x, 0), (y, 1));
+impl_vec_accessor!(3, (x, 0), (y, 1), (z, 2));
+impl_vec_accessor!(4, (x, 0), (y, 1), (z, 2), (w, 3));
+
+macro_rules! impl_vec_swizzle{
+    ($dim: literal, $out: literal, $(( $swizzle: ident, $($index: expr),* ) ),*) => {
+        impl<T, Type> CoreVector<T, $dim, Type>
+            where T: Scalar
+        {
+            $(
+                pub fn $swizzle(&self) -> CoreVector<T, $out, Type> {
+                    CoreVector::from([$(self.data[$index]),*])
+                }
+            )*
+        }
+    };
+ }
 
-impl_vec_accessor!(U2, (x, 0), (y, 1));
-impl_vec_accessor!(U3, (x, 0), (y, 1), (z, 2));
-impl_vec_accessor!(U4, (x, 0), (y, 1), (z, 2), (w, 3));
+// Every swizzle of two, three and four components, e.g. `v.xy()`, `v.zyx()` or `v.xxxx()`.
+impl_vec_swizzle!(2, 2,
+                  (xx, 0, 0), (xy, 0, 1), (yx, 1, 0), (yy, 1, 1));
+impl_vec_swizzle!(2, 3,
+                  (xxx, 0, 0, 0), (xxy, 0, 0, 1), (xyx, 0, 1, 0), (xyy, 0, 1, 1), (yxx, 1, 0, 0),
+                  (yxy, 1, 0, 1), (yyx, 1, 1, 0), (yyy, 1, 1, 1));
+impl_vec_swizzle!(2, 4,
+                  (xxxx, 0, 0, 0, 0), (xxxy, 0, 0, 0, 1), (xxyx, 0, 0, 1, 0), (xxyy, 0, 0, 1, 1),
+                  (xyxx, 0, 1, 0, 0), (xyxy, 0, 1, 0, 1), (xyyx, 0, 1, 1, 0), (xyyy, 0, 1, 1, 1),
+                  (yxxx, 1, 0, 0, 0), (yxxy, 1, 0, 0, 1), (yxyx, 1, 0, 1, 0), (yxyy, 1, 0, 1, 1),
+                  (yyxx, 1, 1, 0, 0), (yyxy, 1, 1, 0, 1), (yyyx, 1, 1, 1, 0), (yyyy, 1, 1, 1, 1));
+impl_vec_swizzle!(3, 2,
+                  (xx, 0, 0), (xy, 0, 1), (xz, 0, 2), (yx, 1, 0), (yy, 1, 1), (yz, 1, 2),
+                  (zx, 2, 0), (zy, 2, 1), (zz, 2, 2));
+impl_vec_swizzle!(3, 3,
+                  (xxx, 0, 0, 0), (xxy, 0, 0, 1), (xxz, 0, 0, 2), (xyx, 0, 1, 0), (xyy, 0, 1, 1),
+                  (xyz, 0, 1, 2), (xzx, 0, 2, 0), (xzy, 0, 2, 1), (xzz, 0, 2, 2), (yxx, 1, 0, 0),
+                  (yxy, 1, 0, 1), (yxz, 1, 0, 2), (yyx, 1, 1, 0), (yyy, 1, 1, 1), (yyz, 1, 1, 2),
+                  (yzx, 1, 2, 0), (yzy, 1, 2, 1), (yzz, 1, 2, 2), (zxx, 2, 0, 0), (zxy, 2, 0, 1),
+                  (zxz, 2, 0, 2), (zyx, 2, 1, 0), (zyy, 2, 1, 1), (zyz, 2, 1, 2), (zzx, 2, 2, 0),
+                  (zzy, 2, 2, 1), (zzz, 2, 2, 2));
+impl_vec_swizzle!(3, 4,
+                  (xxxx, 0, 0, 0, 0), (xxxy, 0, 0, 0, 1), (xxxz, 0, 0, 0, 2), (xxyx, 0, 0, 1, 0),
+                  (xxyy, 0, 0, 1, 1), (xxyz, 0, 0, 1, 2), (xxzx, 0, 0, 2, 0), (xxzy, 0, 0, 2, 1),
+                  (xxzz, 0, 0, 2, 2), (xyxx, 0, 1, 0, 0), (xyxy, 0, 1, 0, 1), (xyxz, 0, 1, 0, 2),
+                  (xyyx, 0, 1, 1, 0), (xyyy, 0, 1, 1, 1), (xyyz, 0, 1, 1, 2), (xyzx, 0, 1, 2, 0),
+                  (xyzy, 0, 1, 2, 1), (xyzz, 0, 1, 2, 2), (xzxx, 0, 2, 0, 0), (xzxy, 0, 2, 0, 1),
+                  (xzxz, 0, 2, 0, 2), (xzyx, 0, 2, 1, 0), (xzyy, 0, 2, 1, 1), (xzyz, 0, 2, 1, 2),
+                  (xzzx, 0, 2, 2, 0), (xzzy, 0, 2, 2, 1), (xzzz, 0, 2, 2, 2), (yxxx, 1, 0, 0, 0),
+                  (yxxy, 1, 0, 0, 1), (yxxz, 1, 0, 0, 2), (yxyx, 1, 0, 1, 0), (yxyy, 1, 0, 1, 1),
+                  (yxyz, 1, 0, 1, 2), (yxzx, 1, 0, 2, 0), (yxzy, 1, 0, 2, 1), (yxzz, 1, 0, 2, 2),
+                  (yyxx, 1, 1, 0, 0), (yyxy, 1, 1, 0, 1), (yyxz, 1, 1, 0, 2), (yyyx, 1, 1, 1, 0),
+                  (yyyy, 1, 1, 1, 1), (yyyz, 1, 1, 1, 2), (yyzx, 1, 1, 2, 0), (yyzy, 1, 1, 2, 1),
+                  (yyzz, 1, 1, 2, 2), (yzxx, 1, 2, 0, 0), (yzxy, 1, 2, 0, 1), (yzxz, 1, 2, 0, 2),
+                  (yzyx, 1, 2, 1, 0), (yzyy, 1, 2, 1, 1), (yzyz, 1, 2, 1, 2), (yzzx, 1, 2, 2, 0),
+                  (yzzy, 1, 2, 2, 1), (yzzz, 1, 2, 2, 2), (zxxx, 2, 0, 0, 0), (zxxy, 2, 0, 0, 1),
+                  (zxxz, 2, 0, 0, 2), (zxyx, 2, 0, 1, 0), (zxyy, 2, 0, 1, 1), (zxyz, 2, 0, 1, 2),
+                  (zxzx, 2, 0, 2, 0), (zxzy, 2, 0, 2, 1), (zxzz, 2, 0, 2, 2), (zyxx, 2, 1, 0, 0),
+                  (zyxy, 2, 1, 0, 1), (zyxz, 2, 1, 0, 2), (zyyx, 2, 1, 1, 0), (zyyy, 2, 1, 1, 1),
+                  (zyyz, 2, 1, 1, 2), (zyzx, 2, 1, 2, 0), (zyzy, 2, 1, 2, 1), (zyzz, 2, 1, 2, 2),
+                  (zzxx, 2, 2, 0, 0), (zzxy, 2, 2, 0, 1), (zzxz, 2, 2, 0, 2), (zzyx, 2, 2, 1, 0),
+                  (zzyy, 2, 2, 1, 1), (zzyz, 2, 2, 1, 2), (zzzx, 2, 2, 2, 0), (zzzy, 2, 2, 2, 1),
+                  (zzzz, 2, 2, 2, 2));
+impl_vec_swizzle!(4, 2,
+                  (xx, 0, 0), (xy, 0, 1), (xz, 0, 2), (xw, 0, 3), (yx, 1, 0), (yy, 1, 1),
+                  (yz, 1, 2), (yw, 1, 3), (zx, 2, 0), (zy, 2, 1), (zz, 2, 2), (zw, 2, 3),
+                  (wx, 3, 0), (wy, 3, 1), (wz, 3, 2), (ww, 3, 3));
+impl_vec_swizzle!(4, 3,
+                  (xxx, 0, 0, 0), (xxy, 0, 0, 1), (xxz, 0, 0, 2), (xxw, 0, 0, 3), (xyx, 0, 1, 0),
+                  (xyy, 0, 1, 1), (xyz, 0, 1, 2), (xyw, 0, 1, 3), (xzx, 0, 2, 0), (xzy, 0, 2, 1),
+                  (xzz, 0, 2, 2), (xzw, 0, 2, 3), (xwx, 0, 3, 0), (xwy, 0, 3, 1), (xwz, 0, 3, 2),
+                  (xww, 0, 3, 3), (yxx, 1, 0, 0), (yxy, 1, 0, 1), (yxz, 1, 0, 2), (yxw, 1, 0, 3),
+                  (yyx, 1, 1, 0), (yyy, 1, 1, 1), (yyz, 1, 1, 2), (yyw, 1, 1, 3), (yzx, 1, 2, 0),
+                  (yzy, 1, 2, 1), (yzz, 1, 2, 2), (yzw, 1, 2, 3), (ywx, 1, 3, 0), (ywy, 1, 3, 1),
+                  (ywz, 1, 3, 2), (yww, 1, 3, 3), (zxx, 2, 0, 0), (zxy, 2, 0, 1), (zxz, 2, 0, 2),
+                  (zxw, 2, 0, 3), (zyx, 2, 1, 0), (zyy, 2, 1, 1), (zyz, 2, 1, 2), (zyw, 2, 1, 3),
+                  (zzx, 2, 2, 0), (zzy, 2, 2, 1), (zzz, 2, 2, 2), (zzw, 2, 2, 3), (zwx, 2, 3, 0),
+                  (zwy, 2, 3, 1), (zwz, 2, 3, 2), (zww, 2, 3, 3), (wxx, 3, 0, 0), (wxy, 3, 0, 1),
+                  (wxz, 3, 0, 2), (wxw, 3, 0, 3), (wyx, 3, 1, 0), (wyy, 3, 1, 1), (wyz, 3, 1, 2),
+                  (wyw, 3, 1, 3), (wzx, 3, 2, 0), (wzy, 3, 2, 1), (wzz, 3, 2, 2), (wzw, 3, 2, 3),
+                  (wwx, 3, 3, 0), (wwy, 3, 3, 1), (wwz, 3, 3, 2), (www, 3, 3, 3));
+impl_vec_swizzle!(4, 4,
+                  (xxxx, 0, 0, 0, 0), (xxxy, 0, 0, 0, 1), (xxxz, 0, 0, 0, 2), (xxxw, 0, 0, 0, 3),
+                  (xxyx, 0, 0, 1, 0), (xxyy, 0, 0, 1, 1), (xxyz, 0, 0, 1, 2), (xxyw, 0, 0, 1, 3),
+                  (xxzx, 0, 0, 2, 0), (xxzy, 0, 0, 2, 1), (xxzz, 0, 0, 2, 2), (xxzw, 0, 0, 2, 3),
+                  (xxwx, 0, 0, 3, 0), (xxwy, 0, 0, 3, 1), (xxwz, 0, 0, 3, 2), (xxww, 0, 0, 3, 3),
+                  (xyxx, 0, 1, 0, 0), (xyxy, 0, 1, 0, 1), (xyxz, 0, 1, 0, 2), (xyxw, 0, 1, 0, 3),
+                  (xyyx, 0, 1, 1, 0), (xyyy, 0, 1, 1, 1), (xyyz, 0, 1, 1, 2), (xyyw, 0, 1, 1, 3),
+                  (xyzx, 0, 1, 2, 0), (xyzy, 0, 1, 2, 1), (xyzz, 0, 1, 2, 2), (xyzw, 0, 1, 2, 3),
+                  (xywx, 0, 1, 3, 0), (xywy, 0, 1, 3, 1), (xywz, 0, 1, 3, 2), (xyww, 0, 1, 3, 3),
+                  (xzxx, 0, 2, 0, 0), (xzxy, 0, 2, 0, 1), (xzxz, 0, 2, 0, 2), (xzxw, 0, 2, 0, 3),
+                  (xzyx, 0, 2, 1, 0), (xzyy, 0, 2, 1, 1), (xzyz, 0, 2, 1, 2), (xzyw, 0, 2, 1, 3),
+                  (xzzx, 0, 2, 2, 0), (xzzy, 0, 2, 2, 1), (xzzz, 0, 2, 2, 2), (xzzw, 0, 2, 2, 3),
+                  (xzwx, 0, 2, 3, 0), (xzwy, 0, 2, 3, 1), (xzwz, 0, 2, 3, 2), (xzww, 0, 2, 3, 3),
+                  (xwxx, 0, 3, 0, 0), (xwxy, 0, 3, 0, 1), (xwxz, 0, 3, 0, 2), (xwxw, 0, 3, 0, 3),
+                  (xwyx, 0, 3, 1, 0), (xwyy, 0, 3, 1, 1), (xwyz, 0, 3, 1, 2), (xwyw, 0, 3, 1, 3),
+                  (xwzx, 0, 3, 2, 0), (xwzy, 0, 3, 2, 1), (xwzz, 0, 3, 2, 2), (xwzw, 0, 3, 2, 3),
+                  (xwwx, 0, 3, 3, 0), (xwwy, 0, 3, 3, 1), (xwwz, 0, 3, 3, 2), (xwww, 0, 3, 3, 3),
+                  (yxxx, 1, 0, 0, 0), (yxxy, 1, 0, 0, 1), (yxxz, 1, 0, 0, 2), (yxxw, 1, 0, 0, 3),
+                  (yxyx, 1, 0, 1, 0), (yxyy, 1, 0, 1, 1), (yxyz, 1, 0, 1, 2), (yxyw, 1, 0, 1, 3),
+                  (yxzx, 1, 0, 2, 0), (yxzy, 1, 0, 2, 1), (yxzz, 1, 0, 2, 2), (yxzw, 1, 0, 2, 3),
+                  (yxwx, 1, 0, 3, 0), (yxwy, 1, 0, 3, 1), (yxwz, 1, 0, 3, 2), (yxww, 1, 0, 3, 3),
+                  (yyxx, 1, 1, 0, 0), (yyxy, 1, 1, 0, 1), (yyxz, 1, 1, 0, 2), (yyxw, 1, 1, 0, 3),
+                  (yyyx, 1, 1, 1, 0), (yyyy, 1, 1, 1, 1), (yyyz, 1, 1, 1, 2), (yyyw, 1, 1, 1, 3),
+                  (yyzx, 1, 1, 2, 0), (yyzy, 1, 1, 2, 1), (yyzz, 1, 1, 2, 2), (yyzw, 1, 1, 2, 3),
+                  (yywx, 1, 1, 3, 0), (yywy, 1, 1, 3, 1), (yywz, 1, 1, 3, 2), (yyww, 1, 1, 3, 3),
+                  (yzxx, 1, 2, 0, 0), (yzxy, 1, 2, 0, 1), (yzxz, 1, 2, 0, 2), (yzxw, 1, 2, 0, 3),
+                  (yzyx, 1, 2, 1, 0), (yzyy, 1, 2, 1, 1), (yzyz, 1, 2, 1, 2), (yzyw, 1, 2, 1, 3),
+                  (yzzx, 1, 2, 2, 0), (yzzy, 1, 2, 2, 1), (yzzz, 1, 2, 2, 2), (yzzw, 1, 2, 2, 3),
+                  (yzwx, 1, 2, 3, 0), (yzwy, 1, 2, 3, 1), (yzwz, 1, 2, 3, 2), (yzww, 1, 2, 3, 3),
+                  (ywxx, 1, 3, 0, 0), (ywxy, 1, 3, 0, 1), (ywxz, 1, 3, 0, 2), (ywxw, 1, 3, 0, 3),
+                  (ywyx, 1, 3, 1, 0), (ywyy, 1, 3, 1, 1), (ywyz, 1, 3, 1, 2), (ywyw, 1, 3, 1, 3),
+                  (ywzx, 1, 3, 2, 0), (ywzy, 1, 3, 2, 1), (ywzz, 1, 3, 2, 2), (ywzw, 1, 3, 2, 3),
+                  (ywwx, 1, 3, 3, 0), (ywwy, 1, 3, 3, 1), (ywwz, 1, 3, 3, 2), (ywww, 1, 3, 3, 3),
+                  (zxxx, 2, 0, 0, 0), (zxxy, 2, 0, 0, 1), (zxxz, 2, 0, 0, 2), (zxxw, 2, 0, 0, 3),
+                  (zxyx, 2, 0, 1, 0), (zxyy, 2, 0, 1, 1), (zxyz, 2, 0, 1, 2), (zxyw, 2, 0, 1, 3),
+                  (zxzx, 2, 0, 2, 0), (zxzy, 2, 0, 2, 1), (zxzz, 2, 0, 2, 2), (zxzw, 2, 0, 2, 3),
+                  (zxwx, 2, 0, 3, 0), (zxwy, 2, 0, 3, 1), (zxwz, 2, 0, 3, 2), (zxww, 2, 0, 3, 3),
+                  (zyxx, 2, 1, 0, 0), (zyxy, 2, 1, 0, 1), (zyxz, 2, 1, 0, 2), (zyxw, 2, 1, 0, 3),
+                  (zyyx, 2, 1, 1, 0), (zyyy, 2, 1, 1, 1), (zyyz, 2, 1, 1, 2), (zyyw, 2, 1, 1, 3),
+                  (zyzx, 2, 1, 2, 0), (zyzy, 2, 1, 2, 1), (zyzz, 2, 1, 2, 2), (zyzw, 2, 1, 2, 3),
+                  (zywx, 2, 1, 3, 0), (zywy, 2, 1, 3, 1), (zywz, 2, 1, 3, 2), (zyww, 2, 1, 3, 3),
+                  (zzxx, 2, 2, 0, 0), (zzxy, 2, 2, 0, 1), (zzxz, 2, 2, 0, 2), (zzxw, 2, 2, 0, 3),
+                  (zzyx, 2, 2, 1, 0), (zzyy, 2, 2, 1, 1), (zzyz, 2, 2, 1, 2), (zzyw, 2, 2, 1, 3),
+                  (zzzx, 2, 2, 2, 0), (zzzy, 2, 2, 2, 1), (zzzz, 2, 2, 2, 2), (zzzw, 2, 2, 2, 3),
+                  (zzwx, 2, 2, 3, 0), (zzwy, 2, 2, 3, 1), (zzwz, 2, 2, 3, 2), (zzww, 2, 2, 3, 3),
+                  (zwxx, 2, 3, 0, 0), (zwxy, 2, 3, 0, 1), (zwxz, 2, 3, 0, 2), (zwxw, 2, 3, 0, 3),
+                  (zwyx, 2, 3, 1, 0), (zwyy, 2, 3, 1, 1), (zwyz, 2, 3, 1, 2), (zwyw, 2, 3, 1, 3),
+                  (zwzx, 2, 3, 2, 0), (zwzy, 2, 3, 2, 1), (zwzz, 2, 3, 2, 2), (zwzw, 2, 3, 2, 3),
+                  (zwwx, 2, 3, 3, 0), (zwwy, 2, 3, 3, 1), (zwwz, 2, 3, 3, 2), (zwww, 2, 3, 3, 3),
+                  (wxxx, 3, 0, 0, 0), (wxxy, 3, 0, 0, 1), (wxxz, 3, 0, 0, 2), (wxxw, 3, 0, 0, 3),
+                  (wxyx, 3, 0, 1, 0), (wxyy, 3, 0, 1, 1), (wxyz, 3, 0, 1, 2), (wxyw, 3, 0, 1, 3),
+                  (wxzx, 3, 0, 2, 0), (wxzy, 3, 0, 2, 1), (wxzz, 3, 0, 2, 2), (wxzw, 3, 0, 2, 3),
+                  (wxwx, 3, 0, 3, 0), (wxwy, 3, 0, 3, 1), (wxwz, 3, 0, 3, 2), (wxww, 3, 0, 3, 3),
+                  (wyxx, 3, 1, 0, 0), (wyxy, 3, 1, 0, 1), (wyxz, 3, 1, 0, 2), (wyxw, 3, 1, 0, 3),
+                  (wyyx, 3, 1, 1, 0), (wyyy, 3, 1, 1, 1), (wyyz, 3, 1, 1, 2), (wyyw, 3, 1, 1, 3),
+                  (wyzx, 3, 1, 2, 0), (wyzy, 3, 1, 2, 1), (wyzz, 3, 1, 2, 2), (wyzw, 3, 1, 2, 3),
+                  (wywx, 3, 1, 3, 0), (wywy, 3, 1, 3, 1), (wywz, 3, 1, 3, 2), (wyww, 3, 1, 3, 3),
+                  (wzxx, 3, 2, 0, 0), (wzxy, 3, 2, 0, 1), (wzxz, 3, 2, 0, 2), (wzxw, 3, 2, 0, 3),
+                  (wzyx, 3, 2, 1, 0), (wzyy, 3, 2, 1, 1), (wzyz, 3, 2, 1, 2), (wzyw, 3, 2, 1, 3),
+                  (wzzx, 3, 2, 2, 0), (wzzy, 3, 2, 2, 1), (wzzz, 3, 2, 2, 2), (wzzw, 3, 2, 2, 3),
+                  (wzwx, 3, 2, 3, 0), (wzwy, 3, 2, 3, 1), (wzwz, 3, 2, 3, 2), (wzww, 3, 2, 3, 3),
+                  (wwxx, 3, 3, 0, 0), (wwxy, 3, 3, 0, 1), (wwxz, 3, 3, 0, 2), (wwxw, 3, 3, 0, 3),
+                  (wwyx, 3, 3, 1, 0), (wwyy, 3, 3, 1, 1), (wwyz, 3, 3, 1, 2), (wwyw, 3, 3, 1, 3),
+                  (wwzx, 3, 3, 2, 0), (wwzy, 3, 3, 2, 1), (wwzz, 3, 3, 2, 2), (wwzw, 3, 3, 2, 3),
+                  (wwwx, 3, 3, 3, 0), (wwwy, 3, 3, 3, 1), (wwwz, 3, 3, 3, 2), (wwww, 3, 3, 3, 3));
 
 macro_rules! impl_vec_new{
-    ($dim: ident, $( $x: ident),*) => {
+    ($dim: literal, $( $x: ident),*) => {
         impl<T, Type> CoreVector<T, $dim, Type>
-            where T: Float + Zero,
-                  CoreVector<T, $dim, Type>: Copy
+            where T: Scalar
         {
             pub fn new($($x : T), *) -> Self {
-                Self::from_slice(&[$($x),*])
+                Self::from([$($x),*])
             }
         }
     };
  }
 
-impl_vec_new!(U2, x, y);
-impl_vec_new!(U3, x, y, z);
-impl_vec_new!(U4, x, y, z, w);
+impl_vec_new!(2, x, y);
+impl_vec_new!(3, x, y, z);
+impl_vec_new!(4, x, y, z, w);
 #[cfg(test)]
 mod test {
    use vector::*;
@@ -441,14 +1006,41 @@ mod test {
    fn distance_vec() {
        let v1 = Vec2f::from_slice(&[0.0, 2.0]);
        let v2 = Vec2f::from_slice(&[0.0, 10.0]);
-       assert!(v1.distance(v2) == 8.0);
-       assert!(v1.length_sq() == 4.0);
-       assert!(v1.length() == 2.0);
-       assert!(v2.normalize().unwrap() == Vec2f::from_slice(&[0.0, 1.0]));
+       assert_abs_diff_eq!(v1.distance(v2), 8.0);
+       assert_abs_diff_eq!(v1.length_sq(), 4.0);
+       assert_abs_diff_eq!(v1.length(), 2.0);
+       assert_approx_eq!(v2.normalize().unwrap(), Vec2f::from_slice(&[0.0, 1.0]));
        let n = Vec2f::from_slice(&[0.0, 1.0]);
        let reflect_v1 = Vec2f::from_slice(&[1.0, -1.0]);
-       assert!(reflect_v1.reflect_normal(n) == Vec2f::from_slice(&[1.0, 1.0]));
+       assert_approx_eq!(reflect_v1.reflect_normal(n), Vec2f::from_slice(&[1.0, 1.0]));
        Vec3f::from_one_less(v1, 1.0);
+       // The squared lengths under- and overflow.
+       assert!(Vec2f::new(1e-30, 0.0).normalize().unwrap() == Vec2f::new(1.0, 0.0));
+       assert_approx_eq!(Vec2f::new(3e30, -4e30).normalize().unwrap(), Vec2f::new(0.6, -0.8));
+       assert!(Vec2f::zero().normalize().is_none());
+   }
+
+   #[test]
+   fn affine() {
+       let p = Point3f::new(1., 2., 3.);
+       let q = Point3f::new(4., 6., 3.);
+       let v: Vec3f = q - p;
+       assert!(v == Vec3f::new(3., 4., 0.));
+       assert!(p + v == q);
+       assert!(q - v == p);
+       assert!(p.distance(q) == 5.);
+       assert!(p.lerp(q, 0.5) == Point3f::new(2.5, 4., 3.));
+       assert!(p.to_vector().to_point() == p);
+   }
+
+   #[test]
+   fn normal() {
+       let n = Normal3f::new(0., 1., 0.);
+       let v = Vec3f::new(1., -1., 0.);
+       assert!(n.dot(v) == -1.);
+       assert!(v.reflect(n) == Vec3f::new(1., 1., 0.));
+       assert!(-n == Normal3f::new(0., -1., 0.));
+       assert!((n * 2.).normalize().unwrap() == n);
    }
 
    #[test]
@@ -457,4 +1049,150 @@ mod test {
        let v2 = v.extend(1.);
        assert!(v2 == Vec3f::new( 1., 1., 1. ));
    }
+
+   #[test]
+   fn swizzle() {
+       let v = Vec4f::new(1., 2., 3., 4.);
+       assert!(v.xy() == Vec2f::new(1., 2.));
+       assert!(v.wzyx() == Vec4f::new(4., 3., 2., 1.));
+       assert!(v.xxxx() == Vec4f::new(1., 1., 1., 1.));
+       assert!(v.xyz().zyx() == Vec3f::new(3., 2., 1.));
+       assert!(Vec2f::new(1., 2.).yxy() == Vec3f::new(2., 1., 2.));
+       let p = Point3f::new(1., 2., 3.);
+       let q: Point2f = p.xz();
+       assert!(q == Point2f::new(1., 3.));
+   }
+
+   #[test]
+   fn from_iter() {
+       let v: Vec3f = vec![1., 2., 3.].into_iter().collect();
+       assert!(v == Vec3f::new(1., 2., 3.));
+       assert!(Vec3f::try_from_iter(vec![1., 2.]) ==
+               Err(LengthError::TooShort { expected: 3, found: 2 }));
+       assert!(Vec2f::try_from_iter((0..).map(|i| i as f32)) ==
+               Err(LengthError::TooLong { expected: 2 }));
+       assert!(Vec4f::zero() == Vec4f::new(0., 0., 0., 0.));
+   }
+
+   #[test]
+   #[should_panic]
+   fn from_iter_too_long() {
+       let _: Vec2f = vec![1., 2., 3.].into_iter().collect();
+   }
+
+   #[test]
+   #[should_panic]
+   fn from_slice_too_short() {
+       Vec3f::from_slice(&[1., 2.]);
+   }
+
+   #[test]
+   fn conversions() {
+       let v = Vec3f::from([1., 2., 3.]);
+       assert!(v == Vec3f::new(1., 2., 3.));
+       assert!(<[f32; 3]>::from(v) == [1., 2., 3.]);
+       assert!(Point2f::from((1., 2.)) == Point2f::new(1., 2.));
+       assert!(<(f32, f32, f32, f32)>::from(Vec4f::new(1., 2., 3., 4.)) == (1., 2., 3., 4.));
+       let v5: Vector<f32, 5> = v.extend(4.).extend(5.);
+       assert!(v5.data == [1., 2., 3., 4., 5.]);
+       assert!(v5.truncate().truncate() == v);
+       assert!(v.into_iter().sum::<f32>() == 6.);
+
+       use generic_array::GenericArray;
+       use typenum::U3;
+       let array: GenericArray<f32, U3> = v.into();
+       assert!(*array == [1., 2., 3.]);
+       assert!(Vec3f::from(array) == v);
+   }
+
+   #[test]
+   fn compound_ops() {
+       let mut p = Point3f::new(1., 2., 3.);
+       let v = Vec3f::new(1., 1., 1.);
+       p += v * 0.5;
+       assert!(p == Point3f::new(1.5, 2.5, 3.5));
+       p -= &v;
+       assert!(p == Point3f::new(0.5, 1.5, 2.5));
+       let (p_ref, v_ref) = (&p, &v);
+       assert!(p_ref - v_ref == p - v);
+       assert!(p_ref + v == v_ref + p);
+
+       let mut w = Vec3f::new(2., 4., 6.);
+       w *= 0.5;
+       w /= Vec3f::new(1., 2., 3.);
+       w += 1.;
+       w -= &v;
+       assert!(w == v);
+       let w_ref = &w;
+       assert!(2. * w_ref == w_ref + w_ref);
+       assert!(-w_ref == w * -1.);
+       assert!(w_ref * 2. == w + w_ref);
+       assert!(2.0f64 * Vec2::new(1.0f64, 2.0) == Vec2::new(2., 4.));
+       let mut n = Normal3f::new(0., 1., 0.);
+       n *= 2.;
+       n += Normal3f::new(1., 0., 0.);
+       assert!(0.5 * n == Normal3f::new(0.5, 1., 0.));
+
+       let vs = [Vec2f::new(1., 2.), Vec2f::new(3., 4.), Vec2f::new(5., 6.)];
+       assert!(vs.iter().sum::<Vec2f>() == Vec2f::new(9., 12.));
+       assert!(vs.iter().cloned().product::<Vec2f>() == Vec2f::new(15., 48.));
+       assert!(Vec::<Vec2f>::new().into_iter().sum::<Vec2f>() == Vec2f::zero());
+   }
+
+   #[test]
+   fn integers() {
+       let a = Vec2::new(3i32, -4);
+       let b = Vec2::new(1, 2);
+       assert!(a + b == Vec2::new(4, -2));
+       assert!(a - b == Vec2::new(2, -6));
+       assert!(a * b == Vec2::new(3, -8));
+       assert!(a / 2 == Vec2::new(1, -2));
+       assert!(2 * a == Vec2::new(6, -8));
+       assert!(-a == Vec2::new(-3, 4));
+       assert!(a.dot(b) == -5);
+       assert!(a.length_sq() == 25);
+       assert!(a.min(b) == Vec2::new(1, -4));
+       assert!(a.max(b) == Vec2::new(3, 2));
+       assert!(a[1] == -4);
+       let p = Point3::new(1u32, 2, 3) + Vec3::new(1, 1, 1);
+       assert!(p == Point3::new(2, 3, 4));
+       assert!(Vec3::new(1i64, 0, 0).cross(Vec3::new(0, 1, 0)) == Vec3::new(0, 0, 1));
+       assert!([a, b].iter().sum::<Vec2<i32>>() == Vec2::new(4, -2));
+
+       let mut tiles = ::std::collections::HashSet::new();
+       tiles.insert(Vec2::new(1i32, 2));
+       assert!(tiles.contains(&Vec2::new(1, 2)));
+   }
+
+   #[test]
+   fn overflow() {
+       let max = Vec2::new(u8::MAX, 1);
+       let one = Vec2::new(1u8, 1);
+       assert!(max.checked_add(one).is_none());
+       assert!(one.checked_add(one) == Some(Vec2::new(2, 2)));
+       assert!(Vec2::new(0u8, 1).checked_sub(one).is_none());
+       assert!(max.checked_mul(Vec2::new(2, 2)).is_none());
+       assert!(one.checked_div(Vec2::new(1, 0)).is_none());
+       assert!(max.saturating_add(one) == Vec2::new(u8::MAX, 2));
+       assert!(Vec2::new(0u8, 3).saturating_sub(one) == Vec2::new(0, 2));
+       assert!(max.saturating_mul(Vec2::new(2, 2)) == Vec2::new(u8::MAX, 2));
+       assert!(max.wrapping_add(one) == Vec2::new(0, 2));
+       assert!(Vec2::new(0u8, 3).wrapping_sub(one) == Vec2::new(u8::MAX, 2));
+       assert!(max.wrapping_mul(Vec2::new(2, 2)) == Vec2::new(254, 2));
+   }
+
+   #[test]
+   fn casts() {
+       let texel = Vec2::new(3u32, 7);
+       assert!(texel.as_f32() == Vec2f::new(3., 7.));
+       assert!(texel.as_f64() / 2.0 == Vec2::new(1.5, 3.5));
+       assert!(Vec3f::new(1.5, -2.0, 3.9).cast::<i32>() == Some(Vec3::new(1, -2, 3)));
+       assert!(Vec2f::new(1.0, f32::NAN).cast::<i32>().is_none());
+       assert!(Vec2::new(300i32, 0).cast::<u8>().is_none());
+       assert!(Point2::new(-1i64, 5).cast::<i8>() == Some(Point2::new(-1, 5)));
+       // NaN is ignored like in `Float::min` and `Float::max`.
+       let nan = Vec2f::new(f32::NAN, 1.);
+       assert!(nan.min(Vec2f::new(2., 2.)) == Vec2f::new(2., 1.));
+       assert!(Vec2f::new(2., 2.).max(nan) == Vec2f::new(2., 2.));
+   }
 }